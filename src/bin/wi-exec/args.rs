@@ -0,0 +1,144 @@
+use std :: { fmt, str::FromStr };
+
+// A minimal command line lexer. Options are written as --name, --name=value, --name value, -n, -nvalue or -n value;
+// everything else (including a lone "-", and anything following "--") is a positional argument. Whether an option takes
+// a value is decided by the command: it calls value() right after matching the option.
+pub struct Parser {
+    args:       Vec<String>,
+    position:   usize,
+    inline:     Option<String>,
+    last:       String,
+    positional: bool,
+}
+
+#[derive(PartialEq, Eq, Debug)]
+pub enum Arg<'a> {
+    Long(&'a str),
+    Short(char),
+    Value(String),
+}
+
+impl Parser {
+    pub fn new(args: Vec<String>) -> Self {
+        Self { args, position: 0, inline: None, last: String::new(), positional: false }
+    }
+
+    pub fn next(&mut self) -> Result<Option<Arg<'_>>, ArgError> {
+        // An option's attached value must have been consumed by the command, otherwise the option doesn't take one
+        if let Some(value) = self.inline.take() {
+            return Err(ArgError::UnexpectedValue(self.last.clone(), value));
+        }
+
+        let arg = match self.args.get(self.position) {
+            Some(arg) => arg.clone(),
+            None => return Ok(None)
+        };
+        self.position += 1;
+
+        if self.positional || arg == "-" || !arg.starts_with('-') {
+            return Ok(Some(Arg::Value(arg)));
+        }
+
+        if arg == "--" {
+            self.positional = true;
+            return self.next();
+        }
+
+        if let Some(long) = arg.strip_prefix("--") {
+            let (name, value) = match long.find('=') {
+                Some(i) => (&long[..i], Some(long[i + 1..].to_string())),
+                None => (long, None)
+            };
+
+            self.last = format!("--{}", name);
+            self.inline = value;
+
+            return Ok(Some(Arg::Long(&self.last[2..])));
+        }
+
+        let mut chars = arg[1..].chars();
+        let short = chars.next().unwrap_or('-');
+        let rest = chars.as_str();
+
+        self.last = format!("-{}", short);
+        self.inline = if rest.is_empty() { None } else { Some(rest.to_string()) };
+
+        Ok(Some(Arg::Short(short)))
+    }
+
+    // The value of the option that was just returned by next()
+    pub fn value(&mut self) -> Result<String, ArgError> {
+        if let Some(value) = self.inline.take() {
+            return Ok(value);
+        }
+
+        match self.args.get(self.position) {
+            Some(value) if !value.starts_with('-') || value == "-" => {
+                self.position += 1;
+                Ok(value.clone())
+            },
+            _ => Err(ArgError::MissingValue(self.last.clone()))
+        }
+    }
+
+    pub fn parse<T: FromStr>(&mut self) -> Result<T, ArgError> where T::Err: fmt::Display {
+        let value = self.value()?;
+
+        match value.parse() {
+            Ok(parsed) => Ok(parsed),
+            Err(err) => Err(ArgError::InvalidValue(self.last.clone(), value, err.to_string()))
+        }
+    }
+
+    // For values that need validation beyond what FromStr provides
+    pub fn invalid(&self, value: &str, reason: &str) -> ArgError {
+        ArgError::InvalidValue(self.last.clone(), value.to_string(), reason.to_string())
+    }
+}
+
+impl<'a> Arg<'a> {
+    pub fn unexpected(self) -> ArgError {
+        match self {
+            Arg::Long(name)  => ArgError::Unknown(format!("--{}", name)),
+            Arg::Short(name) => ArgError::Unknown(format!("-{}", name)),
+            Arg::Value(value) => ArgError::Unexpected(value),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug)]
+pub enum ArgError {
+    UnknownCommand(String),
+    Unknown(String),
+    Unexpected(String),
+    MissingValue(String),
+    UnexpectedValue(String, String),
+    InvalidValue(String, String, String),
+    Missing(&'static str),
+    Conflict(&'static str, &'static str),
+}
+
+impl fmt::Display for ArgError {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArgError::UnknownCommand(command) =>
+                write!(formatter, "unknown command '{}'", command),
+            ArgError::Unknown(option) =>
+                write!(formatter, "unknown option '{}'", option),
+            ArgError::Unexpected(value) =>
+                write!(formatter, "unexpected argument '{}'", value),
+            ArgError::MissingValue(option) =>
+                write!(formatter, "option '{}' requires a value", option),
+            ArgError::UnexpectedValue(option, value) =>
+                write!(formatter, "option '{}' does not take a value (got '{}')", option, value),
+            ArgError::InvalidValue(option, value, reason) =>
+                write!(formatter, "invalid value '{}' for '{}': {}", value, option, reason),
+            ArgError::Missing(what) =>
+                write!(formatter, "missing {}", what),
+            ArgError::Conflict(a, b) =>
+                write!(formatter, "{} can't be combined with {}", a, b),
+        }
+    }
+}
+
+impl std::error::Error for ArgError { }
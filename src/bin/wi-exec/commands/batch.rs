@@ -0,0 +1,286 @@
+use crate :: {
+    args :: { ArgError, Parser },
+    commands :: { Error, PuzzleArg, Status, percentile },
+    report :: { OutputFormat, Report, milliseconds, string },
+};
+
+use waffle_iron :: parse_puzzle;
+
+use std :: {
+    collections :: BTreeMap,
+    fs,
+    io          :: { self, BufWriter, Read, Write },
+    sync        :: { mpsc, atomic :: { AtomicUsize, Ordering } },
+    thread,
+    time        :: { Duration, Instant },
+};
+
+// Runs a command over every puzzle of a file (or standard input) instead of a single puzzle argument. The input holds
+// one puzzle per line; blank lines and lines starting with # or ; are skipped. Results are printed one line per puzzle,
+// in the same order as the input regardless of how many jobs are used, followed by a summary on standard error.
+pub struct Batch {
+    command:    &'static str,
+    input:      Option<String>,
+    jobs:       usize,
+    quiet:      bool,
+    format:     OutputFormat,
+}
+
+struct Record {
+    line:       usize,
+    report:     Report,
+    parsed:     bool,
+}
+
+impl Batch {
+    pub fn new(command: &'static str) -> Self {
+        Self { command, input: None, jobs: 1, quiet: false, format: OutputFormat::Text }
+    }
+
+    pub fn input(&mut self, parser: &mut Parser) -> Result<(), Error> {
+        self.input = Some(parser.value()?);
+        Ok(())
+    }
+
+    // 0 uses one job per available CPU
+    pub fn jobs(&mut self, parser: &mut Parser) -> Result<(), Error> {
+        self.jobs = match parser.parse()? {
+            0 => thread::available_parallelism().map_or(1, |jobs| jobs.get()),
+            jobs => jobs
+        };
+        Ok(())
+    }
+
+    // Only prints the summary
+    pub fn quiet(&mut self, quiet: bool) -> &mut Self {
+        self.quiet = quiet;
+
+        self
+    }
+
+    pub fn format(&mut self, format: OutputFormat) -> &mut Self {
+        self.format = format;
+
+        self
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.input.is_some()
+    }
+
+    // The task fills in a report for each puzzle, while describe summarizes a successful report for the text format
+    pub fn run<F, T>(&self, puzzle: &PuzzleArg, task: F, describe: T) -> Result<Status, Error>
+    where F: Fn(&[u8; 81]) -> Report + Sync, T: Fn(&Report) -> String {
+        let input = match &self.input {
+            Some(input) => input,
+            None => return Err(ArgError::Missing("--input").into())
+        };
+
+        if puzzle.is_some() {
+            return Err(ArgError::Conflict("--input", "a puzzle argument").into());
+        }
+
+        let contents = read(input)?;
+        let lines = lines(&contents);
+
+        let next = AtomicUsize::new(0);
+        let (sender, receiver) = mpsc::channel();
+        let mut summary = Summary::default();
+        let start = Instant::now();
+
+        let stdout = io::stdout();
+        let mut output = BufWriter::new(stdout.lock());
+
+        if self.format == OutputFormat::Csv && !self.quiet {
+            writeln!(output, "{}", Report::CSV_HEADER).map_err(|err| Error::Io(String::from("stdout"), err))?;
+        }
+
+        thread::scope(|scope| -> Result<(), Error> {
+            for _ in 0..std::cmp::max(self.jobs, 1) {
+                let (sender, next, lines, task, command) = (sender.clone(), &next, &lines, &task, self.command);
+
+                scope.spawn(move || loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    let &(line, text) = match lines.get(i) {
+                        Some(line) => line,
+                        None => break
+                    };
+
+                    if sender.send((i, process(command, line, text, task))).is_err() {
+                        break;
+                    }
+                });
+            }
+            drop(sender);
+
+            // Records arrive in whichever order the jobs finish them, and are held back until it's their turn
+            let mut pending = BTreeMap::new();
+            let mut expected = 0;
+
+            for (i, record) in receiver {
+                pending.insert(i, record);
+
+                while let Some(record) = pending.remove(&expected) {
+                    if !self.quiet {
+                        self.write(&mut output, &record.report, &describe).map_err(|err| Error::Io(String::from("stdout"), err))?;
+                    }
+                    summary.add(record);
+                    expected += 1;
+                }
+            }
+
+            Ok(())
+        })?;
+
+        output.flush().map_err(|err| Error::Io(String::from("stdout"), err))?;
+        match self.format {
+            OutputFormat::Json => summary.print_json(start.elapsed(), self.jobs),
+            _ => summary.print(start.elapsed(), self.jobs)
+        }
+
+        match summary.failures.is_empty() {
+            true  => Ok(Status::Success),
+            false => Ok(Status::Rejected)
+        }
+    }
+
+    fn write<W: Write, T>(&self, output: &mut W, report: &Report, describe: &T) -> io::Result<()>
+    where T: Fn(&Report) -> String {
+        match (self.format, &report.error) {
+            (OutputFormat::Json, _)     => writeln!(output, "{}", report.to_json()),
+            (OutputFormat::Csv, _)      => writeln!(output, "{}", report.to_csv()),
+            (_, Some(error))            => writeln!(output, "{} error: {}", report.puzzle, error),
+            (_, None)                   => writeln!(output, "{} {}", report.puzzle, describe(report)),
+        }
+    }
+}
+
+// A file, or standard input for -
+pub fn read(input: &str) -> Result<String, Error> {
+    match input {
+        "-" => {
+            let mut text = String::new();
+            io::stdin().read_to_string(&mut text).map_err(|err| Error::Io(String::from("stdin"), err))?;
+            Ok(text)
+        },
+        path => fs::read_to_string(path).map_err(|err| Error::Io(path.to_string(), err))
+    }
+}
+
+// Numbered lines holding puzzles, leaving out blank lines and comments
+pub fn lines(contents: &str) -> Vec<(usize, &str)> {
+    contents.lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !(line.is_empty() || line.starts_with('#') || line.starts_with(';')))
+        .collect()
+}
+
+fn process<F>(command: &'static str, line: usize, text: &str, task: &F) -> Record where F: Fn(&[u8; 81]) -> Report {
+    match parse_puzzle(text) {
+        Ok(puzzle) => {
+            let time = Instant::now();
+            let mut report = task(&puzzle);
+            report.time(time.elapsed());
+
+            Record { line, report, parsed: true }
+        },
+        Err(err) => {
+            let mut report = Report::from_text(command, text);
+            report.error(format!("invalid puzzle: {}", err));
+
+            Record { line, report, parsed: false }
+        }
+    }
+}
+
+#[derive(Default)]
+struct Summary {
+    count:      usize,
+    failures:   Vec<(usize, String)>,
+    times:      Vec<Duration>,
+}
+
+impl Summary {
+    // Only the first few failures are listed, the rest are counted
+    const FAILURES_SHOWN: usize = 10;
+
+    fn add(&mut self, record: Record) {
+        self.count += 1;
+
+        if record.parsed {
+            self.times.push(record.report.time);
+        }
+
+        if let Some(error) = record.report.error {
+            self.failures.push((record.line, error));
+        }
+    }
+
+    fn print(&mut self, elapsed: Duration, jobs: usize) {
+        self.times.sort();
+        let total: Duration = self.times.iter().sum();
+
+        eprintln!();
+        eprintln!("Puzzles: {}, Succeeded: {}, Failed: {}", self.count, self.count - self.failures.len(), self.failures.len());
+        eprintln!("Time: {:.2?} elapsed, {:.2?} spent solving, {} job(s)", elapsed, total, jobs);
+
+        if !self.times.is_empty() {
+            eprintln!(
+                "Per puzzle: mean {:.2?}, p50 {:.2?}, p90 {:.2?}, p99 {:.2?}, max {:.2?}",
+                self.mean(),
+                percentile(&self.times, 50),
+                percentile(&self.times, 90),
+                percentile(&self.times, 99),
+                self.times[self.times.len() - 1]
+            );
+        }
+
+        if !self.failures.is_empty() {
+            eprintln!("Failures:");
+            for (line, reason) in self.failures.iter().take(Self::FAILURES_SHOWN) {
+                eprintln!("  line {}: {}", line, reason);
+            }
+
+            if self.failures.len() > Self::FAILURES_SHOWN {
+                eprintln!("  ... and {} more", self.failures.len() - Self::FAILURES_SHOWN);
+            }
+        }
+    }
+
+    // Unlike the text summary every failure is listed
+    fn print_json(&mut self, elapsed: Duration, jobs: usize) {
+        self.times.sort();
+
+        let failures: Vec<String> = self.failures.iter()
+            .map(|(line, error)| format!("{{\"line\":{},\"error\":{}}}", line, string(error)))
+            .collect();
+
+        let timing = |time: Option<Duration>| time.map_or(String::from("null"), milliseconds);
+        let any = !self.times.is_empty();
+
+        eprintln!(
+            "{{\"puzzles\":{},\"succeeded\":{},\"failed\":{},\"jobs\":{},\"elapsed_ms\":{},\"total_ms\":{},\
+             \"mean_ms\":{},\"p50_ms\":{},\"p90_ms\":{},\"p99_ms\":{},\"max_ms\":{},\"failures\":[{}]}}",
+            self.count,
+            self.count - self.failures.len(),
+            self.failures.len(),
+            jobs,
+            milliseconds(elapsed),
+            milliseconds(self.times.iter().sum()),
+            timing(Some(self.mean()).filter(|_| any)),
+            timing(Some(percentile(&self.times, 50)).filter(|_| any)),
+            timing(Some(percentile(&self.times, 90)).filter(|_| any)),
+            timing(Some(percentile(&self.times, 99)).filter(|_| any)),
+            timing(self.times.last().copied()),
+            failures.join(",")
+        );
+    }
+
+    fn mean(&self) -> Duration {
+        match self.times.len() {
+            0 => Duration::default(),
+            n => self.times.iter().sum::<Duration>() / n as u32
+        }
+    }
+}
@@ -0,0 +1,279 @@
+use crate :: {
+    args :: { Arg, ArgError, Parser },
+    commands :: { Error, Status, PUZZLES, batch, percentile },
+    report   :: { OutputFormat, Report },
+};
+
+use waffle_iron :: { Generator, Solver, parse_puzzle, output :: SolverOutput };
+
+use std :: {
+    collections :: BTreeMap,
+    time        :: { Duration, Instant },
+};
+
+pub const HELP: &str = "\
+Times the solver against bundled collections of puzzles, and the generator against newly generated ones.
+
+Usage: wi-exec bench [OPTIONS]
+
+Options:
+  -c, --corpus <NAME>   Collection of puzzles to solve, can be repeated [default: all of them]
+  -i, --input <PATH>    Solve the puzzles of a file (one per line), or of standard input with -, instead
+  -r, --runs <N>        Number of times each puzzle is solved [default: 10]
+  -l, --limit <N>       Stop after N solutions, 0 finds all of them [default: 1]
+  -g, --generate <N>    Number of puzzles to generate, 0 skips the generator [default: 10]
+      --format <FORMAT> Output format: text, json or csv [default: text]
+  -h, --help            Print this help
+
+Corpora:
+  easy                  Generated puzzles with extra givens, so that they can be solved with singles alone
+  hard                  Generated puzzles that need four or more guesses
+  17-clue               Puzzles with the fewest givens possible
+  hardest               Well known puzzles considered among the hardest
+  samples               The built-in sample puzzles
+
+Each puzzle gets a line with its mean, fastest and slowest time, its iterations and the branches taken to solve it.
+Every corpus is followed by its totals: time percentiles of the per-puzzle means, iteration percentiles, and how many
+puzzles took each number of branches.
+
+With --format json or csv, each puzzle gets one record whose time is the mean of its runs, and whose corpus field names
+the collection it belongs to (generated for the generator's puzzles). Totals are only printed as text.
+
+Exit status: 0 on success, 1 if a puzzle can't be parsed, 2 on usage errors, 3 if the input can't be read.
+";
+
+const CORPORA: [(&str, &str); 4] = [
+    ("easy",    include_str!("../corpus/easy.txt")),
+    ("hard",    include_str!("../corpus/hard.txt")),
+    ("17-clue", include_str!("../corpus/17-clue.txt")),
+    ("hardest", include_str!("../corpus/hardest.txt")),
+];
+
+pub fn run(parser: &mut Parser) -> Result<Status, Error> {
+    let mut corpora = vec![];
+    let mut input = None;
+    let mut runs: u32 = 10;
+    let mut limit = 1;
+    let mut generate = 10;
+    let mut format = OutputFormat::Text;
+
+    while let Some(arg) = parser.next()? {
+        match arg {
+            Arg::Long("help") | Arg::Short('h') => {
+                print!("{}", HELP);
+                return Ok(Status::Success);
+            },
+            Arg::Long("corpus") | Arg::Short('c') => corpora.push(corpus(parser)?),
+            Arg::Long("input") | Arg::Short('i') => input = Some(parser.value()?),
+            Arg::Long("runs") | Arg::Short('r') => runs = std::cmp::max(parser.parse()?, 1),
+            Arg::Long("limit") | Arg::Short('l') => limit = parser.parse()?,
+            Arg::Long("generate") | Arg::Short('g') => generate = parser.parse()?,
+            Arg::Long("format") => format = OutputFormat::parse(parser)?,
+            arg => return Err(arg.unexpected().into())
+        }
+    }
+
+    let corpora = match (input, corpora.is_empty()) {
+        (Some(_), false) => return Err(ArgError::Conflict("--input", "--corpus").into()),
+        (Some(input), true) => vec![(input.clone(), load(&batch::read(&input)?)?)],
+        (None, true) => {
+            let mut corpora = vec![(String::from("samples"), load(&PUZZLES.join("\n"))?)];
+            for (name, text) in CORPORA.iter() {
+                corpora.push((name.to_string(), load(text)?));
+            }
+            corpora
+        },
+        (None, false) => corpora
+    };
+
+    let solver = Solver::with_limit(limit);
+    let mut total = Duration::default();
+    let mut solves = 0;
+
+    Report::print_header(format);
+
+    for (name, puzzles) in corpora.iter() {
+        let mut stats = Stats::default();
+
+        if format == OutputFormat::Text {
+            println!("{} ({} puzzles, {} runs each)", name, puzzles.len(), runs);
+            println!("{:>6}{:>12}{:>12}{:>12}{:>12}{:>10}", "#", "Mean", "Min", "Max", "Iterations", "Branches");
+        }
+
+        for (i, puzzle) in puzzles.iter().enumerate() {
+            let mut times = vec![];
+            let mut output = None;
+
+            for _ in 0..runs {
+                let time = Instant::now();
+                output = Some(solver.solve(puzzle));
+                times.push(time.elapsed());
+            }
+
+            let output = output.unwrap();
+            let sum: Duration = times.iter().sum();
+            total += sum;
+            solves += runs as usize;
+
+            stats.add(sum / runs, &output);
+
+            if format != OutputFormat::Text {
+                let mut report = Report::new("bench", puzzle);
+                report.corpus = Some(name.clone());
+                report.solver(&output).time(sum / runs).print_line(format);
+                continue;
+            }
+
+            println!(
+                "{:>6}{:>12}{:>12}{:>12}{:>12}{:>10}",
+                i + 1,
+                format!("{:.2?}", sum / runs),
+                format!("{:.2?}", times.iter().min().unwrap()),
+                format!("{:.2?}", times.iter().max().unwrap()),
+                output.iterations,
+                branches(&output)
+            );
+        }
+
+        if format == OutputFormat::Text {
+            stats.print();
+            println!();
+        }
+    }
+
+    if generate > 0 {
+        bench_generator(generate, format);
+    }
+
+    if format == OutputFormat::Text {
+        println!("Total: {:.2?} for {} solves", total, solves);
+    }
+
+    Ok(Status::Success)
+}
+
+// Generated puzzles are solved once more (untimed) for their iterations and branches
+fn bench_generator(count: usize, format: OutputFormat) {
+    let generator = Generator::new();
+    let mut stats = Stats::default();
+    let mut difficulties = vec![];
+
+    if format == OutputFormat::Text {
+        println!("generated ({} puzzles)", count);
+        println!("{:>6}{:>12}{:>12}{:>12}{:>10}", "#", "Time", "Difficulty", "Iterations", "Branches");
+    }
+
+    for i in 0..count {
+        let time = Instant::now();
+        let output = generator.generate();
+        let elapsed = time.elapsed();
+
+        let solved = Solver::with_limit(1).solve(&output.puzzle);
+        stats.add(elapsed, &solved);
+        difficulties.push(output.difficulty);
+
+        if format != OutputFormat::Text {
+            let mut report = Report::new("bench", &output.puzzle);
+            report.corpus = Some(String::from("generated"));
+            report.difficulty = Some(output.difficulty);
+            report.solver(&solved).time(elapsed).print_line(format);
+            continue;
+        }
+
+        println!(
+            "{:>6}{:>12}{:>12}{:>12}{:>10}",
+            i + 1,
+            format!("{:.2?}", elapsed),
+            output.difficulty,
+            solved.iterations,
+            branches(&solved)
+        );
+    }
+
+    if format == OutputFormat::Text {
+        stats.print();
+        difficulties.sort_unstable();
+        println!(
+            "  Difficulty  p50 {}, p90 {}, max {}",
+            percentile(&difficulties, 50),
+            percentile(&difficulties, 90),
+            difficulties.last().copied().unwrap_or_default()
+        );
+        println!();
+    }
+}
+
+fn corpus(parser: &mut Parser) -> Result<(String, Vec<[u8; 81]>), Error> {
+    let value = parser.value()?;
+
+    if value == "samples" {
+        return Ok((value, load(&PUZZLES.join("\n"))?));
+    }
+
+    match CORPORA.iter().find(|(name, _)| *name == value) {
+        Some((name, text)) => Ok((name.to_string(), load(text)?)),
+        None => Err(parser.invalid(&value, "expected easy, hard, 17-clue, hardest or samples").into())
+    }
+}
+
+fn load(text: &str) -> Result<Vec<[u8; 81]>, Error> {
+    let mut puzzles = vec![];
+    for (_, line) in batch::lines(text) {
+        puzzles.push(parse_puzzle(line)?);
+    }
+
+    Ok(puzzles)
+}
+
+fn branches(output: &SolverOutput) -> usize {
+    output.result.first().map_or(0, |result| result.branches)
+}
+
+#[derive(Default)]
+struct Stats {
+    times:      Vec<Duration>,
+    iterations: Vec<usize>,
+    // Number of puzzles that took each number of branches
+    branches:   BTreeMap<usize, usize>,
+}
+
+impl Stats {
+    fn add(&mut self, time: Duration, output: &SolverOutput) {
+        self.times.push(time);
+        self.iterations.push(output.iterations);
+        *self.branches.entry(branches(output)).or_insert(0) += 1;
+    }
+
+    fn print(&mut self) {
+        self.times.sort();
+        self.iterations.sort_unstable();
+
+        if self.times.is_empty() {
+            return;
+        }
+
+        let total: Duration = self.times.iter().sum();
+        let iterations: usize = self.iterations.iter().sum();
+
+        println!(
+            "  Time        total {:.2?}, mean {:.2?}, p50 {:.2?}, p90 {:.2?}, max {:.2?}",
+            total,
+            total / self.times.len() as u32,
+            percentile(&self.times, 50),
+            percentile(&self.times, 90),
+            self.times[self.times.len() - 1]
+        );
+        println!(
+            "  Iterations  mean {}, p50 {}, p90 {}, max {}",
+            iterations / self.iterations.len(),
+            percentile(&self.iterations, 50),
+            percentile(&self.iterations, 90),
+            self.iterations[self.iterations.len() - 1]
+        );
+
+        let branches: Vec<String> = self.branches.iter()
+            .map(|(branches, count)| format!("{}: {}", branches, count))
+            .collect();
+        println!("  Branches    {}", branches.join(", "));
+    }
+}
@@ -0,0 +1,65 @@
+use crate :: {
+    Flags,
+    args :: { Arg, Parser },
+    commands :: { Error, PuzzleArg, Status, flat },
+    report   :: { OutputFormat, Report },
+};
+
+use waffle_iron :: canonicalize;
+
+pub const HELP: &str = "\
+Prints the canonical form of a puzzle. Two puzzles are equivalent (the same up to relabeling digits, rotating,
+mirroring, and reordering rows and columns) if and only if their canonical forms match.
+
+Usage: wi-exec canonicalize [OPTIONS] <PUZZLE>
+
+Arguments:
+  <PUZZLE>              81 cells, in any of the notations accepted by the parser
+
+Options:
+  -p, --sample <N>      Canonicalize one of the built-in sample puzzles (0-8) instead
+      --flat            Print the result as a line of digits instead of a grid
+      --ascii           Draw grids with ASCII characters only
+      --blank           Leave empty cells blank instead of drawing them as '.'
+      --format <FORMAT> Output format: text, json or csv [default: text]
+  -h, --help            Print this help
+
+Exit status: 0 on success, 1 if the puzzle can't be parsed, 2 on usage errors.
+";
+
+pub fn run(parser: &mut Parser) -> Result<Status, Error> {
+    let mut flags = Flags::empty();
+    let mut puzzle = PuzzleArg::new();
+    let mut format = OutputFormat::Text;
+
+    while let Some(arg) = parser.next()? {
+        match arg {
+            Arg::Long("help") | Arg::Short('h') => {
+                print!("{}", HELP);
+                return Ok(Status::Success);
+            },
+            Arg::Long("sample") | Arg::Short('p') => puzzle.sample(parser)?,
+            Arg::Value(value) => puzzle.value(value)?,
+            Arg::Long("format") => format = OutputFormat::parse(parser)?,
+            arg => match Flags::from_arg(&arg).filter(|&flag| flag != Flags::VERBOSE) {
+                Some(flag) => flags.insert(flag),
+                None => return Err(arg.unexpected().into())
+            }
+        }
+    }
+
+    let puzzle = puzzle.get()?;
+    let time = std::time::Instant::now();
+    let canonical = canonicalize(&puzzle);
+
+    match format {
+        OutputFormat::Text => flags.print(&canonical),
+        format => {
+            let mut report = Report::new("canonicalize", &puzzle);
+            report.result = Some(flat(&canonical));
+            report.time(time.elapsed()).print(format);
+        }
+    }
+
+    Ok(Status::Success)
+}
@@ -0,0 +1,199 @@
+use crate :: {
+    args :: { Arg, ArgError, Parser },
+    commands :: { Error, Status, flat },
+    report   :: { OutputFormat, csv, string },
+};
+
+use waffle_iron :: { CompactPuzzle, Format, PuzzleRecord };
+
+use std :: { fs, io :: { self, Read, Write }, path :: Path };
+
+pub const HELP: &str = "\
+Converts a collection of puzzles from one file format to another.
+
+Usage: wi-exec convert [OPTIONS] <INPUT>
+
+Arguments:
+  <INPUT>               File to read the puzzles from, or - for standard input
+
+Options:
+  -f, --from <FORMAT>   Format of the input [default: taken from the input's extension]
+  -t, --to <FORMAT>     Format of the output [default: taken from the output's extension]
+  -o, --output <PATH>   File to write the puzzles to [default: standard output]
+      --format <FORMAT> Write the puzzles and their metadata as json or csv records instead of --to [default: text]
+  -h, --help            Print this help
+
+Formats:
+  sdk                   SadMan Sudoku, with #D/#A/#L/#C metadata
+  ss                    Simple Sudoku grids
+  txt                   One puzzle per line, optionally followed by its difficulty
+  opensudoku, xml       OpenSudoku XML
+  base64, b64           Compact base64 encoding, one puzzle per line
+
+With --format json each puzzle is written as one line holding an object with puzzle, title, author, difficulty and
+comment fields (null when missing); with --format csv the same fields are written as columns after a header row.
+
+Exit status: 0 on success, 1 if the input is malformed, 2 on usage errors, 3 if a file can't be read or written.
+";
+
+#[derive(Copy, Clone)]
+enum Target {
+    File(Format),
+    Base64,
+}
+
+impl Target {
+    fn from_name(name: &str) -> Option<Target> {
+        match name.to_ascii_lowercase().as_str() {
+            "base64" | "b64" => Some(Target::Base64),
+            name => Format::from_extension(name).map(Target::File)
+        }
+    }
+
+    fn from_path(path: &str) -> Option<Target> {
+        Path::new(path).extension().and_then(|extension| Target::from_name(&extension.to_string_lossy()))
+    }
+
+    fn read(self, input: &str) -> Result<Vec<PuzzleRecord>, Error> {
+        let format = match self {
+            Target::File(format) => return Ok(format.read(input)?),
+            Target::Base64 => input
+        };
+
+        let mut records = vec![];
+        for (line, text) in format.lines().enumerate() {
+            let text = text.trim();
+            if text.is_empty() || text.starts_with('#') {
+                continue;
+            }
+
+            match CompactPuzzle::from_base64(text) {
+                Ok(compact) => records.push(PuzzleRecord::new(compact.puzzle)),
+                Err(err) => return Err(Error::Codec(line + 1, err))
+            }
+        }
+
+        Ok(records)
+    }
+
+    fn write(self, records: &[PuzzleRecord]) -> Result<String, Error> {
+        let format = match self {
+            Target::File(format) => return Ok(format.write(records)),
+            Target::Base64 => records
+        };
+
+        let mut output = String::new();
+        for (i, record) in format.iter().enumerate() {
+            match CompactPuzzle::from(record.puzzle).to_base64() {
+                Ok(text) => output.push_str(&text),
+                Err(err) => return Err(Error::Codec(i + 1, err))
+            }
+            output.push('\n');
+        }
+
+        Ok(output)
+    }
+}
+
+pub fn run(parser: &mut Parser) -> Result<Status, Error> {
+    let mut from = None;
+    let mut to = None;
+    let mut input = None;
+    let mut output = None;
+    let mut records = OutputFormat::Text;
+
+    while let Some(arg) = parser.next()? {
+        match arg {
+            Arg::Long("help") | Arg::Short('h') => {
+                print!("{}", HELP);
+                return Ok(Status::Success);
+            },
+            Arg::Long("from") | Arg::Short('f') => from = Some(format(parser)?),
+            Arg::Long("to") | Arg::Short('t') => to = Some(format(parser)?),
+            Arg::Long("output") | Arg::Short('o') => output = Some(parser.value()?),
+            Arg::Long("format") => records = OutputFormat::parse(parser)?,
+            Arg::Value(value) if input.is_none() => input = Some(value),
+            arg => return Err(arg.unexpected().into())
+        }
+    }
+
+    let input = input.ok_or(ArgError::Missing("input file"))?;
+
+    let from = from.or_else(|| Target::from_path(&input))
+        .ok_or(ArgError::Missing("input format; use --from to specify one"))?;
+
+    if records != OutputFormat::Text && to.is_some() {
+        return Err(ArgError::Conflict("--format", "--to").into());
+    }
+
+    let to = match records {
+        OutputFormat::Text => Some(to.or_else(|| output.as_deref().and_then(Target::from_path))
+            .ok_or(ArgError::Missing("output format; use --to to specify one"))?),
+        _ => None
+    };
+
+    let text = match input.as_str() {
+        "-" => {
+            let mut text = String::new();
+            io::stdin().read_to_string(&mut text).map_err(|err| Error::Io(String::from("stdin"), err))?;
+            text
+        },
+        path => fs::read_to_string(path).map_err(|err| Error::Io(path.to_string(), err))?
+    };
+
+    let converted = match to {
+        Some(to) => to.write(&from.read(&text)?)?,
+        None => write_records(&from.read(&text)?, records)
+    };
+
+    match output {
+        Some(path) => fs::write(&path, converted).map_err(|err| Error::Io(path, err))?,
+        None => io::stdout().write_all(converted.as_bytes()).map_err(|err| Error::Io(String::from("stdout"), err))?
+    }
+
+    Ok(Status::Success)
+}
+
+fn format(parser: &mut Parser) -> Result<Target, Error> {
+    let value = parser.value()?;
+
+    match Target::from_name(&value) {
+        Some(target) => Ok(target),
+        None => Err(parser.invalid(&value, "expected sdk, ss, txt, opensudoku or base64").into())
+    }
+}
+
+// Like the reports of the other commands, every field is always written
+fn write_records(records: &[PuzzleRecord], format: OutputFormat) -> String {
+    let mut output = String::new();
+
+    if format == OutputFormat::Csv {
+        output.push_str("puzzle,title,author,difficulty,comment\n");
+    }
+
+    for record in records {
+        let puzzle = flat(&record.puzzle);
+        let fields = [&record.title, &record.author, &record.difficulty, &record.comment];
+
+        let line = match format {
+            OutputFormat::Csv => {
+                let columns: Vec<String> = fields.iter().map(|field| csv(field.as_deref().unwrap_or_default())).collect();
+                format!("{},{}", puzzle, columns.join(","))
+            },
+            _ => {
+                let values: Vec<String> = fields.iter()
+                    .map(|field| field.as_deref().map_or(String::from("null"), string))
+                    .collect();
+                format!(
+                    "{{\"puzzle\":{},\"title\":{},\"author\":{},\"difficulty\":{},\"comment\":{}}}",
+                    string(&puzzle), values[0], values[1], values[2], values[3]
+                )
+            }
+        };
+
+        output.push_str(&line);
+        output.push('\n');
+    }
+
+    output
+}
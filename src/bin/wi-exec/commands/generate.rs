@@ -0,0 +1,93 @@
+use crate :: {
+    Flags,
+    args :: { Arg, Parser },
+    commands :: { Error, Status, flat },
+    report   :: { OutputFormat, Report },
+};
+
+use waffle_iron :: Generator;
+
+pub const HELP: &str = "\
+Generates new puzzles with a unique solution.
+
+Usage: wi-exec generate [OPTIONS]
+
+Options:
+  -n, --count <N>       Number of puzzles to generate [default: 1]
+      --samples <N>     Number of samples taken per puzzle [default: 21]
+      --iterations <N>  Removal iterations performed per sample, at most 64 [default: 58]
+      --removals <N>    Values removed per iteration [default: 1]
+  -v, --verbose         Print the solution and difficulty along with each puzzle
+      --format <FORMAT> Output format: text, json or csv [default: text]
+      --flat            Print puzzles as lines of digits instead of grids
+      --ascii           Draw grids with ASCII characters only
+      --blank           Leave empty cells blank instead of drawing them as '.'
+  -h, --help            Print this help
+
+Exit status: 0 on success, 2 on usage errors.
+";
+
+pub fn run(parser: &mut Parser) -> Result<Status, Error> {
+    let mut flags = Flags::empty();
+    let mut generator = Generator::new();
+    let mut count = 1;
+    let mut format = OutputFormat::Text;
+
+    while let Some(arg) = parser.next()? {
+        match arg {
+            Arg::Long("help") | Arg::Short('h') => {
+                print!("{}", HELP);
+                return Ok(Status::Success);
+            },
+            Arg::Long("count") | Arg::Short('n') => count = parser.parse()?,
+            Arg::Long("samples")    => { generator.samples(parser.parse()?); },
+            Arg::Long("iterations") => { generator.iterations(parser.parse()?); },
+            Arg::Long("removals")   => { generator.removals(parser.parse()?); },
+            Arg::Long("format") => format = OutputFormat::parse(parser)?,
+            arg => match Flags::from_arg(&arg) {
+                Some(flag) => flags.insert(flag),
+                None => return Err(arg.unexpected().into())
+            }
+        }
+    }
+
+    let verbose = flags.contains(Flags::VERBOSE);
+
+    Report::print_header(format);
+
+    for _ in 0..count {
+        let time = std::time::Instant::now();
+        let output = generator.generate();
+        let elapsed = time.elapsed();
+        let dur = elapsed.as_millis();
+
+        if format != OutputFormat::Text {
+            let mut report = Report::new("generate", &output.puzzle);
+            report.solutions = Some(vec![flat(&output.solution)]);
+            report.difficulty = Some(output.difficulty);
+            report.time(elapsed).print_line(format);
+            continue;
+        }
+
+        println!();
+        if flags.contains(Flags::FLAT) {
+            match verbose {
+                true  => print!("{:?}", output),
+                false => print!("{}", output)
+            }
+            println!();
+        }
+        else if verbose {
+            print!("{}", flags.grid(&output.puzzle, Some(&output.solution)));
+            println!("Difficulty: {}", output.difficulty);
+            println!();
+        }
+        else {
+            print!("{}", flags.grid(&output.puzzle, None));
+            println!();
+        }
+        println!("Time: {}ms", dur);
+    }
+
+    Ok(Status::Success)
+}
@@ -0,0 +1,110 @@
+use crate :: {
+    Flags,
+    args :: { Arg, Parser },
+    commands :: { Error, PuzzleArg, Status, batch :: Batch },
+    report   :: { OutputFormat, Report },
+};
+
+use waffle_iron :: grade;
+
+pub const HELP: &str = "\
+Rates how difficult a puzzle is, and lists the techniques needed to solve it by hand.
+
+Usage: wi-exec grade [OPTIONS] <PUZZLE>
+
+Arguments:
+  <PUZZLE>              81 cells, in any of the notations accepted by the parser
+
+Options:
+  -p, --sample <N>      Grade one of the built-in sample puzzles (0-8) instead
+  -i, --input <PATH>    Grade every puzzle of a file (one per line), or of standard input with -
+  -j, --jobs <N>        Number of puzzles graded in parallel with --input, 0 uses every CPU [default: 1]
+  -v, --verbose         Print the puzzle's grid along with its grade
+      --format <FORMAT> Output format: text, json or csv [default: text]
+      --ascii           Draw grids with ASCII characters only
+      --blank           Leave empty cells blank instead of drawing them as '.'
+  -h, --help            Print this help
+
+With --input, each line of output holds a puzzle followed by its difficulty and level, or by the reason it failed. A
+summary of counts, failures and timings is written to standard error.
+
+Exit status: 0 on success (for every puzzle), 1 if a puzzle is invalid or doesn't have a unique solution, 2 on usage
+errors, 3 if the input can't be read.
+";
+
+pub fn run(parser: &mut Parser) -> Result<Status, Error> {
+    let mut flags = Flags::empty();
+    let mut puzzle = PuzzleArg::new();
+    let mut batch = Batch::new("grade");
+    let mut format = OutputFormat::Text;
+
+    while let Some(arg) = parser.next()? {
+        match arg {
+            Arg::Long("help") | Arg::Short('h') => {
+                print!("{}", HELP);
+                return Ok(Status::Success);
+            },
+            Arg::Long("sample") | Arg::Short('p') => puzzle.sample(parser)?,
+            Arg::Value(value) => puzzle.value(value)?,
+            Arg::Long("input") | Arg::Short('i') => batch.input(parser)?,
+            Arg::Long("jobs") | Arg::Short('j') => batch.jobs(parser)?,
+            Arg::Long("format") => format = OutputFormat::parse(parser)?,
+            arg => match Flags::from_arg(&arg).filter(|&flag| flag != Flags::FLAT) {
+                Some(flag) => flags.insert(flag),
+                None => return Err(arg.unexpected().into())
+            }
+        }
+    }
+
+    if batch.is_enabled() {
+        return batch.format(format).run(
+            &puzzle,
+            report,
+            |report| format!("{} {}", report.difficulty.unwrap_or_default(), report.level.as_deref().unwrap_or_default())
+        );
+    }
+
+    let puzzle = puzzle.get()?;
+
+    if format != OutputFormat::Text {
+        let time = std::time::Instant::now();
+        let mut report = report(&puzzle);
+        report.time(time.elapsed()).print(format);
+
+        return Ok(if report.is_ok() { Status::Success } else { Status::Rejected });
+    }
+
+    if flags.contains(Flags::VERBOSE) {
+        println!();
+        print!("{}", flags.grid(&puzzle, None));
+        println!();
+    }
+
+    let grade = match grade(&puzzle) {
+        Ok(grade) => grade,
+        Err(err) => {
+            eprintln!("Invalid puzzle: {}", err);
+            return Ok(Status::Rejected);
+        }
+    };
+
+    println!("Difficulty: {}", grade.difficulty);
+    println!("Level: {}", grade.level);
+    println!("Techniques:");
+    for (technique, count) in grade.techniques.iter() {
+        println!("  {:<20}{}", technique, count);
+    }
+
+    Ok(Status::Success)
+}
+
+fn report(puzzle: &[u8; 81]) -> Report {
+    let mut report = Report::new("grade", puzzle);
+
+    match grade(puzzle) {
+        Ok(grade) => report.grade(&grade),
+        Err(err) => report.error(err)
+    };
+
+    report
+}
@@ -0,0 +1,76 @@
+use crate :: {
+    args :: { Arg, Parser },
+    commands :: { Error, PuzzleArg, Status, location },
+    report   :: { OutputFormat, Report },
+};
+
+use waffle_iron :: { Sudoku, hint, traits :: SudokuState };
+
+pub const HELP: &str = "\
+Finds the next value that can be placed in a puzzle, and the technique that finds it.
+
+Usage: wi-exec hint [OPTIONS] <PUZZLE>
+
+Arguments:
+  <PUZZLE>              81 cells, in any of the notations accepted by the parser
+
+Options:
+  -p, --sample <N>      Give a hint for one of the built-in sample puzzles (0-8) instead
+      --format <FORMAT> Output format: text, json or csv [default: text]
+  -h, --help            Print this help
+
+Exit status: 0 if a hint was found or the puzzle is already solved, 1 if the puzzle can't be solved, 2 on usage errors.
+";
+
+pub fn run(parser: &mut Parser) -> Result<Status, Error> {
+    let mut puzzle = PuzzleArg::new();
+    let mut format = OutputFormat::Text;
+
+    while let Some(arg) = parser.next()? {
+        match arg {
+            Arg::Long("help") | Arg::Short('h') => {
+                print!("{}", HELP);
+                return Ok(Status::Success);
+            },
+            Arg::Long("sample") | Arg::Short('p') => puzzle.sample(parser)?,
+            Arg::Value(value) => puzzle.value(value)?,
+            Arg::Long("format") => format = OutputFormat::parse(parser)?,
+            arg => return Err(arg.unexpected().into())
+        }
+    }
+
+    let puzzle = puzzle.get()?;
+    let state = Sudoku::new(&puzzle);
+
+    // A solved puzzle's report has neither a hint nor an error
+    if format != OutputFormat::Text {
+        let time = std::time::Instant::now();
+        let mut report = Report::new("hint", &puzzle);
+
+        if state.remaining() > 0 {
+            report.hint = hint(&state);
+            if report.hint.is_none() {
+                report.error("the puzzle can't be solved");
+            }
+        }
+
+        report.time(time.elapsed()).print(format);
+        return Ok(if report.is_ok() { Status::Success } else { Status::Rejected });
+    }
+
+    if state.remaining() == 0 {
+        println!("Already solved");
+        return Ok(Status::Success);
+    }
+
+    match hint(&state) {
+        Some(hint) => {
+            println!("{}: {} ({})", location(hint.index), hint.value, hint.technique);
+            Ok(Status::Success)
+        },
+        None => {
+            println!("No hint available: the puzzle can't be solved");
+            Ok(Status::Rejected)
+        }
+    }
+}
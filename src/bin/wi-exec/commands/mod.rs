@@ -0,0 +1,178 @@
+pub mod solve;
+pub mod generate;
+pub mod grade;
+pub mod validate;
+pub mod hint;
+pub mod canonicalize;
+pub mod transform;
+pub mod convert;
+pub mod bench;
+pub mod play;
+pub mod stats;
+pub mod batch;
+
+use crate :: {
+    Flags,
+    args :: { Arg, ArgError, Parser },
+};
+
+use waffle_iron :: { CodecError, Grid, GridStyle, ParseError, parse_puzzle };
+
+use std :: { fmt, io };
+
+pub const PUZZLES: [&str; 9] = [
+    "309000400200709000087000000750060230600904008028050041000000590000106007006000104",
+    "295743861431865900876192543387459216612387495549216738763534189928671354154938600",
+    "005300000800000020070010500400005300010070006003200080060500009004000030000009700",
+    "003000000809460702200018600000006070008000400070800000002940005406032807000000200",
+    "000500012000003070000907540803400090020000000009000103700040000140000030090600200",
+    "534008010000002090000007604000500100100000003009001000305400000080200000060700382",
+    "000065000510003000080000060000006040040309018000080200300001009000000800091700400",
+    "090001007060030050070200001804000009500010830000005000000842000000000200006000003",
+    "089005030005018900070400000950200600000030002000004003400102500002000000090850000",
+];
+
+// Process exit codes shared by every command
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum Status {
+    Success     = 0,
+    // The puzzle was rejected: it couldn't be parsed, is invalid, or has no (unique) solution
+    Rejected    = 1,
+    Usage       = 2,
+    Failure     = 3,
+}
+
+pub enum Error {
+    Args(ArgError),
+    Puzzle(ParseError),
+    // Line number of a compact puzzle that couldn't be decoded
+    Codec(usize, CodecError),
+    Io(String, io::Error),
+}
+
+impl Error {
+    pub fn status(&self) -> Status {
+        match self {
+            Error::Args(_)      => Status::Usage,
+            Error::Puzzle(_)    => Status::Rejected,
+            Error::Codec(_, _)  => Status::Rejected,
+            Error::Io(_, _)     => Status::Failure,
+        }
+    }
+}
+
+impl From<ArgError> for Error {
+    fn from(err: ArgError) -> Self {
+        Error::Args(err)
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(err: ParseError) -> Self {
+        Error::Puzzle(err)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Args(err)        => write!(formatter, "{}", err),
+            Error::Puzzle(err)      => write!(formatter, "invalid puzzle: {}", err),
+            Error::Codec(line, err) => write!(formatter, "invalid compact puzzle: line {}: {}", line, err),
+            Error::Io(path, err)    => write!(formatter, "{}: {}", path, err),
+        }
+    }
+}
+
+impl Flags {
+    // The display options accepted by every command that prints grids
+    pub fn from_arg(arg: &Arg) -> Option<Flags> {
+        match arg {
+            Arg::Long("verbose") | Arg::Short('v') => Some(Flags::VERBOSE),
+            Arg::Long("flat")   => Some(Flags::FLAT),
+            Arg::Long("ascii")  => Some(Flags::ASCII),
+            Arg::Long("blank")  => Some(Flags::BLANK),
+            _ => None
+        }
+    }
+
+    pub fn grid(self, puzzle: &[u8; 81], solution: Option<&[u8; 81]>) -> String {
+        let mut grid = Grid::new(puzzle);
+
+        if let Some(solution) = solution {
+            grid.solution(solution);
+        }
+
+        if self.contains(Flags::ASCII) {
+            grid.style(GridStyle::Ascii);
+        }
+
+        if self.contains(Flags::BLANK) {
+            grid.blank(' ');
+        }
+
+        grid.to_string()
+    }
+
+    pub fn print(self, puzzle: &[u8; 81]) {
+        match self.contains(Flags::FLAT) {
+            true  => println!("{}", flat(puzzle)),
+            false => print!("{}", self.grid(puzzle, None))
+        }
+    }
+}
+
+// Reads the puzzle argument shared by most commands: either a puzzle written out in full, or --sample N.
+pub struct PuzzleArg(Option<[u8; 81]>);
+
+impl PuzzleArg {
+    pub fn new() -> Self {
+        Self(None)
+    }
+
+    pub fn value(&mut self, value: String) -> Result<(), Error> {
+        if self.0.is_some() {
+            return Err(ArgError::Unexpected(value).into());
+        }
+
+        self.0 = Some(parse_puzzle(&value)?);
+        Ok(())
+    }
+
+    pub fn sample(&mut self, parser: &mut Parser) -> Result<(), Error> {
+        let value = parser.value()?;
+
+        if self.0.is_some() {
+            return Err(parser.invalid(&value, "a puzzle was already given").into());
+        }
+
+        match value.parse::<usize>().ok().and_then(|i| PUZZLES.get(i)) {
+            Some(sample) => self.0 = Some(parse_puzzle(sample)?),
+            None => return Err(parser.invalid(&value, "expected a sample number from 0 to 8").into())
+        }
+
+        Ok(())
+    }
+
+    pub fn is_some(&self) -> bool {
+        self.0.is_some()
+    }
+
+    pub fn get(self) -> Result<[u8; 81], Error> {
+        self.0.ok_or(Error::Args(ArgError::Missing("puzzle argument")))
+    }
+}
+
+pub fn location(index: usize) -> String {
+    format!("r{}c{}", index / 9 + 1, index % 9 + 1)
+}
+
+pub fn flat(puzzle: &[u8; 81]) -> String {
+    puzzle.iter().map(|&value| (b'0' + value) as char).collect()
+}
+
+// Nearest rank percentile of sorted values
+pub fn percentile<T: Copy + Default>(sorted: &[T], percent: usize) -> T {
+    let rank = (sorted.len() * percent).div_ceil(100);
+    sorted.get(rank.saturating_sub(1)).copied().unwrap_or_default()
+}
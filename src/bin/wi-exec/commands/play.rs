@@ -0,0 +1,355 @@
+use crate :: {
+    Flags,
+    args :: { Arg, ArgError, Parser },
+    commands :: { Error, PuzzleArg, Status, location },
+    terminal :: { Key, Terminal },
+};
+
+use waffle_iron :: { Game, Generator };
+
+use std :: { fs, io, path :: Path, time :: { Duration, Instant } };
+
+pub const HELP: &str = "\
+Plays a puzzle interactively in the terminal.
+
+Usage: wi-exec play [OPTIONS] [PUZZLE]
+
+Arguments:
+  [PUZZLE]              81 cells, in any of the notations accepted by the parser [default: a newly generated puzzle]
+
+Options:
+  -p, --sample <N>      Play one of the built-in sample puzzles (0-8) instead
+  -s, --save <PATH>     Resume the game saved at PATH if there is one, and save it there when quitting
+      --ascii           Draw the grid with ASCII characters only
+  -h, --help            Print this help
+
+Keys:
+  arrows, hjkl          Move the cursor
+  1-9                   Enter a digit, or toggle a pencil mark in pencil mode
+  0, space, backspace   Erase the cell
+  m                     Switch between entering digits and pencil marks
+  a                     Turn automatic pencil marks on or off: placing a digit removes it from the marks of the cells
+                        it sees, and erasing it puts them back
+  u                     Undo the last change
+  r                     Redo the last change that was undone
+  ?                     Show a hint for the next cell to fill in
+  c                     Check the entries against the solution, highlighting mistakes
+  q, esc                Quit
+
+Digits that conflict with another digit of the same row, column or box are drawn in red.
+
+Saved games hold the entries, pencil marks, undo history, time and hints taken, in the same format the library and
+WASM bindings use. Once a saved game is solved its file is removed, so the next game starts a new puzzle.
+
+Exit status: 0 on success, 1 if the puzzle is invalid or doesn't have a unique solution or the saved game can't be
+read, 2 on usage errors, 3 if the terminal can't be used or the game can't be saved.
+";
+
+pub fn run(parser: &mut Parser) -> Result<Status, Error> {
+    let mut flags = Flags::empty();
+    let mut puzzle = PuzzleArg::new();
+    let mut save = None;
+
+    while let Some(arg) = parser.next()? {
+        match arg {
+            Arg::Long("help") | Arg::Short('h') => {
+                print!("{}", HELP);
+                return Ok(Status::Success);
+            },
+            Arg::Long("sample") | Arg::Short('p') => puzzle.sample(parser)?,
+            Arg::Long("save") | Arg::Short('s') => save = Some(parser.value()?),
+            Arg::Value(value) => puzzle.value(value)?,
+            arg => match Flags::from_arg(&arg).filter(|&flag| flag == Flags::ASCII) {
+                Some(flag) => flags.insert(flag),
+                None => return Err(arg.unexpected().into())
+            }
+        }
+    }
+
+    let saved = match &save {
+        Some(path) if Path::new(path).exists() => {
+            if puzzle.is_some() {
+                return Err(ArgError::Conflict("a saved game", "a puzzle argument").into());
+            }
+
+            let text = fs::read_to_string(path).map_err(|err| Error::Io(path.clone(), err))?;
+            match Game::from_base64(&text) {
+                Ok(game) => Some(game),
+                Err(err) => {
+                    eprintln!("{}: invalid saved game: {}", path, err);
+                    return Ok(Status::Rejected);
+                }
+            }
+        },
+        _ => None
+    };
+
+    let game = match (saved, puzzle.is_some()) {
+        (Some(game), _) => game,
+        (None, true) => match Game::new(&puzzle.get()?) {
+            Ok(game) => game,
+            Err(err) => {
+                eprintln!("Invalid puzzle: {}", err);
+                return Ok(Status::Rejected);
+            }
+        },
+        (None, false) => {
+            println!("Generating a puzzle...");
+            let output = Generator::new().generate();
+            Game::with_solution(&output.puzzle, &output.solution)
+        }
+    };
+
+    let mut terminal = Terminal::open().map_err(|err| Error::Io(String::from("terminal"), err))?;
+    let mut screen = Screen::new(game, flags.contains(Flags::ASCII));
+
+    play(&mut terminal, &mut screen).map_err(|err| Error::Io(String::from("terminal"), err))?;
+    drop(terminal);
+
+    if let Some(path) = save {
+        screen.game.set_elapsed(screen.elapsed());
+        store(&path, &screen.game).map_err(|err| Error::Io(path, err))?;
+    }
+
+    Ok(Status::Success)
+}
+
+fn store(path: &str, game: &Game) -> io::Result<()> {
+    match game.is_complete() {
+        true if Path::new(path).exists() => fs::remove_file(path),
+        true => Ok(()),
+        false => fs::write(path, game.to_base64() + "\n")
+    }
+}
+
+fn play(terminal: &mut Terminal, screen: &mut Screen) -> std::io::Result<()> {
+    loop {
+        terminal.draw(&screen.render())?;
+
+        for key in terminal.keys()? {
+            // Once solved, any key leaves the game
+            if screen.game.is_complete() {
+                return Ok(());
+            }
+
+            match key {
+                Key::Char('q') | Key::Escape | Key::Interrupt => return Ok(()),
+                key => screen.press(key)
+            }
+        }
+    }
+}
+
+// The game along with everything needed to draw it
+struct Screen {
+    game:       Game,
+    cursor:     usize,
+    pencil:     bool,
+    // Mistakes stay highlighted after a check until the board changes
+    checked:    bool,
+    message:    String,
+    ascii:      bool,
+    // Time played before the game was resumed
+    played:     Duration,
+    start:      Instant,
+}
+
+impl Screen {
+    fn new(game: Game, ascii: bool) -> Self {
+        let cursor = game.givens().iter().position(|&value| value == 0).unwrap_or(0);
+
+        let played = game.elapsed();
+
+        Self { game, cursor, pencil: false, checked: false, message: String::new(), ascii, played, start: Instant::now() }
+    }
+
+    fn elapsed(&self) -> Duration {
+        self.played + self.start.elapsed()
+    }
+
+    fn press(&mut self, key: Key) {
+        self.message.clear();
+
+        let (row, col) = (self.cursor / 9, self.cursor % 9);
+
+        let result = match key {
+            Key::Up    | Key::Char('k') => { self.cursor = (row + 8) % 9 * 9 + col; Ok(()) },
+            Key::Down  | Key::Char('j') => { self.cursor = (row + 1) % 9 * 9 + col; Ok(()) },
+            Key::Left  | Key::Char('h') => { self.cursor = row * 9 + (col + 8) % 9; Ok(()) },
+            Key::Right | Key::Char('l') => { self.cursor = row * 9 + (col + 1) % 9; Ok(()) },
+            Key::Char(digit @ '1'..='9') => self.enter(digit as u8 - b'0'),
+            Key::Char('0') | Key::Char(' ') | Key::Backspace | Key::Delete => self.erase(),
+            Key::Char('m') => { self.pencil = !self.pencil; Ok(()) },
+            Key::Char('a') => { self.toggle_auto_marks(); Ok(()) },
+            Key::Char('u') => { self.history(Game::undo, "Nothing to undo"); Ok(()) },
+            Key::Char('r') => { self.history(Game::redo, "Nothing to redo"); Ok(()) },
+            Key::Char('?') => { self.hint(); Ok(()) },
+            Key::Char('c') => { self.check(); Ok(()) },
+            _ => Ok(())
+        };
+
+        if let Err(err) = result {
+            self.message = format!("{}{}", err[..1].to_uppercase(), &err[1..]);
+        }
+    }
+
+    fn enter(&mut self, value: u8) -> Result<(), String> {
+        let result = match self.pencil {
+            true  => self.game.toggle_mark(self.cursor, value),
+            false => self.game.place(self.cursor, value)
+        };
+
+        self.checked = false;
+        result.map_err(|err| err.to_string())
+    }
+
+    // Erasing a cell without a digit clears its pencil marks instead
+    fn erase(&mut self) -> Result<(), String> {
+        let result = match self.game.value(self.cursor) {
+            0 => self.game.set_marks(self.cursor, &[]),
+            _ => self.game.erase(self.cursor)
+        };
+
+        self.checked = false;
+        result.map_err(|err| err.to_string())
+    }
+
+    fn history(&mut self, action: fn(&mut Game) -> bool, nothing: &str) {
+        match action(&mut self.game) {
+            true  => self.checked = false,
+            false => self.message = String::from(nothing)
+        }
+    }
+
+    fn toggle_auto_marks(&mut self) {
+        let enabled = !self.game.has_auto_marks();
+        self.game.auto_marks(enabled);
+
+        self.message = match enabled {
+            true  => String::from("Automatic pencil marks on"),
+            false => String::from("Automatic pencil marks off")
+        };
+    }
+
+    fn hint(&mut self) {
+        if !self.game.mistakes().is_empty() {
+            self.message = String::from("The board has mistakes: press c to find them");
+            return;
+        }
+
+        match self.game.hint() {
+            Some(hint) => {
+                self.cursor = hint.index;
+                self.message = format!("Hint: {} is {} ({})", location(hint.index), hint.value, hint.technique);
+            },
+            None => self.message = String::from("No hint available")
+        }
+    }
+
+    fn check(&mut self) {
+        self.checked = true;
+        self.message = match self.game.mistakes().len() {
+            0 => String::from("No mistakes so far"),
+            1 => String::from("1 mistake"),
+            n => format!("{} mistakes", n)
+        };
+    }
+
+    fn render(&self) -> String {
+        let (vertical, horizontal, cross) = match self.ascii {
+            true  => ('|', '-', '+'),
+            false => ('│', '─', '┼'),
+        };
+
+        let separator = vec![horizontal.to_string().repeat(21); 3].join(&cross.to_string());
+
+        let mut screen = String::new();
+
+        for row in 0..9 {
+            if row > 0 && row % 3 == 0 {
+                screen.push_str(&separator);
+                screen.push('\n');
+            }
+
+            // Each cell is three lines tall, with room for a 3x3 block of pencil marks
+            for line in 0..3 {
+                for col in 0..9 {
+                    if col > 0 && col % 3 == 0 {
+                        screen.push(vertical);
+                    }
+                    screen.push_str(&self.cell(row * 9 + col, line));
+                }
+                screen.push('\n');
+            }
+        }
+
+        let elapsed = self.elapsed().as_secs();
+
+        screen.push('\n');
+        screen.push_str(&format!(
+            "{}  Mode: {}{}  Time: {}:{:02}  Hints: {}\n",
+            location(self.cursor),
+            if self.pencil { "pencil" } else { "digits" },
+            if self.game.has_auto_marks() { " (auto marks)" } else { "" },
+            elapsed / 60,
+            elapsed % 60,
+            self.game.hints()
+        ));
+
+        match self.game.is_complete() {
+            true  => screen.push_str("Solved! Press any key to quit.\n"),
+            false => {
+                screen.push_str(&self.message);
+                screen.push('\n');
+            }
+        }
+
+        screen.push_str("\x1b[2marrows move  1-9 enter  0 erase  m pencil  a auto  u undo  r redo  ? hint  c check  q quit\x1b[0m\n");
+        screen
+    }
+
+    fn cell(&self, index: usize, line: usize) -> String {
+        let value = self.game.value(index);
+
+        let text = match value {
+            0 => {
+                let marks: Vec<String> = (1..=3)
+                    .map(|i| line as u8 * 3 + i)
+                    .map(|mark| match self.game.has_mark(index, mark) {
+                        false => String::from(" "),
+                        true  => mark.to_string()
+                    })
+                    .collect();
+                format!(" \x1b[2m{}\x1b[22m ", marks.join(" "))
+            },
+            value if line == 1 => format!("   {}   ", value),
+            _ => String::from("       ")
+        };
+
+        let mut style = vec![];
+
+        if self.game.is_given(index) {
+            style.push("1");
+        }
+        else if value != 0 {
+            style.push("34");
+        }
+
+        if self.game.is_conflict(index) {
+            style.push("31");
+        }
+
+        if self.checked && self.game.is_mistake(index) {
+            style.push("41");
+        }
+
+        if index == self.cursor {
+            style.push("7");
+        }
+
+        match style.is_empty() {
+            true  => text,
+            false => format!("\x1b[{}m{}\x1b[0m", style.join(";"), text)
+        }
+    }
+}
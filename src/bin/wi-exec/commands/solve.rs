@@ -0,0 +1,125 @@
+use crate :: {
+    Flags,
+    args :: { Arg, Parser },
+    commands :: { Error, PuzzleArg, Status, batch :: Batch },
+    report   :: { OutputFormat, Report },
+};
+
+use waffle_iron :: Solver;
+
+pub const HELP: &str = "\
+Solves a puzzle, printing each solution next to the original puzzle.
+
+Usage: wi-exec solve [OPTIONS] <PUZZLE>
+
+Arguments:
+  <PUZZLE>              81 cells, in any of the notations accepted by the parser
+
+Options:
+  -l, --limit <N>       Stop after N solutions, 0 finds all of them [default: 1]
+  -p, --sample <N>      Solve one of the built-in sample puzzles (0-8) instead
+  -i, --input <PATH>    Solve every puzzle of a file (one per line), or of standard input with -
+  -j, --jobs <N>        Number of puzzles solved in parallel with --input, 0 uses every CPU [default: 1]
+  -v, --verbose         Print iteration and branch counts
+      --format <FORMAT> Output format: text, json or csv [default: text]
+      --flat            Print solutions as lines of digits instead of grids
+      --ascii           Draw grids with ASCII characters only
+      --blank           Leave empty cells blank instead of drawing them as '.'
+  -h, --help            Print this help
+
+With --input, each line of output holds a puzzle followed by its solutions, or by the reason it failed. A summary
+of counts, failures and timings is written to standard error.
+
+Exit status: 0 if a solution was found (for every puzzle), 1 if a puzzle is invalid or has no solution, 2 on usage
+errors, 3 if the input can't be read.
+";
+
+pub fn run(parser: &mut Parser) -> Result<Status, Error> {
+    let mut flags = Flags::empty();
+    let mut puzzle = PuzzleArg::new();
+    let mut batch = Batch::new("solve");
+    let mut format = OutputFormat::Text;
+    let mut limit = 1;
+
+    while let Some(arg) = parser.next()? {
+        match arg {
+            Arg::Long("help") | Arg::Short('h') => {
+                print!("{}", HELP);
+                return Ok(Status::Success);
+            },
+            Arg::Long("limit") | Arg::Short('l') => limit = parser.parse()?,
+            Arg::Long("sample") | Arg::Short('p') => puzzle.sample(parser)?,
+            Arg::Value(value) => puzzle.value(value)?,
+            Arg::Long("input") | Arg::Short('i') => batch.input(parser)?,
+            Arg::Long("jobs") | Arg::Short('j') => batch.jobs(parser)?,
+            Arg::Long("format") => format = OutputFormat::parse(parser)?,
+            arg => match Flags::from_arg(&arg) {
+                Some(flag) => flags.insert(flag),
+                None => return Err(arg.unexpected().into())
+            }
+        }
+    }
+
+    if batch.is_enabled() {
+        let solver = Solver::with_limit(limit);
+
+        return batch.format(format).run(
+            &puzzle,
+            |puzzle| {
+                let mut report = Report::new("solve", puzzle);
+                report.solver(&solver.solve(puzzle));
+                report
+            },
+            |report| report.solutions.as_ref().map(|solutions| solutions.join(" ")).unwrap_or_default()
+        );
+    }
+
+    let puzzle = puzzle.get()?;
+    let verbose = flags.contains(Flags::VERBOSE);
+
+    let time = std::time::Instant::now();
+    let output = Solver::with_limit(limit).solve(&puzzle);
+    let elapsed = time.elapsed();
+    let dur = elapsed.as_millis();
+
+    let status = match output.result.is_empty() {
+        true  => Status::Rejected,
+        false => Status::Success
+    };
+
+    if format != OutputFormat::Text {
+        Report::new("solve", &puzzle).solver(&output).time(elapsed).print(format);
+        return Ok(status);
+    }
+
+    println!();
+    if flags.contains(Flags::FLAT) {
+        match verbose {
+            true  => print!("{:?}", output),
+            false => print!("{}", output)
+        }
+        println!();
+    }
+    else {
+        for result in output.result.iter() {
+            print!("{}", flags.grid(&puzzle, Some(&result.solution)));
+            if verbose {
+                println!("Iteration: {}, Branches: {}", result.iteration, result.branches);
+            }
+            println!();
+        }
+
+        if output.result.is_empty() {
+            print!("{}", flags.grid(&puzzle, None));
+            println!("No solution found");
+            println!();
+        }
+
+        if verbose {
+            println!("Iterations: {}, Steps: {}", output.iterations, output.steps);
+        }
+    }
+    println!("Time: {}ms", dur);
+
+    Ok(status)
+}
@@ -0,0 +1,286 @@
+use crate :: {
+    args :: { Arg, Parser },
+    commands :: { Error, Status, batch },
+    report   :: { OutputFormat, csv, string, technique_key },
+};
+
+use waffle_iron :: { Generator, Solver, Technique, grade, parse_puzzle };
+
+use std :: {
+    collections :: BTreeMap,
+    sync        :: { Mutex, atomic :: { AtomicUsize, Ordering } },
+    thread,
+    time        :: Instant,
+};
+
+pub const HELP: &str = "\
+Prints histograms of the difficulty, givens, branches, iterations and techniques of many puzzles, to help tune the
+generator's settings.
+
+Usage: wi-exec stats [OPTIONS]
+
+Options:
+  -n, --count <N>       Number of puzzles to generate [default: 100]
+  -i, --input <PATH>    Analyze the puzzles of a file (one per line), or of standard input with -, instead
+  -j, --jobs <N>        Number of puzzles generated and analyzed in parallel, 0 uses every CPU [default: 1]
+      --samples <N>     Number of samples taken per generated puzzle [default: 21]
+      --iterations <N>  Removal iterations performed per sample, at most 64 [default: 58]
+      --removals <N>    Values removed per iteration [default: 1]
+      --format <FORMAT> Output format: text, json or csv [default: text]
+  -h, --help            Print this help
+
+Difficulty, levels and techniques are those reported by the grade command; branches and iterations are those the
+solver needs to find the first solution. Puzzles that are invalid or don't have a unique solution are left out and
+listed on standard error.
+
+With --format csv, each line holds a histogram's name, a bucket and the number of puzzles that fall into it; with
+--format json, the histograms are written as one object mapping each name to its buckets and counts. The techniques
+histogram counts the puzzles that needed each technique at least once, while technique_uses counts every use.
+
+Exit status: 0 on success, 1 if a puzzle was left out, 2 on usage errors, 3 if the input can't be read.
+";
+
+// What the histograms are made of, for a single puzzle
+struct Sample {
+    difficulty: usize,
+    level:      String,
+    givens:     usize,
+    branches:   usize,
+    iterations: usize,
+    techniques: BTreeMap<Technique, usize>,
+}
+
+pub fn run(parser: &mut Parser) -> Result<Status, Error> {
+    let mut generator = Generator::new();
+    let mut count = 100;
+    let mut input = None;
+    let mut jobs = 1;
+    let mut format = OutputFormat::Text;
+
+    while let Some(arg) = parser.next()? {
+        match arg {
+            Arg::Long("help") | Arg::Short('h') => {
+                print!("{}", HELP);
+                return Ok(Status::Success);
+            },
+            Arg::Long("count") | Arg::Short('n') => count = parser.parse()?,
+            Arg::Long("input") | Arg::Short('i') => input = Some(parser.value()?),
+            Arg::Long("jobs") | Arg::Short('j') => jobs = match parser.parse()? {
+                0 => thread::available_parallelism().map_or(1, |jobs| jobs.get()),
+                jobs => jobs
+            },
+            Arg::Long("samples")    => { generator.samples(parser.parse()?); },
+            Arg::Long("iterations") => { generator.iterations(parser.parse()?); },
+            Arg::Long("removals")   => { generator.removals(parser.parse()?); },
+            Arg::Long("format") => format = OutputFormat::parse(parser)?,
+            arg => return Err(arg.unexpected().into())
+        }
+    }
+
+    let start = Instant::now();
+
+    let (source, puzzles) = match &input {
+        Some(input) => {
+            let contents = batch::read(input)?;
+            let puzzles: Vec<(usize, String)> = batch::lines(&contents).iter()
+                .map(|&(line, text)| (line, text.to_string()))
+                .collect();
+            (format!("read from {}", input), Some(puzzles))
+        },
+        None => (String::from("generated"), None)
+    };
+
+    let total = puzzles.as_ref().map_or(count, |puzzles| puzzles.len());
+    let next = AtomicUsize::new(0);
+    let samples = Mutex::new(vec![]);
+    let failures = Mutex::new(vec![]);
+
+    thread::scope(|scope| {
+        for _ in 0..std::cmp::max(jobs, 1) {
+            let (next, samples, failures, puzzles, generator) = (&next, &samples, &failures, &puzzles, &generator);
+
+            scope.spawn(move || loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                if i >= total {
+                    break;
+                }
+
+                let sample = match puzzles {
+                    Some(puzzles) => parse_puzzle(&puzzles[i].1)
+                        .map_err(|err| format!("invalid puzzle: {}", err))
+                        .and_then(|puzzle| analyze(&puzzle))
+                        .map_err(|err| (puzzles[i].0, err)),
+                    None => analyze(&generator.generate().puzzle).map_err(|err| (i + 1, err))
+                };
+
+                match sample {
+                    Ok(sample) => samples.lock().unwrap().push(sample),
+                    Err(failure) => failures.lock().unwrap().push(failure)
+                }
+            });
+        }
+    });
+
+    let samples = samples.into_inner().unwrap();
+    let mut failures = failures.into_inner().unwrap();
+    failures.sort();
+
+    for (line, err) in failures.iter() {
+        eprintln!("line {}: {}", line, err);
+    }
+
+    let histograms = histograms(&samples);
+
+    match format {
+        OutputFormat::Text => {
+            println!("Puzzles: {} {} in {:.2?}", samples.len(), source, start.elapsed());
+            for histogram in histograms.iter() {
+                println!();
+                histogram.print();
+            }
+        },
+        OutputFormat::Csv => {
+            println!("histogram,bucket,count");
+            for histogram in histograms.iter() {
+                for (bucket, count) in histogram.buckets.iter() {
+                    println!("{},{},{}", histogram.name, csv(bucket), count);
+                }
+            }
+        },
+        OutputFormat::Json => {
+            let fields: Vec<String> = histograms.iter()
+                .map(|histogram| {
+                    let buckets: Vec<String> = histogram.buckets.iter()
+                        .map(|(bucket, count)| format!("{}:{}", string(bucket), count))
+                        .collect();
+                    format!("{}:{{{}}}", string(histogram.name), buckets.join(","))
+                })
+                .collect();
+            println!("{{\"puzzles\":{},{}}}", samples.len(), fields.join(","));
+        }
+    }
+
+    match failures.is_empty() {
+        true  => Ok(Status::Success),
+        false => Ok(Status::Rejected)
+    }
+}
+
+fn analyze(puzzle: &[u8; 81]) -> Result<Sample, String> {
+    let grade = grade(puzzle).map_err(|err| err.to_string())?;
+    let output = Solver::with_limit(1).solve(puzzle);
+
+    Ok(Sample {
+        difficulty: grade.difficulty,
+        level:      grade.level.to_string(),
+        givens:     puzzle.iter().filter(|&&value| value != 0).count(),
+        branches:   output.result.first().map_or(0, |result| result.branches),
+        iterations: output.iterations,
+        techniques: grade.techniques,
+    })
+}
+
+struct Histogram {
+    name:       &'static str,
+    title:      &'static str,
+    buckets:    Vec<(String, usize)>,
+}
+
+fn histograms(samples: &[Sample]) -> Vec<Histogram> {
+    let mut levels = BTreeMap::new();
+    let mut techniques = BTreeMap::new();
+    let mut uses = BTreeMap::new();
+
+    for sample in samples.iter() {
+        *levels.entry(sample.level.clone()).or_insert(0) += 1;
+
+        for (&technique, &count) in sample.techniques.iter() {
+            *techniques.entry(technique).or_insert(0) += 1;
+            *uses.entry(technique).or_insert(0) += count;
+        }
+    }
+
+    // Levels in order of difficulty rather than alphabetically
+    let mut levels: Vec<(String, usize)> = levels.into_iter().collect();
+    levels.sort_by_key(|(level, _)| ["Easy", "Medium", "Hard", "Expert"].iter().position(|name| name == level));
+
+    let named = |counts: BTreeMap<Technique, usize>| -> Vec<(String, usize)> {
+        counts.into_iter().map(|(technique, count)| (technique_key(technique).to_string(), count)).collect()
+    };
+
+    vec![
+        Histogram {
+            name:       "difficulty",
+            title:      "Difficulty",
+            buckets:    bucket(samples.iter().map(|sample| sample.difficulty).collect()),
+        },
+        Histogram { name: "level", title: "Level", buckets: levels },
+        Histogram {
+            name:       "givens",
+            title:      "Givens",
+            buckets:    bucket(samples.iter().map(|sample| sample.givens).collect()),
+        },
+        Histogram {
+            name:       "branches",
+            title:      "Branches",
+            buckets:    bucket(samples.iter().map(|sample| sample.branches).collect()),
+        },
+        Histogram {
+            name:       "iterations",
+            title:      "Iterations",
+            buckets:    bucket(samples.iter().map(|sample| sample.iterations).collect()),
+        },
+        Histogram { name: "techniques", title: "Puzzles needing each technique", buckets: named(techniques) },
+        Histogram { name: "technique_uses", title: "Uses of each technique", buckets: named(uses) },
+    ]
+}
+
+// Groups values into at most MAX_BUCKETS buckets of a round width (1, 2, 5, 10, 20, 50, ...), leaving out empty buckets
+fn bucket(values: Vec<usize>) -> Vec<(String, usize)> {
+    const MAX_BUCKETS: usize = 20;
+
+    let (min, max) = match (values.iter().min(), values.iter().max()) {
+        (Some(&min), Some(&max)) => (min, max),
+        _ => return vec![]
+    };
+
+    let (mut width, mut scale) = (1, 1);
+    while max / width - min / width >= MAX_BUCKETS {
+        width = match width / scale {
+            1 => 2 * scale,
+            2 => 5 * scale,
+            _ => {
+                scale *= 10;
+                scale
+            }
+        };
+    }
+
+    let mut counts = BTreeMap::new();
+    for value in values {
+        *counts.entry(value / width).or_insert(0) += 1;
+    }
+
+    counts.into_iter()
+        .map(|(bucket, count)| match width {
+            1 => (bucket.to_string(), count),
+            _ => (format!("{}-{}", bucket * width, (bucket + 1) * width - 1), count)
+        })
+        .collect()
+}
+
+impl Histogram {
+    const BAR_WIDTH: usize = 40;
+
+    fn print(&self) {
+        println!("{}", self.title);
+
+        let label = self.buckets.iter().map(|(bucket, _)| bucket.len()).max().unwrap_or(0);
+        let most = self.buckets.iter().map(|&(_, count)| count).max().unwrap_or(0);
+
+        for (bucket, count) in self.buckets.iter() {
+            let bar = (count * Self::BAR_WIDTH).div_ceil(std::cmp::max(most, 1));
+            println!("  {:>label$}  {:<width$}  {}", bucket, "#".repeat(bar), count, label = label, width = Self::BAR_WIDTH);
+        }
+    }
+}
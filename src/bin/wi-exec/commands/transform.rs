@@ -0,0 +1,157 @@
+use crate :: {
+    Flags,
+    args :: { Arg, Parser },
+    commands :: { Error, PuzzleArg, Status, flat },
+    report   :: { OutputFormat, Report },
+};
+
+use waffle_iron :: Transform;
+
+pub const HELP: &str = "\
+Rewrites a puzzle into an equivalent one. Transformations are applied in the order they are given.
+
+Usage: wi-exec transform [OPTIONS] <PUZZLE>
+
+Arguments:
+  <PUZZLE>                  81 cells, in any of the notations accepted by the parser
+
+Transformations:
+      --rotate <DEGREES>    Rotate clockwise by 90, 180 or 270 degrees
+      --flip <AXIS>         Mirror left to right (horizontal) or top to bottom (vertical)
+      --transpose           Swap rows and columns
+      --relabel <DIGITS>    Replace 1 through 9 with the given digits, ex: 912345678
+      --swap-rows <A,B>     Swap two rows (1-9) of the same band
+      --swap-columns <A,B>  Swap two columns (1-9) of the same stack
+      --swap-bands <A,B>    Swap two bands of three rows (1-3)
+      --swap-stacks <A,B>   Swap two stacks of three columns (1-3)
+      --random              Apply a random combination of all of the above
+
+Options:
+  -p, --sample <N>          Transform one of the built-in sample puzzles (0-8) instead
+      --flat                Print the result as a line of digits instead of a grid
+      --ascii               Draw grids with ASCII characters only
+      --blank               Leave empty cells blank instead of drawing them as '.'
+      --format <FORMAT>     Output format: text, json or csv [default: text]
+  -h, --help                Print this help
+
+Exit status: 0 on success, 1 if the puzzle can't be parsed, 2 on usage errors.
+";
+
+pub fn run(parser: &mut Parser) -> Result<Status, Error> {
+    let mut flags = Flags::empty();
+    let mut puzzle = PuzzleArg::new();
+    let mut format = OutputFormat::Text;
+    let mut transforms = vec![];
+
+    while let Some(arg) = parser.next()? {
+        let transform = match arg {
+            Arg::Long("help") | Arg::Short('h') => {
+                print!("{}", HELP);
+                return Ok(Status::Success);
+            },
+            Arg::Long("rotate")         => rotate(parser)?,
+            Arg::Long("flip")           => flip(parser)?,
+            Arg::Long("transpose")      => Transform::transpose(),
+            Arg::Long("relabel")        => relabel(parser)?,
+            Arg::Long("swap-rows")      => swap(parser, 9, Transform::swap_rows)?,
+            Arg::Long("swap-columns")   => swap(parser, 9, Transform::swap_columns)?,
+            Arg::Long("swap-bands")     => swap(parser, 3, Transform::swap_bands)?,
+            Arg::Long("swap-stacks")    => swap(parser, 3, Transform::swap_stacks)?,
+            Arg::Long("random")         => Transform::random(),
+            Arg::Long("sample") | Arg::Short('p') => {
+                puzzle.sample(parser)?;
+                continue;
+            },
+            Arg::Value(value) => {
+                puzzle.value(value)?;
+                continue;
+            },
+            Arg::Long("format") => {
+                format = OutputFormat::parse(parser)?;
+                continue;
+            },
+            arg => match Flags::from_arg(&arg).filter(|&flag| flag != Flags::VERBOSE) {
+                Some(flag) => {
+                    flags.insert(flag);
+                    continue;
+                },
+                None => return Err(arg.unexpected().into())
+            }
+        };
+
+        transforms.push(transform);
+    }
+
+    let puzzle = puzzle.get()?;
+    let transform = transforms.iter().fold(Transform::identity(), |combined, next| combined.then(next));
+
+    let transformed = transform.apply(&puzzle);
+
+    match format {
+        OutputFormat::Text => flags.print(&transformed),
+        format => {
+            let mut report = Report::new("transform", &puzzle);
+            report.result = Some(flat(&transformed));
+            report.print(format);
+        }
+    }
+
+    Ok(Status::Success)
+}
+
+fn rotate(parser: &mut Parser) -> Result<Transform, Error> {
+    let value = parser.value()?;
+    let turns = match value.as_str() {
+        "90"    => 1,
+        "180"   => 2,
+        "270"   => 3,
+        _ => return Err(parser.invalid(&value, "expected 90, 180 or 270").into())
+    };
+
+    Ok((0..turns).fold(Transform::identity(), |transform, _| transform.then(&Transform::rotate())))
+}
+
+fn flip(parser: &mut Parser) -> Result<Transform, Error> {
+    let value = parser.value()?;
+    match value.as_str() {
+        "horizontal" | "h"  => Ok(Transform::flip_horizontal()),
+        "vertical" | "v"    => Ok(Transform::flip_vertical()),
+        _ => Err(parser.invalid(&value, "expected horizontal or vertical").into())
+    }
+}
+
+fn relabel(parser: &mut Parser) -> Result<Transform, Error> {
+    let value = parser.value()?;
+    let mut digits = [0; 9];
+
+    if value.len() == 9 {
+        for (digit, c) in digits.iter_mut().zip(value.chars()) {
+            *digit = c.to_digit(10).unwrap_or(0) as u8;
+        }
+    }
+
+    match Transform::relabel(&digits) {
+        Some(transform) => Ok(transform),
+        None => Err(parser.invalid(&value, "expected each of the digits 1 through 9 exactly once").into())
+    }
+}
+
+// Lines are numbered from 1 on the command line
+fn swap(parser: &mut Parser, count: usize, swap: fn(usize, usize) -> Option<Transform>) -> Result<Transform, Error> {
+    let value = parser.value()?;
+
+    let lines: Vec<usize> = value.split(',').filter_map(|line| line.trim().parse().ok()).collect();
+    if let [a @ 1..=9, b @ 1..=9] = lines[..] {
+        if a <= count && b <= count {
+            if let Some(transform) = swap(a - 1, b - 1) {
+                return Ok(transform);
+            }
+        }
+    }
+
+    let reason = match count {
+        3 => String::from("expected two numbers from 1 to 3, ex: 1,3"),
+        _ => String::from("expected two numbers from 1 to 9 within the same group of three, ex: 4,6")
+    };
+    Err(parser.invalid(&value, &reason).into())
+}
@@ -0,0 +1,93 @@
+use crate :: {
+    args :: { Arg, Parser },
+    commands :: { Error, PuzzleArg, Status, batch :: Batch, flat },
+    report   :: { OutputFormat, Report },
+};
+
+use waffle_iron :: validate;
+
+pub const HELP: &str = "\
+Checks that a puzzle is well formed: none of its givens conflict, and it has exactly one solution.
+
+Usage: wi-exec validate [OPTIONS] <PUZZLE>
+
+Arguments:
+  <PUZZLE>              81 cells, in any of the notations accepted by the parser
+
+Options:
+  -p, --sample <N>      Validate one of the built-in sample puzzles (0-8) instead
+  -i, --input <PATH>    Validate every puzzle of a file (one per line), or of standard input with -
+  -j, --jobs <N>        Number of puzzles validated in parallel with --input, 0 uses every CPU [default: 1]
+      --format <FORMAT> Output format: text, json or csv [default: text]
+  -q, --quiet           Don't print anything (other than the --input summary), only set the exit status
+  -h, --help            Print this help
+
+With --input, each line of output holds a puzzle followed by \"valid\", or by the reason it is invalid. A summary of
+counts, failures and timings is written to standard error.
+
+Exit status: 0 if the puzzle (every puzzle) is valid, 1 if it isn't, 2 on usage errors, 3 if the input can't be read.
+";
+
+pub fn run(parser: &mut Parser) -> Result<Status, Error> {
+    let mut puzzle = PuzzleArg::new();
+    let mut batch = Batch::new("validate");
+    let mut format = OutputFormat::Text;
+    let mut quiet = false;
+
+    while let Some(arg) = parser.next()? {
+        match arg {
+            Arg::Long("help") | Arg::Short('h') => {
+                print!("{}", HELP);
+                return Ok(Status::Success);
+            },
+            Arg::Long("quiet") | Arg::Short('q') => quiet = true,
+            Arg::Long("sample") | Arg::Short('p') => puzzle.sample(parser)?,
+            Arg::Value(value) => puzzle.value(value)?,
+            Arg::Long("input") | Arg::Short('i') => batch.input(parser)?,
+            Arg::Long("jobs") | Arg::Short('j') => batch.jobs(parser)?,
+            Arg::Long("format") => format = OutputFormat::parse(parser)?,
+            arg => return Err(arg.unexpected().into())
+        }
+    }
+
+    if batch.is_enabled() {
+        return batch.quiet(quiet).format(format).run(&puzzle, report, |_| String::from("valid"));
+    }
+
+    let puzzle = puzzle.get()?;
+
+    if format != OutputFormat::Text {
+        let time = std::time::Instant::now();
+        let mut report = report(&puzzle);
+        report.time(time.elapsed());
+
+        if !quiet {
+            report.print(format);
+        }
+
+        return Ok(if report.is_ok() { Status::Success } else { Status::Rejected });
+    }
+
+    match validate(&puzzle) {
+        Ok(_) => {
+            if !quiet { println!("Valid"); }
+            Ok(Status::Success)
+        },
+        Err(err) => {
+            if !quiet { println!("Invalid: {}", err); }
+            Ok(Status::Rejected)
+        }
+    }
+}
+
+// A valid puzzle's report includes its (only) solution
+fn report(puzzle: &[u8; 81]) -> Report {
+    let mut report = Report::new("validate", puzzle);
+
+    match validate(puzzle) {
+        Ok(solution) => report.solutions = Some(vec![flat(&solution)]),
+        Err(err) => { report.error(err); }
+    };
+
+    report
+}
@@ -1,3 +1,6 @@
+// Debugging aid for inspecting structural sharing between states; not wired into any command.
+#![allow(dead_code)]
+
 use std :: {
     hash :: { Hash, BuildHasher },
     cmp  :: { Eq, PartialEq },
@@ -52,10 +55,10 @@ where   T: ReadCollection<'a, K, V>,
     }
 }
 
-pub fn compare<'a, K:'a, V:'a, T>(old_collection: &'a T, new_collection: &'a T, compare: Comparison)
+pub fn compare<'a, K, V, T>(old_collection: &'a T, new_collection: &'a T, compare: Comparison)
 where   T: ReadCollection<'a, K, V>,
-        K: Display + PartialEq, 
-        V: Debug   + PartialEq + Default {
+        K: 'a + Display + PartialEq,
+        V: 'a + Debug   + PartialEq + Default {
 
     let mut ptr_diff_count = 0;
     let mut val_diff_count = 0;
@@ -63,7 +66,7 @@ where   T: ReadCollection<'a, K, V>,
 
     for (key, old) in old_collection.iter() {
         let empty = V::default();
-        let new = match new_collection.get(&key) {
+        let new = match new_collection.get(key) {
             Some(thing) => thing,
             None        => &empty
         };
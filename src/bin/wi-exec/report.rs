@@ -0,0 +1,271 @@
+use crate :: {
+    args :: { Parser },
+    commands :: { Error, flat, location },
+};
+
+use waffle_iron :: { Grade, Hint, Technique, output :: SolverOutput };
+
+use std :: { fmt::Write, time::Duration };
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum OutputFormat {
+    Text,
+    Json,
+    Csv,
+}
+
+impl OutputFormat {
+    pub fn parse(parser: &mut Parser) -> Result<OutputFormat, Error> {
+        let value = parser.value()?;
+
+        match value.to_ascii_lowercase().as_str() {
+            "text"  => Ok(OutputFormat::Text),
+            "json"  => Ok(OutputFormat::Json),
+            "csv"   => Ok(OutputFormat::Csv),
+            _ => Err(parser.invalid(&value, "expected text, json or csv").into())
+        }
+    }
+}
+
+// The result of running a command against a single puzzle, as printed by --format json and --format csv. Every field is
+// always written, using null (or an empty CSV column) for anything the command doesn't compute. See the "Machine
+// readable output" section of the README for the documented schema; fields may be added, but never renamed or removed.
+pub struct Report {
+    pub command:    &'static str,
+    pub puzzle:     String,
+    pub error:      Option<String>,
+    pub solutions:  Option<Vec<String>>,
+    pub iterations: Option<usize>,
+    pub branches:   Option<usize>,
+    pub steps:      Option<usize>,
+    pub difficulty: Option<usize>,
+    pub level:      Option<String>,
+    pub techniques: Option<Vec<(Technique, usize)>>,
+    pub hint:       Option<Hint>,
+    pub result:     Option<String>,
+    pub time:       Duration,
+    pub corpus:     Option<String>,
+}
+
+impl Report {
+    pub const CSV_HEADER: &'static str = "command,puzzle,status,error,solution_count,solutions,iterations,branches,steps,\
+                                          difficulty,level,techniques,hint,result,time_ms,corpus";
+
+    pub fn new(command: &'static str, puzzle: &[u8; 81]) -> Self {
+        Self::from_text(command, &flat(puzzle))
+    }
+
+    // For input that couldn't be parsed as a puzzle
+    pub fn from_text(command: &'static str, puzzle: &str) -> Self {
+        Self {
+            command,
+            puzzle:     puzzle.to_string(),
+            error:      None,
+            solutions:  None,
+            iterations: None,
+            branches:   None,
+            steps:      None,
+            difficulty: None,
+            level:      None,
+            techniques: None,
+            hint:       None,
+            result:     None,
+            time:       Duration::default(),
+            corpus:     None,
+        }
+    }
+
+    pub fn error(&mut self, error: impl ToString) -> &mut Self {
+        self.error = Some(error.to_string());
+
+        self
+    }
+
+    pub fn time(&mut self, time: Duration) -> &mut Self {
+        self.time = time;
+
+        self
+    }
+
+    pub fn solver(&mut self, output: &SolverOutput) -> &mut Self {
+        self.solutions  = Some(output.result.iter().map(|result| flat(&result.solution)).collect());
+        self.iterations = Some(output.iterations);
+        self.branches   = output.result.first().map(|result| result.branches);
+        self.steps      = Some(output.steps);
+
+        if output.result.is_empty() {
+            self.error("no solution");
+        }
+
+        self
+    }
+
+    pub fn grade(&mut self, grade: &Grade) -> &mut Self {
+        self.difficulty = Some(grade.difficulty);
+        self.level      = Some(grade.level.to_string());
+        self.techniques = Some(grade.techniques.iter().map(|(&technique, &count)| (technique, count)).collect());
+
+        self
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none()
+    }
+
+    pub fn status(&self) -> &'static str {
+        match self.error {
+            None    => "ok",
+            Some(_) => "rejected"
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        let mut json = String::from("{");
+
+        let hint = self.hint.map(|hint| format!(
+            "{{\"index\":{},\"row\":{},\"column\":{},\"value\":{},\"technique\":{}}}",
+            hint.index, hint.index / 9 + 1, hint.index % 9 + 1, hint.value, string(technique_key(hint.technique))
+        ));
+
+        let techniques = self.techniques.as_ref().map(|techniques| {
+            let fields: Vec<String> = techniques.iter()
+                .map(|&(technique, count)| format!("{}:{}", string(technique_key(technique)), count))
+                .collect();
+            format!("{{{}}}", fields.join(","))
+        });
+
+        let solutions = self.solutions.as_ref().map(|solutions| {
+            let items: Vec<String> = solutions.iter().map(|solution| string(solution)).collect();
+            format!("[{}]", items.join(","))
+        });
+
+        let fields = [
+            ("command",         string(self.command)),
+            ("puzzle",          string(&self.puzzle)),
+            ("status",          string(self.status())),
+            ("error",           optional(self.error.as_deref().map(string))),
+            ("solution_count",  optional(self.solutions.as_ref().map(|solutions| solutions.len().to_string()))),
+            ("solutions",       optional(solutions)),
+            ("iterations",      optional(self.iterations.map(|n| n.to_string()))),
+            ("branches",        optional(self.branches.map(|n| n.to_string()))),
+            ("steps",           optional(self.steps.map(|n| n.to_string()))),
+            ("difficulty",      optional(self.difficulty.map(|n| n.to_string()))),
+            ("level",           optional(self.level.as_deref().map(string))),
+            ("techniques",      optional(techniques)),
+            ("hint",            optional(hint)),
+            ("result",          optional(self.result.as_deref().map(string))),
+            ("time_ms",         milliseconds(self.time)),
+            ("corpus",          optional(self.corpus.as_deref().map(string))),
+        ];
+
+        for (i, (key, value)) in fields.iter().enumerate() {
+            if i > 0 { json.push(','); }
+            let _ = write!(json, "{}:{}", string(key), value);
+        }
+
+        json.push('}');
+        json
+    }
+
+    pub fn to_csv(&self) -> String {
+        let techniques = self.techniques.as_ref().map(|techniques| {
+            let items: Vec<String> = techniques.iter()
+                .map(|&(technique, count)| format!("{}={}", technique_key(technique), count))
+                .collect();
+            items.join(";")
+        });
+
+        let hint = self.hint.map(|hint| {
+            format!("{}={} {}", location(hint.index), hint.value, technique_key(hint.technique))
+        });
+
+        let columns = [
+            self.command.to_string(),
+            self.puzzle.clone(),
+            self.status().to_string(),
+            self.error.clone().unwrap_or_default(),
+            self.solutions.as_ref().map(|solutions| solutions.len().to_string()).unwrap_or_default(),
+            self.solutions.as_ref().map(|solutions| solutions.join(" ")).unwrap_or_default(),
+            self.iterations.map(|n| n.to_string()).unwrap_or_default(),
+            self.branches.map(|n| n.to_string()).unwrap_or_default(),
+            self.steps.map(|n| n.to_string()).unwrap_or_default(),
+            self.difficulty.map(|n| n.to_string()).unwrap_or_default(),
+            self.level.clone().unwrap_or_default(),
+            techniques.unwrap_or_default(),
+            hint.unwrap_or_default(),
+            self.result.clone().unwrap_or_default(),
+            milliseconds(self.time),
+            self.corpus.clone().unwrap_or_default(),
+        ];
+
+        let columns: Vec<String> = columns.iter().map(|column| csv(column)).collect();
+        columns.join(",")
+    }
+
+    // Prints a single report in the given format; text output is left to the commands themselves
+    pub fn print(&self, format: OutputFormat) {
+        Report::print_header(format);
+        self.print_line(format);
+    }
+
+    // Commands printing several reports start with the header, then print a line for each of them
+    pub fn print_header(format: OutputFormat) {
+        if format == OutputFormat::Csv {
+            println!("{}", Report::CSV_HEADER);
+        }
+    }
+
+    pub fn print_line(&self, format: OutputFormat) {
+        match format {
+            OutputFormat::Json  => println!("{}", self.to_json()),
+            OutputFormat::Csv   => println!("{}", self.to_csv()),
+            OutputFormat::Text  => ()
+        }
+    }
+}
+
+// Technique names as written in JSON and CSV output. These are part of the schema and must not change.
+pub fn technique_key(technique: Technique) -> &'static str {
+    match technique {
+        Technique::NakedSingle      => "naked_single",
+        Technique::HiddenSingle     => "hidden_single",
+        Technique::LockedCandidates => "locked_candidates",
+        Technique::NakedPair        => "naked_pair",
+        Technique::Guess            => "guess",
+    }
+}
+
+pub fn milliseconds(time: Duration) -> String {
+    format!("{:.3}", time.as_secs_f64() * 1000.0)
+}
+
+fn optional(value: Option<String>) -> String {
+    value.unwrap_or_else(|| String::from("null"))
+}
+
+pub fn string(text: &str) -> String {
+    let mut json = String::with_capacity(text.len() + 2);
+    json.push('"');
+
+    for c in text.chars() {
+        match c {
+            '"'     => json.push_str("\\\""),
+            '\\'    => json.push_str("\\\\"),
+            '\n'    => json.push_str("\\n"),
+            '\r'    => json.push_str("\\r"),
+            '\t'    => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => { let _ = write!(json, "\\u{:04x}", c as u32); },
+            c => json.push(c)
+        }
+    }
+
+    json.push('"');
+    json
+}
+
+pub fn csv(text: &str) -> String {
+    match text.contains([',', '"', '\n', '\r']) {
+        true  => format!("\"{}\"", text.replace('"', "\"\"")),
+        false => text.to_string()
+    }
+}
@@ -0,0 +1,116 @@
+use std :: {
+    io      :: { self, Read, Write },
+    process :: { Command, Stdio },
+};
+
+// A full screen terminal session for interactive commands. Rather than pulling in a terminal library, raw mode is set
+// up with stty and the screen is drawn with plain ANSI escape sequences, which covers every Unix terminal (and Windows
+// terminals that run a Unix shell). The terminal's previous settings are restored when the session is dropped.
+pub struct Terminal {
+    settings: String,
+}
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum Key {
+    Up,
+    Down,
+    Left,
+    Right,
+    Char(char),
+    Backspace,
+    Delete,
+    Escape,
+    // Ctrl-C and Ctrl-D; raw mode turns them into plain input instead of signals
+    Interrupt,
+}
+
+impl Terminal {
+    pub fn open() -> io::Result<Terminal> {
+        let settings = stty(&["-g"])?;
+
+        // Reads return as soon as a key is pressed, and wait a tenth of a second for the rest of an escape sequence
+        stty(&["raw", "-echo", "min", "1", "time", "1"])?;
+
+        // Alternate screen, hidden cursor
+        print!("\x1b[?1049h\x1b[?25l");
+        io::stdout().flush()?;
+
+        Ok(Terminal { settings: settings.trim().to_string() })
+    }
+
+    // Waits for the next key press; a single read can hold several keys when they are typed (or pasted) quickly
+    pub fn keys(&mut self) -> io::Result<Vec<Key>> {
+        let mut buffer = [0u8; 64];
+        let count = io::stdin().read(&mut buffer)?;
+
+        if count == 0 {
+            return Ok(vec![Key::Interrupt]);
+        }
+
+        Ok(parse(&buffer[..count]))
+    }
+
+    // Replaces the whole screen. Raw mode also turns off newline translation, so lines are ended with \r\n.
+    pub fn draw(&mut self, screen: &str) -> io::Result<()> {
+        let mut output = String::from("\x1b[H\x1b[2J");
+        for line in screen.lines() {
+            output.push_str(line);
+            output.push_str("\x1b[K\r\n");
+        }
+
+        let stdout = io::stdout();
+        let mut stdout = stdout.lock();
+        stdout.write_all(output.as_bytes())?;
+        stdout.flush()
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        print!("\x1b[?25h\x1b[?1049l");
+        let _ = io::stdout().flush();
+        let _ = stty(&[&self.settings]);
+    }
+}
+
+fn stty(args: &[&str]) -> io::Result<String> {
+    let output = Command::new("stty")
+        .args(args)
+        .stdin(Stdio::inherit())
+        .stderr(Stdio::null())
+        .output()?;
+
+    match output.status.success() {
+        true  => Ok(String::from_utf8_lossy(&output.stdout).into_owned()),
+        false => Err(io::Error::other("standard input is not a terminal"))
+    }
+}
+
+fn parse(mut input: &[u8]) -> Vec<Key> {
+    let mut keys = vec![];
+
+    while let Some((&byte, rest)) = input.split_first() {
+        input = rest;
+
+        let key = match byte {
+            0x1b => match input {
+                [b'[', b'A', ..] | [b'O', b'A', ..] => { input = &input[2..]; Key::Up },
+                [b'[', b'B', ..] | [b'O', b'B', ..] => { input = &input[2..]; Key::Down },
+                [b'[', b'C', ..] | [b'O', b'C', ..] => { input = &input[2..]; Key::Right },
+                [b'[', b'D', ..] | [b'O', b'D', ..] => { input = &input[2..]; Key::Left },
+                [b'[', b'3', b'~', ..]              => { input = &input[3..]; Key::Delete },
+                // Any other sequence is dropped along with the rest of the read
+                [b'[', ..] | [b'O', ..]             => { input = &[]; continue },
+                _ => Key::Escape
+            },
+            0x03 | 0x04 => Key::Interrupt,
+            0x08 | 0x7f => Key::Backspace,
+            byte if byte.is_ascii() => Key::Char(byte as char),
+            _ => continue
+        };
+
+        keys.push(key);
+    }
+
+    keys
+}
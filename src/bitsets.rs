@@ -13,6 +13,7 @@ pub mod traits {
     pub trait BitSetBase<B, T> where B: UnsignedInteger {
         fn bits(&self) -> B;
         fn from_bits(bits: B) -> Option<T>;
+        #[allow(dead_code)]
         fn is_empty(&self) -> bool;
     }
 
@@ -22,6 +23,7 @@ pub mod traits {
 
         fn len(&self) -> usize;
         fn iter(&self) -> Self::Iter;
+        #[allow(dead_code)]
         fn is_empty(&self) -> bool;
     }
 }
@@ -0,0 +1,291 @@
+use crate :: {
+    generator   :: output :: { GeneratorOutput, PuzzleGeneratorOutput },
+    svg         :: { SvgRenderer, Theme },
+    formats     :: escape,
+    puzzle      :: Puzzle,
+};
+
+use std::fmt::Write;
+
+// Page dimensions in CSS pixels (96 per inch)
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PageSize {
+    A4,
+    Letter,
+}
+
+impl PageSize {
+    pub fn dimensions(self) -> (u32, u32) {
+        match self {
+            PageSize::A4        => (794, 1123),
+            PageSize::Letter    => (816, 1056),
+        }
+    }
+
+    fn css(self) -> &'static str {
+        match self {
+            PageSize::A4        => "210mm 297mm",
+            PageSize::Letter    => "8.5in 11in",
+        }
+    }
+}
+
+struct Entry {
+    puzzle:     Puzzle,
+    solution:   Puzzle,
+    difficulty: usize,
+}
+
+// Lays out generated puzzles as printable pages: a number of puzzles per page, each labeled with its number and
+// difficulty, followed by a separate section holding all of the answer keys.
+pub struct Book {
+    title:              Option<String>,
+    page_size:          PageSize,
+    puzzles_per_page:   usize,
+    answers_per_page:   usize,
+    theme:              Theme,
+    label:              fn(usize) -> String,
+    entries:            Vec<Entry>,
+}
+
+impl Default for Book {
+    fn default() -> Self {
+        Self {
+            title:              None,
+            page_size:          PageSize::A4,
+            puzzles_per_page:   4,
+            answers_per_page:   9,
+            theme:              Theme::print(),
+            label:              |difficulty| format!("Difficulty: {}", difficulty),
+            entries:            Vec::default(),
+        }
+    }
+}
+
+impl Book {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn title(&mut self, title: &str) -> &mut Self {
+        self.title = Some(title.to_string());
+
+        self
+    }
+
+    pub fn page_size(&mut self, page_size: PageSize) -> &mut Self {
+        self.page_size = page_size;
+
+        self
+    }
+
+    // Anything beyond 3x3 grids per page becomes too small to be played on paper
+    pub fn puzzles_per_page(&mut self, count: usize) -> &mut Self {
+        self.puzzles_per_page = count.clamp(1, 9);
+
+        self
+    }
+
+    pub fn answers_per_page(&mut self, count: usize) -> &mut Self {
+        self.answers_per_page = count.clamp(1, 16);
+
+        self
+    }
+
+    pub fn theme(&mut self, theme: Theme) -> &mut Self {
+        self.theme = theme;
+
+        self
+    }
+
+    // Controls the text printed underneath each puzzle, given the puzzle's difficulty score
+    pub fn difficulty_label(&mut self, label: fn(usize) -> String) -> &mut Self {
+        self.label = label;
+
+        self
+    }
+
+    pub fn add(&mut self, output: &GeneratorOutput) -> &mut Self {
+        self.entries.push(Entry {
+            puzzle:     Puzzle::from(output.puzzle),
+            solution:   Puzzle::from(output.solution),
+            difficulty: output.difficulty,
+        });
+
+        self
+    }
+
+    // Puzzles of any layout, each drawn on its own board. Larger boards are drawn with smaller cells to fit their slot.
+    pub fn add_puzzle(&mut self, output: &PuzzleGeneratorOutput) -> &mut Self {
+        self.entries.push(Entry {
+            puzzle:     output.puzzle.clone(),
+            solution:   output.solution.clone(),
+            difficulty: output.difficulty,
+        });
+
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    // Every page as a standalone SVG document: the puzzle pages first, then the answer keys.
+    pub fn pages(&self) -> Vec<String> {
+        let numbered: Vec<(usize, &Entry)> = self.entries.iter().enumerate().map(|(i, entry)| (i + 1, entry)).collect();
+        let mut pages = vec![];
+
+        for (page, entries) in numbered.chunks(self.puzzles_per_page).enumerate() {
+            let heading = match (&self.title, page) {
+                (Some(title), 0) => title.as_str(),
+                _ => "Puzzles"
+            };
+
+            pages.push(self.page(Section::Puzzles(heading), pages.len() + 1, entries));
+        }
+
+        for entries in numbered.chunks(self.answers_per_page) {
+            pages.push(self.page(Section::Answers, pages.len() + 1, entries));
+        }
+
+        pages
+    }
+
+    // A single HTML document that prints one page per sheet, without any external resources.
+    pub fn to_html(&self) -> String {
+        let (width, height) = self.page_size.dimensions();
+        let title = self.title.as_deref().unwrap_or("Sudoku");
+
+        let mut html = String::new();
+        html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+        html.push_str(&format!("<title>{}</title>\n", escape(title)));
+        html.push_str("<style>\n");
+        html.push_str(&format!("@page {{ size: {}; margin: 0; }}\n", self.page_size.css()));
+        html.push_str("body { margin: 0; }\n");
+        html.push_str(&format!(
+            ".page {{ width: {}px; height: {}px; overflow: hidden; page-break-after: always; break-after: page; }}\n",
+            width, height
+        ));
+        html.push_str("</style>\n</head>\n<body>\n");
+
+        for page in self.pages() {
+            html.push_str("<div class=\"page\">\n");
+            html.push_str(&page);
+            html.push_str("</div>\n");
+        }
+
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+
+    fn page(&self, section: Section, number: usize, entries: &[(usize, &Entry)]) -> String {
+        let mut svg = String::new();
+
+        // Writing into a String can't fail
+        let _ = self.write_page(&mut svg, section, number, entries);
+
+        svg
+    }
+
+    fn write_page<W: Write>(&self, writer: &mut W, section: Section, number: usize, entries: &[(usize, &Entry)])
+    -> std::fmt::Result {
+        let (width, height) = self.page_size.dimensions();
+        let (margin, header, footer) = (48, 56, 32);
+
+        let (heading, per_page) = match section {
+            Section::Puzzles(heading)   => (heading, self.puzzles_per_page),
+            Section::Answers            => ("Answers", self.answers_per_page),
+        };
+
+        let columns = match per_page { 1..=2 => 1, 3..=6 => 2, 7..=9 => 3, _ => 4 };
+        let rows = per_page.div_ceil(columns);
+
+        let slot_width = (width - margin * 2) / columns as u32;
+        let slot_height = (height - margin * 2 - header - footer) / rows as u32;
+
+
+        writeln!(
+            writer,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" viewBox="0 0 {0} {1}" font-family="{2}">"#,
+            width, height, escape(&self.theme.font_family)
+        )?;
+        writeln!(writer, r#"<rect width="{}" height="{}" fill="{}"/>"#, width, height, self.theme.background)?;
+        self.text(writer, width / 2, margin + header / 2, 28, heading)?;
+
+        for (i, &(puzzle_number, entry)) in entries.iter().enumerate() {
+            // Boards are drawn with half a cell of margin around them, and are 1 2/3 cells taller once their title and
+            // footer are included: 10 by 11 2/3 cells for a 9x9 grid
+            let (board_rows, board_cols) = entry.puzzle.layout().board_size();
+            let cell_size = std::cmp::min(
+                slot_width / (board_cols as u32 + 1),
+                slot_height * 3 / (board_rows as u32 * 3 + 8)
+            );
+
+            let mut renderer = SvgRenderer::new();
+            renderer
+                .layout(entry.puzzle.layout())
+                .theme(self.theme.clone())
+                .cell_size(cell_size)
+                .title(&format!("No. {}", puzzle_number));
+
+            let solution = match section {
+                Section::Puzzles(_) => {
+                    renderer.footer(&(self.label)(entry.difficulty));
+                    None
+                },
+                Section::Answers => Some(entry.solution.cells())
+            };
+
+            let x = margin + (i % columns) as u32 * slot_width + (slot_width - renderer.width()) / 2;
+            let y = margin + header + (i / columns) as u32 * slot_height + (slot_height - renderer.height()) / 2;
+
+            writeln!(writer, r#"<g transform="translate({} {})">"#, x, y)?;
+            renderer.write(writer, entry.puzzle.cells(), solution)?;
+            writeln!(writer, "</g>")?;
+        }
+
+        self.text(writer, width / 2, height - margin - footer / 2, 14, &number.to_string())?;
+        writeln!(writer, "</svg>")
+    }
+
+    fn text<W: Write>(&self, writer: &mut W, x: u32, y: u32, size: u32, text: &str) -> std::fmt::Result {
+        writeln!(
+            writer,
+            r#"<text x="{}" y="{}" font-size="{}" fill="{}" text-anchor="middle" dominant-baseline="central">{}</text>"#,
+            x, y, size, self.theme.text, escape(text)
+        )
+    }
+}
+
+#[derive(Copy, Clone)]
+enum Section<'a> {
+    Puzzles(&'a str),
+    Answers,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::Layout;
+
+    #[test]
+    fn puzzles_are_drawn_on_their_own_board() {
+        let layout = Layout::new(3, 2).unwrap();
+        let cells: Vec<u8> = (0 .. 36).map(|index| (index % 6 + 1) as u8).collect();
+        let output = PuzzleGeneratorOutput {
+            puzzle:     Puzzle::empty(&layout),
+            solution:   Puzzle::new(&layout, &cells).unwrap(),
+            difficulty: 0,
+        };
+
+        let pages = Book::new().add_puzzle(&output).pages();
+
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages[1].matches(r#"font-weight="normal""#).count(), 36);
+    }
+}
@@ -0,0 +1,197 @@
+use crate :: {
+    candidates  :: CandidateSet,
+    bitsets     :: traits :: BitSet,
+    layout      :: Layout,
+    random      :: traits :: Random,
+};
+
+// A group of connected cells whose digits add up to sum, without any digit repeating (Killer Sudoku). Cells are given
+// by their index, in row order.
+#[derive(PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Cage {
+    pub sum:    usize,
+    pub cells:  Vec<usize>,
+}
+
+impl Cage {
+    pub fn new(sum: usize, cells: &[usize]) -> Cage {
+        Cage { sum, cells: cells.to_vec() }
+    }
+
+    // The cage's top left cell, where its sum is written
+    pub fn anchor(&self) -> usize {
+        self.cells.iter().copied().min().unwrap_or(0)
+    }
+}
+
+// Every digit that is part of at least one set of count different digits, taken from the candidates, that adds up to
+// the sum. Ex: 2 digits adding up to 4 can only be 1 and 3.
+pub(crate) fn combinations(candidates: CandidateSet, count: usize, sum: usize) -> CandidateSet {
+    let digits: Vec<usize> = candidates.iter().map(|candidate| usize::from(u8::from(candidate))).collect();
+    let mut found = CandidateSet::empty();
+
+    combine(&digits, count, sum, CandidateSet::empty(), &mut found);
+
+    found
+}
+
+fn combine(digits: &[usize], count: usize, sum: usize, chosen: CandidateSet, found: &mut CandidateSet) {
+    if count == 0 {
+        if sum == 0 { found.insert(chosen); }
+        return;
+    }
+
+    // Digits are in ascending order, so once the smallest ones left can't fit in the sum none of the others will
+    let smallest: usize = digits.iter().take(count).sum();
+    if digits.len() < count || smallest > sum {
+        return;
+    }
+
+    for (i, &digit) in digits.iter().enumerate() {
+        if digit > sum { break; }
+        combine(&digits[i + 1 ..], count - 1, sum - digit, chosen | CandidateSet::from(digit as u8), found);
+    }
+}
+
+// Splits a solved grid into random cages of 2 to 5 cells, with a few single cells. Cages are grown one neighbouring
+// cell at a time, skipping cells whose digit the cage already holds.
+pub(crate) fn random(layout: &Layout, solution: &[u8]) -> Vec<Cage> {
+    const SIZES: [usize; 10] = [1, 2, 2, 2, 3, 3, 3, 4, 4, 5];
+
+    let cells: Vec<usize> = (0 .. layout.cells()).collect();
+    let mut caged = vec![false; layout.cells()];
+    let mut cages = vec![];
+
+    for &start in cells.iter().random() {
+        if caged[start] { continue; }
+
+        let target = *SIZES.iter().random().next().unwrap();
+        let mut cage = vec![start];
+        caged[start] = true;
+
+        while cage.len() < target {
+            let bordering: Vec<usize> = cage.iter()
+                .flat_map(|&cell| layout.neighbours(cell))
+                .filter(|&cell| !caged[cell] && cage.iter().all(|&other| solution[other] != solution[cell]))
+                .collect();
+
+            // Picking from an empty list isn't possible, so cages that can't grow any further are left as they are
+            if bordering.is_empty() {
+                break;
+            }
+
+            let next = *bordering.iter().random().next().unwrap();
+            caged[next] = true;
+            cage.push(next);
+        }
+
+        cages.push(cage_of(solution, cage));
+    }
+
+    // Listing cages in the order of their anchor cells keeps their labels in reading order
+    cages.sort_by_key(Cage::anchor);
+    cages
+}
+
+// Takes the given cell out of its cage into one of its own. Whatever is left of the cage is split into as many cages as
+// it takes to keep each of them connected.
+pub(crate) fn split(layout: &Layout, cages: &mut Vec<Cage>, solution: &[u8], cell: usize) {
+    let index = match cages.iter().position(|cage| cage.cells.contains(&cell)) {
+        Some(index) => index,
+        None => return
+    };
+
+    let mut rest: Vec<usize> = cages.remove(index).cells.into_iter().filter(|&other| other != cell).collect();
+    cages.push(cage_of(solution, vec![cell]));
+
+    while let Some(&first) = rest.first() {
+        let mut part = vec![first];
+        let mut pending = vec![first];
+
+        while let Some(next) = pending.pop() {
+            for neighbour in layout.neighbours(next) {
+                if rest.contains(&neighbour) && !part.contains(&neighbour) {
+                    part.push(neighbour);
+                    pending.push(neighbour);
+                }
+            }
+        }
+
+        rest.retain(|other| !part.contains(other));
+        cages.push(cage_of(solution, part));
+    }
+
+    cages.sort_by_key(Cage::anchor);
+}
+
+fn cage_of(solution: &[u8], mut cells: Vec<usize>) -> Cage {
+    cells.sort_unstable();
+    Cage { sum: cells.iter().map(|&cell| usize::from(solution[cell])).sum(), cells }
+}
+
+// Short names for cages in text output: a to z, then aa, ab and so on
+pub(crate) fn label(index: usize) -> String {
+    let mut label = String::new();
+    let mut index = index + 1;
+
+    while index > 0 {
+        index -= 1;
+        label.insert(0, (b'a' + (index % 26) as u8) as char);
+        index /= 26;
+    }
+
+    label
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate :: {
+        layout  :: LayoutError,
+        puzzle  :: Puzzle,
+        solver  :: Solver,
+        grader  :: { validate_puzzle, ValidationError },
+    };
+
+    #[test]
+    fn combinations_leave_out_digits_that_cant_add_up() {
+        let digits = Layout::standard().digits();
+
+        assert_eq!(combinations(digits, 2, 4), CandidateSet::from(1) | CandidateSet::from(3));
+        assert_eq!(combinations(digits, 2, 17), CandidateSet::from(8) | CandidateSet::from(9));
+        assert!(combinations(digits, 2, 18).is_empty());
+    }
+
+    #[test]
+    fn cages_are_checked() {
+        let layout = Layout::new(2, 2).unwrap();
+
+        assert_eq!(layout.with_cages(&[Cage::new(3, &[])]).err(), Some(LayoutError::CageSize(0)));
+        assert_eq!(layout.with_cages(&[Cage::new(3, &[0, 16])]).err(), Some(LayoutError::CageCell(16)));
+        assert_eq!(
+            layout.with_cages(&[Cage::new(3, &[0, 1]), Cage::new(3, &[1, 2])]).err(),
+            Some(LayoutError::CageCell(1))
+        );
+        assert_eq!(layout.with_cages(&[Cage::new(3, &[0, 2])]).err(), Some(LayoutError::DisconnectedCage(0)));
+        assert_eq!(layout.with_cages(&[Cage::new(8, &[0, 1])]).err(), Some(LayoutError::CageSum(0)));
+    }
+
+    #[test]
+    fn solutions_and_givens_follow_the_sums() {
+        let cages = [Cage::new(3, &[0, 1]), Cage::new(7, &[2, 3]), Cage::new(5, &[4, 8]), Cage::new(3, &[15, 14])];
+        let layout = Layout::new(2, 2).unwrap().with_cages(&cages).unwrap();
+
+        let solved = Solver::new().solve_puzzle(&Puzzle::empty(&layout));
+        let cells = solved.result[0].solution.cells();
+        for cage in cages.iter() {
+            assert_eq!(cage.cells.iter().map(|&cell| usize::from(cells[cell])).sum::<usize>(), cage.sum);
+        }
+
+        let mut givens = vec![0; 16];
+        givens[0] = 2;
+        givens[1] = 4;
+        let error = validate_puzzle(&Puzzle::new(&layout, &givens).unwrap()).unwrap_err();
+        assert_eq!(error, ValidationError::CageSum(0));
+    }
+}
@@ -1,25 +1,27 @@
 use crate::{
     sudoku  :: traits :: SudokuState,
     bitsets :: traits :: BitSetBase,
-    indices,
 };
 
 use self::traits::*;
 
+// Candidates are kept one bit per digit, so a layout can have as many digits as these have bits. Digits are written
+// 1-9 then A-Z in text, which only goes up to 35, so a u32 is as wide as is useful: a wider type would take more memory
+// in every state for no more than 3 extra digits. The sets, their flags and Layout::MAX_SIZE all follow from this type
+// though, so changing it is all it takes.
+pub(crate) type CandidateBits = u32;
+
 bitflags! {
-    pub struct CandidateSet: u16 {
-        const C1 = 0x0001;
-        const C2 = 0x0002;
-        const C3 = 0x0004;
-        const C4 = 0x0008;
-        const C5 = 0x0010;
-        const C6 = 0x0020;
-        const C7 = 0x0040;
-        const C8 = 0x0080;
-        const C9 = 0x0100;
+    pub struct CandidateSet: CandidateBits {
+        // Every digit a set can hold. Sets never hold digits past their layout's size.
+        const ALL = CandidateBits::MAX;
     }
 }
 
+impl CandidateSet {
+    pub(crate) const MAX_DIGITS: u8 = CandidateBits::BITS as u8;
+}
+
 impl Default for CandidateSet {
     fn default() -> Self {
         CandidateSet::empty()
@@ -29,45 +31,30 @@ impl Default for CandidateSet {
 impl From<u8> for CandidateSet {
     fn from(item: u8) -> CandidateSet {
         match item {
-            1 => CandidateSet::C1,
-            2 => CandidateSet::C2,
-            3 => CandidateSet::C3,
-            4 => CandidateSet::C4,
-            5 => CandidateSet::C5,
-            6 => CandidateSet::C6,
-            7 => CandidateSet::C7,
-            8 => CandidateSet::C8,
-            9 => CandidateSet::C9,
+            1 ..= CandidateSet::MAX_DIGITS => CandidateSet::from_bits_truncate(1 << (item - 1)),
             _ => CandidateSet::empty()
         }
     }
 }
 
-impl Into<u8> for CandidateSet {
-    fn into(self) -> u8 {
-        match self {
-            CandidateSet::C1 => 1,
-            CandidateSet::C2 => 2,
-            CandidateSet::C3 => 3,
-            CandidateSet::C4 => 4,
-            CandidateSet::C5 => 5,
-            CandidateSet::C6 => 6,
-            CandidateSet::C7 => 7,
-            CandidateSet::C8 => 8,
-            CandidateSet::C9 => 9,
+// Only sets holding a single candidate convert to its digit, anything else gives 0
+impl From<CandidateSet> for u8 {
+    fn from(set: CandidateSet) -> u8 {
+        match set.bits().count_ones() {
+            1 => set.bits().trailing_zeros() as u8 + 1,
             _ => 0
         }
     }
 }
 
-impl BitSetBase<u16, CandidateSet> for CandidateSet { 
+impl BitSetBase<CandidateBits, CandidateSet> for CandidateSet { 
     #[inline]
-    fn bits(&self) -> u16 {
+    fn bits(&self) -> CandidateBits {
         self.bits()
     }
 
     #[inline]
-    fn from_bits(bits: u16) -> Option<CandidateSet> {
+    fn from_bits(bits: CandidateBits) -> Option<CandidateSet> {
         CandidateSet::from_bits(bits)
     }
 
@@ -95,24 +82,42 @@ pub mod traits {
     pub trait Candidates {
         fn init_candidates(&mut self);
         fn update_candidates(&mut self, index: usize, new_value: u8, old_value: u8);
+        fn exclude_candidate(&mut self, index: usize, value: u8);
+        fn restore_candidates(&mut self, index: usize);
     }
 }
 
 impl<T> Candidates for T where T: CandidateBase {
     fn init_candidates(&mut self) {
-        generate_candidates(self, 0..81);
+        generate_candidates(self, 0..self.layout().cells());
     }
 
     fn update_candidates(&mut self, index: usize, new_value: u8, _old_value: u8) {
         if new_value == 0 {
             // TODO: Make the previous value available and optimize this instead of regenerating from scratch? Might not
             // be worth the effort.
-            generate_candidates(self, indices::rcb_containing(index).iter().copied());
+            let layout = self.layout().clone();
+            generate_candidates(self, layout.peers(index).iter().copied());
         }
         else {
             remove_candidates(self, index, new_value);
         }
     }
+
+    // Removes a single candidate from a cell without placing any values. Unlike the other updates this is never derived
+    // from the puzzle's values, so it will be undone if the cell's candidates are ever regenerated.
+    fn exclude_candidate(&mut self, index: usize, value: u8) {
+        if let Some(remaining) = self.remove_diff(index, CandidateSet::from(value)) {
+            if remaining.is_empty() {
+                self.remove(index);
+            }
+        }
+    }
+
+    // Regenerates a single cell's candidates from the values of its peers, giving back any that were excluded
+    fn restore_candidates(&mut self, index: usize) {
+        generate_candidates(self, std::iter::once(index));
+    }
 }
 
 fn generate_candidates<T: CandidateBase>(context: &mut T, indices: impl IntoIterator<Item=usize>) {
@@ -124,8 +129,8 @@ fn generate_candidates<T: CandidateBase>(context: &mut T, indices: impl IntoIter
 
         // For each cell associated with this one (ie: by being contained within this cell's row, column, or box 
         // group), remove this cell's value from their candidate sets.
-        let mut candidates = CandidateSet::all();
-        for &associated_index in indices::rcb_containing(cell_index) {
+        let mut candidates = context.layout().digits();
+        for &associated_index in context.layout().peers(cell_index) {
             if let Some(value) = SudokuState::get(context, associated_index) {
                 candidates.remove(CandidateSet::from(value))
             };
@@ -138,11 +143,14 @@ fn generate_candidates<T: CandidateBase>(context: &mut T, indices: impl IntoIter
 fn remove_candidates<T: CandidateBase>(context: &mut T, index: usize, value: u8) {
     context.remove(index);
 
-    for cell_index in indices::rcb_containing(index) {
-        match context.remove_diff(*cell_index, CandidateSet::from(value)) {
+    // The layout is shared by every state, so holding on to a copy of it is just a reference count
+    let layout = context.layout().clone();
+
+    for &cell_index in layout.peers(index) {
+        match context.remove_diff(cell_index, CandidateSet::from(value)) {
             Some(remaining) => 
                 if remaining.is_empty() {
-                    context.remove(*cell_index);
+                    context.remove(cell_index);
                 },
             None => continue
         }
@@ -0,0 +1,349 @@
+use std::fmt;
+
+// Version 1 layout, with all values packed most significant bit first:
+//
+//  - 1 byte holding the format version
+//  - 1 byte of flags (bit 0: a solution is included)
+//  - 81 bits marking which cells are filled in
+//  - the digits of the filled in cells, packed three at a time as base 9 numbers into 10 bits (a trailing group of one
+//    or two digits uses 4 or 7 bits respectively)
+//  - when a solution is included, the solution's digits for the remaining empty cells, packed the same way
+//
+// A puzzle with 25 clues takes 24 bytes, or 32 characters once encoded as base64.
+const VERSION: u8 = 1;
+const HAS_SOLUTION: u8 = 0x01;
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct CompactPuzzle {
+    pub puzzle:     [u8; 81],
+    pub solution:   Option<[u8; 81]>,
+}
+
+impl CompactPuzzle {
+    pub fn new(puzzle: [u8; 81], solution: Option<[u8; 81]>) -> Self {
+        Self { puzzle, solution }
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, CodecError> {
+        let mut writer = BitWriter::default();
+        writer.write(u32::from(VERSION), 8);
+        writer.write(if self.solution.is_some() { u32::from(HAS_SOLUTION) } else { 0 }, 8);
+
+        for (index, &value) in self.puzzle.iter().enumerate() {
+            if value > 9 {
+                return Err(CodecError::InvalidValue(index));
+            }
+            writer.write(u32::from(value > 0), 1);
+        }
+
+        writer.write_digits(self.puzzle.iter().copied().filter(|&value| value > 0));
+
+        if let Some(solution) = &self.solution {
+            let mut digits = vec![];
+
+            for (index, (&given, &value)) in self.puzzle.iter().zip(solution.iter()).enumerate() {
+                if value == 0 || value > 9 || (given > 0 && given != value) {
+                    return Err(CodecError::InvalidValue(index));
+                }
+                if given == 0 {
+                    digits.push(value);
+                }
+            }
+
+            writer.write_digits(digits.into_iter());
+        }
+
+        Ok(writer.bytes)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CodecError> {
+        let mut reader = BitReader::new(bytes);
+
+        let version = reader.read(8)? as u8;
+        if version != VERSION {
+            return Err(CodecError::UnsupportedVersion(version));
+        }
+
+        let flags = reader.read(8)? as u8;
+
+        let mut filled = vec![];
+        for index in 0..81 {
+            if reader.read(1)? == 1 {
+                filled.push(index);
+            }
+        }
+
+        let mut puzzle = [0; 81];
+        for (&index, value) in filled.iter().zip(reader.read_digits(filled.len())?) {
+            puzzle[index] = value;
+        }
+
+        let solution = if flags & HAS_SOLUTION > 0 {
+            let empty: Vec<usize> = (0..81).filter(|&index| puzzle[index] == 0).collect();
+            let mut solution = puzzle;
+
+            for (&index, value) in empty.iter().zip(reader.read_digits(empty.len())?) {
+                solution[index] = value;
+            }
+
+            Some(solution)
+        }
+        else {
+            None
+        };
+
+        reader.finish()?;
+
+        Ok(Self { puzzle, solution })
+    }
+
+    // URL safe base64 without padding, suitable for use in share links
+    pub fn to_base64(&self) -> Result<String, CodecError> {
+        self.to_bytes().map(|bytes| base64::encode(&bytes))
+    }
+
+    pub fn from_base64(text: &str) -> Result<Self, CodecError> {
+        Self::from_bytes(&base64::decode(text)?)
+    }
+}
+
+impl From<[u8; 81]> for CompactPuzzle {
+    fn from(puzzle: [u8; 81]) -> Self {
+        Self::new(puzzle, None)
+    }
+}
+
+impl From<CompactPuzzle> for [u8; 81] {
+    fn from(compact: CompactPuzzle) -> [u8; 81] {
+        compact.puzzle
+    }
+}
+
+// Shared with the game's save format
+#[derive(Default)]
+pub(crate) struct BitWriter {
+    pub(crate) bytes:   Vec<u8>,
+    bits:               usize,
+}
+
+impl BitWriter {
+    pub(crate) fn write(&mut self, value: u32, width: usize) {
+        for shift in (0..width).rev() {
+            if self.bits % 8 == 0 {
+                self.bytes.push(0);
+            }
+
+            let bit = ((value >> shift) & 1) as u8;
+            *self.bytes.last_mut().unwrap() |= bit << (7 - self.bits % 8);
+            self.bits += 1;
+        }
+    }
+
+    pub(crate) fn write_digits(&mut self, digits: impl Iterator<Item = u8>) {
+        let digits: Vec<u32> = digits.map(|digit| u32::from(digit) - 1).collect();
+
+        for group in digits.chunks(3) {
+            let packed = group.iter().fold(0, |acc, &digit| acc * 9 + digit);
+            self.write(packed, group_width(group.len()));
+        }
+    }
+}
+
+pub(crate) struct BitReader<'a> {
+    bytes:  &'a [u8],
+    bits:   usize,
+}
+
+impl<'a> BitReader<'a> {
+    pub(crate) fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, bits: 0 }
+    }
+
+    pub(crate) fn read(&mut self, width: usize) -> Result<u32, CodecError> {
+        let mut value = 0;
+
+        for _ in 0..width {
+            let byte = match self.bytes.get(self.bits / 8) {
+                Some(&byte) => byte,
+                None => return Err(CodecError::UnexpectedEnd)
+            };
+
+            value = value << 1 | u32::from((byte >> (7 - self.bits % 8)) & 1);
+            self.bits += 1;
+        }
+
+        Ok(value)
+    }
+
+    // Fails if anything follows the byte holding the last bit read, since writers only ever pad out that one byte
+    pub(crate) fn finish(&self) -> Result<(), CodecError> {
+        match self.bytes.len() > self.bits.div_ceil(8) {
+            true  => Err(CodecError::TrailingBytes),
+            false => Ok(())
+        }
+    }
+
+    pub(crate) fn read_digits(&mut self, count: usize) -> Result<Vec<u8>, CodecError> {
+        let mut digits = Vec::with_capacity(count);
+
+        while digits.len() < count {
+            let size = std::cmp::min(3, count - digits.len());
+            let mut packed = self.read(group_width(size))?;
+
+            if packed >= 9u32.pow(size as u32) {
+                return Err(CodecError::InvalidData);
+            }
+
+            let mut group = vec![];
+            for _ in 0..size {
+                group.push((packed % 9) as u8 + 1);
+                packed /= 9;
+            }
+
+            digits.extend(group.into_iter().rev());
+        }
+
+        Ok(digits)
+    }
+}
+
+// Number of bits needed to store a group of base 9 digits: 9 needs 4 bits, 81 needs 7, and 729 needs 10.
+#[inline]
+fn group_width(size: usize) -> usize {
+    match size {
+        1 => 4,
+        2 => 7,
+        _ => 10
+    }
+}
+
+pub(crate) mod base64 {
+    use super::CodecError;
+
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+    pub fn encode(bytes: &[u8]) -> String {
+        let mut output = String::with_capacity((bytes.len() * 4).div_ceil(3));
+
+        for chunk in bytes.chunks(3) {
+            let block = chunk.iter().enumerate().fold(0u32, |acc, (i, &b)| acc | u32::from(b) << (16 - i * 8));
+
+            for i in 0..=chunk.len() {
+                output.push(ALPHABET[(block >> (18 - i * 6) & 0x3F) as usize] as char);
+            }
+        }
+
+        output
+    }
+
+    // Padding is optional, as is the use of the standard alphabet's + and / characters
+    pub fn decode(text: &str) -> Result<Vec<u8>, CodecError> {
+        let text = text.trim().trim_end_matches('=');
+        let mut values = Vec::with_capacity(text.len());
+
+        for (position, c) in text.chars().enumerate() {
+            let value = match c {
+                'A'..='Z'   => c as u8 - b'A',
+                'a'..='z'   => c as u8 - b'a' + 26,
+                '0'..='9'   => c as u8 - b'0' + 52,
+                '-' | '+'   => 62,
+                '_' | '/'   => 63,
+                _ => return Err(CodecError::InvalidCharacter(c, position))
+            };
+            values.push(value);
+        }
+
+        if values.len() % 4 == 1 {
+            return Err(CodecError::UnexpectedEnd);
+        }
+
+        let mut bytes = Vec::with_capacity(values.len() * 3 / 4);
+        for chunk in values.chunks(4) {
+            let block = chunk.iter().enumerate().fold(0u32, |acc, (i, &v)| acc | u32::from(v) << (18 - i * 6));
+
+            for i in 0..chunk.len() - 1 {
+                bytes.push((block >> (16 - i * 8)) as u8);
+            }
+        }
+
+        Ok(bytes)
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CodecError {
+    UnsupportedVersion(u8),
+    UnexpectedEnd,
+    InvalidValue(usize),
+    InvalidData,
+    InvalidCharacter(char, usize),
+    TrailingBytes,
+}
+
+impl fmt::Display for CodecError {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CodecError::UnsupportedVersion(version) =>
+                write!(formatter, "unsupported format version {}", version),
+            CodecError::UnexpectedEnd =>
+                write!(formatter, "unexpected end of input"),
+            CodecError::InvalidValue(index) =>
+                write!(formatter, "invalid value at cell {}", index),
+            CodecError::InvalidData =>
+                write!(formatter, "invalid packed digits"),
+            CodecError::InvalidCharacter(c, position) =>
+                write!(formatter, "unexpected character {:?} at position {}", c, position + 1),
+            CodecError::TrailingBytes =>
+                write!(formatter, "unexpected data after the end of input"),
+        }
+    }
+}
+
+impl std::error::Error for CodecError { }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ fixtures, Solver };
+
+    fn puzzle() -> CompactPuzzle {
+        let puzzle = fixtures::puzzle();
+        let solution = Solver::new().solve(&puzzle).result[0].solution;
+
+        CompactPuzzle::new(puzzle, Some(solution))
+    }
+
+    #[test]
+    fn puzzles_round_trip() {
+        let compact = puzzle();
+        let bare = CompactPuzzle::from(compact.puzzle);
+
+        assert_eq!(CompactPuzzle::from_bytes(&compact.to_bytes().unwrap()), Ok(compact.clone()));
+        assert_eq!(CompactPuzzle::from_base64(&compact.to_base64().unwrap()), Ok(compact));
+        assert_eq!(CompactPuzzle::from_base64(&bare.to_base64().unwrap()), Ok(bare));
+    }
+
+    #[test]
+    fn malformed_input_is_rejected() {
+        let mut bytes = puzzle().to_bytes().unwrap();
+
+        assert_eq!(CompactPuzzle::from_bytes(&bytes[.. bytes.len() - 1]), Err(CodecError::UnexpectedEnd));
+
+        bytes.push(0);
+        assert_eq!(CompactPuzzle::from_bytes(&bytes), Err(CodecError::TrailingBytes));
+
+        bytes[0] = 2;
+        assert_eq!(CompactPuzzle::from_bytes(&bytes), Err(CodecError::UnsupportedVersion(2)));
+
+        assert_eq!(CompactPuzzle::from_base64("AQ!"), Err(CodecError::InvalidCharacter('!', 2)));
+    }
+
+    #[test]
+    fn invalid_values_are_not_written() {
+        let mut compact = puzzle();
+        compact.puzzle[4] = 10;
+
+        assert_eq!(compact.to_bytes(), Err(CodecError::InvalidValue(4)));
+    }
+}
@@ -0,0 +1,571 @@
+use crate :: {
+    sudoku  :: { Sudoku, traits :: SudokuState },
+    layout  :: { Layout, LayoutError },
+    values  :: value_key :: SetType,
+};
+
+use std::fmt;
+
+// A rule on top of the houses of a layout, ex: no two cells a knight's move apart holding the same digit. Layouts are
+// given constraints with Layout::with_constraint, and from then on the solver keeps every cell's candidates in line
+// with them and the generator only produces puzzles that follow them. Constraints are compared by their Debug output,
+// so two constraints that print the same are expected to behave the same.
+pub trait Constraint: fmt::Debug + Send + Sync {
+    // Cells whose candidates can change when a value is placed at the given cell. Constraints that only ever rule out
+    // candidates from the start (ex: even cells) don't need any.
+    fn related(&self, layout: &Layout, index: usize) -> Vec<usize>;
+
+    // Whether the value can be placed at the cell, given the values already placed elsewhere
+    fn allows(&self, state: &Sudoku, index: usize, value: u8) -> bool;
+
+    // Candidates of an empty cell that can't be placed there. By default, those allows rejects.
+    fn prune(&self, state: &Sudoku, index: usize) -> Vec<u8> {
+        state.candidates(index).into_iter().filter(|&value| !self.allows(state, index, value)).collect()
+    }
+
+    // Candidates that can be ruled out by looking at the whole grid rather than one cell at a time, as (cell, value)
+    // pairs. Checked after every placement, so the default is to offer none.
+    fn infer(&self, _state: &Sudoku) -> Vec<(usize, u8)> {
+        Vec::new()
+    }
+
+    // Whether prune or infer look at the candidates of other cells, rather than only at the values placed. Candidates
+    // given back to a cell by erasing a value can then give more back to its related cells, and so on, which erasing
+    // has to follow through. Assumed unless a constraint says otherwise.
+    fn reads_candidates(&self) -> bool {
+        true
+    }
+}
+
+// No digit can repeat a knight's move away (two cells in one direction and one in the other)
+#[derive(Copy, Clone, Debug, Default)]
+pub struct AntiKnight;
+
+// No digit can repeat in the eight cells surrounding it, including diagonally
+#[derive(Copy, Clone, Debug, Default)]
+pub struct AntiKing;
+
+// Cells sharing a side can't hold consecutive digits
+#[derive(Copy, Clone, Debug, Default)]
+pub struct NonConsecutive;
+
+// Cells that can only hold even, or only odd, digits
+#[derive(Clone, Debug, Default)]
+pub struct EvenOdd {
+    even:   Vec<usize>,
+    odd:    Vec<usize>,
+}
+
+// Cells at the same position within their box (ex: the centre of each box) can't repeat a digit. Each grid of an
+// overlapping layout has its own groups. Jigsaw layouts have no such positions, so this doesn't constrain them.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct DisjointGroups;
+
+// Digits strictly increase along the cells of a thermometer, starting from its bulb
+#[derive(Clone, Debug, Default)]
+pub struct Thermometer {
+    cells:  Vec<usize>,
+}
+
+// The digits along an arrow add up to the digit in its circle. Unlike in a cage, digits can repeat along an arrow as
+// long as they don't share a house.
+#[derive(Clone, Debug, Default)]
+pub struct Arrow {
+    circle: usize,
+    cells:  Vec<usize>,
+}
+
+// The digits between the 1 and the largest digit of a row or column add up to the clue's sum. Lines are numbered the
+// same way as the layout's rows and columns, so the rows of an overlapping layout run through each grid in turn.
+#[derive(Copy, Clone, Debug)]
+pub struct Sandwich {
+    line:   Line,
+    sum:    usize,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Line {
+    Row(usize),
+    Column(usize),
+}
+
+impl Constraint for AntiKnight {
+    fn related(&self, layout: &Layout, index: usize) -> Vec<usize> {
+        offsets(layout, index, &[(-2, -1), (-2, 1), (-1, -2), (-1, 2), (1, -2), (1, 2), (2, -1), (2, 1)])
+    }
+
+    fn allows(&self, state: &Sudoku, index: usize, value: u8) -> bool {
+        holds_none(state, &self.related(state.layout(), index), &[value])
+    }
+
+    fn reads_candidates(&self) -> bool {
+        false
+    }
+}
+
+impl Constraint for AntiKing {
+    fn related(&self, layout: &Layout, index: usize) -> Vec<usize> {
+        offsets(layout, index, &[(-1, -1), (-1, 0), (-1, 1), (0, -1), (0, 1), (1, -1), (1, 0), (1, 1)])
+    }
+
+    fn allows(&self, state: &Sudoku, index: usize, value: u8) -> bool {
+        holds_none(state, &self.related(state.layout(), index), &[value])
+    }
+
+    fn reads_candidates(&self) -> bool {
+        false
+    }
+}
+
+impl Constraint for NonConsecutive {
+    fn related(&self, layout: &Layout, index: usize) -> Vec<usize> {
+        layout.neighbours(index)
+    }
+
+    fn allows(&self, state: &Sudoku, index: usize, value: u8) -> bool {
+        // Placing 0 empties the cell, which can't break the rule
+        match value.checked_sub(1) {
+            Some(below) => holds_none(state, &self.related(state.layout(), index), &[below, value.saturating_add(1)]),
+            None => true,
+        }
+    }
+
+    fn reads_candidates(&self) -> bool {
+        false
+    }
+}
+
+impl EvenOdd {
+    pub fn new(even: &[usize], odd: &[usize]) -> EvenOdd {
+        EvenOdd { even: even.to_vec(), odd: odd.to_vec() }
+    }
+}
+
+impl Constraint for EvenOdd {
+    fn related(&self, _layout: &Layout, _index: usize) -> Vec<usize> {
+        Vec::new()
+    }
+
+    fn allows(&self, _state: &Sudoku, index: usize, value: u8) -> bool {
+        match value % 2 {
+            0 => !self.odd.contains(&index),
+            _ => !self.even.contains(&index),
+        }
+    }
+
+    fn reads_candidates(&self) -> bool {
+        false
+    }
+}
+
+impl Constraint for DisjointGroups {
+    fn related(&self, layout: &Layout, index: usize) -> Vec<usize> {
+        layout.disjoint_peers(index).to_vec()
+    }
+
+    fn allows(&self, state: &Sudoku, index: usize, value: u8) -> bool {
+        holds_none(state, state.layout().disjoint_peers(index), &[value])
+    }
+
+    // Groups hold every digit once, like houses, so a digit that only fits in one cell of a group must go there
+    fn infer(&self, state: &Sudoku) -> Vec<(usize, u8)> {
+        let layout = state.layout();
+        let mut inferred = vec![];
+
+        for group in layout.disjoint_groups() {
+            let candidates: Vec<(usize, Vec<u8>)> = group.iter()
+                .map(|&cell| (cell, state.candidates(cell)))
+                .collect();
+
+            for value in 1 ..= layout.size() as u8 {
+                let mut places = candidates.iter().filter(|(_, values)| values.contains(&value));
+
+                if let (Some((cell, values)), None) = (places.next(), places.next()) {
+                    inferred.extend(values.iter().filter(|&&other| other != value).map(|&other| (*cell, other)));
+                }
+            }
+        }
+
+        inferred
+    }
+}
+
+impl Thermometer {
+    // Cells from the bulb to the tip, ex: Thermometer::new(&Layout::standard(), &[0, 1, 2]) for a thermometer along
+    // the top left of a grid. Cells outside of the layout or on the thermometer twice are rejected, and so are cells
+    // that aren't next to the one before them, diagonally or not.
+    pub fn new(layout: &Layout, cells: &[usize]) -> Result<Thermometer, LayoutError> {
+        check_path(layout, cells)?;
+
+        Ok(Thermometer { cells: cells.to_vec() })
+    }
+
+    pub fn cells(&self) -> &[usize] {
+        &self.cells
+    }
+
+    // Each cell comes after the previous ones on the thermometer, so it has to hold at least one more than the
+    // smallest digit they can hold for every step between them. Likewise for the cells after it.
+    fn fits(&self, state: &Sudoku, index: usize, value: u8, candidates: bool) -> bool {
+        let position = match self.cells.iter().position(|&cell| cell == index) {
+            Some(position) => position,
+            None => return true,
+        };
+
+        self.cells.iter().enumerate().filter(|&(other, _)| other != position).all(|(other, &cell)| {
+            let (low, high) = bounds(state, cell, candidates);
+            let steps = position.abs_diff(other);
+
+            match other < position {
+                true  => usize::from(value) >= usize::from(low) + steps,
+                false => usize::from(value) + steps <= usize::from(high),
+            }
+        })
+    }
+}
+
+impl Constraint for Thermometer {
+    fn related(&self, _layout: &Layout, index: usize) -> Vec<usize> {
+        others(&self.cells, index)
+    }
+
+    fn allows(&self, state: &Sudoku, index: usize, value: u8) -> bool {
+        self.fits(state, index, value, false)
+    }
+
+    fn prune(&self, state: &Sudoku, index: usize) -> Vec<u8> {
+        state.candidates(index).into_iter().filter(|&value| !self.fits(state, index, value, true)).collect()
+    }
+}
+
+impl Arrow {
+    // The cell holding the circle, then the cells of the arrow coming out of it. Checked the same way as thermometers,
+    // starting from the circle, so the circle can't be on the arrow either.
+    pub fn new(layout: &Layout, circle: usize, cells: &[usize]) -> Result<Arrow, LayoutError> {
+        let path: Vec<usize> = std::iter::once(circle).chain(cells.iter().copied()).collect();
+        check_path(layout, &path)?;
+
+        Ok(Arrow { circle, cells: cells.to_vec() })
+    }
+
+    pub fn circle(&self) -> usize {
+        self.circle
+    }
+
+    pub fn cells(&self) -> &[usize] {
+        &self.cells
+    }
+
+    // Whether the smallest and largest totals the arrow can still reach overlap with the digits the circle can hold
+    fn fits(&self, state: &Sudoku, index: usize, value: u8, candidates: bool) -> bool {
+        if index != self.circle && !self.cells.contains(&index) {
+            return true;
+        }
+
+        let bounds = |cell: usize| match cell == index {
+            true  => (value, value),
+            false => bounds(state, cell, candidates),
+        };
+
+        let (low, high) = self.cells.iter()
+            .map(|&cell| bounds(cell))
+            .fold((0, 0), |(low, high), (l, h)| (low + usize::from(l), high + usize::from(h)));
+        let (circle_low, circle_high) = bounds(self.circle);
+
+        low <= usize::from(circle_high) && high >= usize::from(circle_low)
+    }
+}
+
+impl Constraint for Arrow {
+    fn related(&self, _layout: &Layout, index: usize) -> Vec<usize> {
+        let mut cells = self.cells.clone();
+        cells.push(self.circle);
+
+        others(&cells, index)
+    }
+
+    fn allows(&self, state: &Sudoku, index: usize, value: u8) -> bool {
+        self.fits(state, index, value, false)
+    }
+
+    fn prune(&self, state: &Sudoku, index: usize) -> Vec<u8> {
+        state.candidates(index).into_iter().filter(|&value| !self.fits(state, index, value, true)).collect()
+    }
+}
+
+impl Sandwich {
+    // Ex: Sandwich::row(&Layout::standard(), 0, 10) for a top row where the digits between the 1 and the 9 add up to
+    // 10. Rows past the last one of the layout are rejected, and so are sums larger than all the digits between the
+    // 1 and the largest one add up to (35 on a 9x9 grid).
+    pub fn row(layout: &Layout, row: usize, sum: usize) -> Result<Sandwich, LayoutError> {
+        Sandwich::new(layout, Line::Row(row), sum)
+    }
+
+    pub fn column(layout: &Layout, col: usize, sum: usize) -> Result<Sandwich, LayoutError> {
+        Sandwich::new(layout, Line::Column(col), sum)
+    }
+
+    fn new(layout: &Layout, line: Line, sum: usize) -> Result<Sandwich, LayoutError> {
+        let sandwich = Sandwich { line, sum };
+        let (Line::Row(index) | Line::Column(index)) = line;
+        if sandwich.cells(layout).is_empty() {
+            return Err(LayoutError::SandwichLine(index));
+        }
+
+        if sum > (2 .. layout.size()).sum() {
+            return Err(LayoutError::SandwichSum(sum));
+        }
+
+        Ok(sandwich)
+    }
+
+    // Index of the row the clue is for, if it's for a row
+    pub fn row_index(&self) -> Option<usize> {
+        match self.line {
+            Line::Row(row) => Some(row),
+            Line::Column(_) => None,
+        }
+    }
+
+    // Index of the column the clue is for, if it's for a column
+    pub fn column_index(&self) -> Option<usize> {
+        match self.line {
+            Line::Column(col) => Some(col),
+            Line::Row(_) => None,
+        }
+    }
+
+    pub fn sum(&self) -> usize {
+        self.sum
+    }
+
+    // Lines past the last row or column of the layout have no cells, and don't constrain anything
+    pub(crate) fn cells<'a>(&self, layout: &'a Layout) -> &'a [usize] {
+        let (set_type, index) = match self.line {
+            Line::Row(row) => (SetType::Row, row),
+            Line::Column(col) => (SetType::Col, col),
+        };
+
+        layout.find_house(set_type, index).unwrap_or(&[])
+    }
+
+    // Tries every pair of cells the 1 and the largest digit could go in. Digits can't repeat within the line, so the
+    // empty cells between them add at least the smallest of the digits that haven't been placed, and at most the
+    // largest of them.
+    fn fits(&self, state: &Sudoku, index: usize, value: u8, candidates: bool) -> bool {
+        let cells = self.cells(state.layout());
+        if !cells.contains(&index) {
+            return true;
+        }
+
+        let largest = state.layout().size() as u8;
+        let values: Vec<u8> = cells.iter()
+            .map(|&cell| if cell == index { value } else { state.get(cell).unwrap_or(0) })
+            .collect();
+
+        let can_hold = |position: usize, digit: u8| match values[position] {
+            0 => !values.contains(&digit) && (!candidates || state.candidates(cells[position]).contains(&digit)),
+            placed => placed == digit,
+        };
+
+        let unused: Vec<usize> = (2 .. largest).filter(|digit| !values.contains(digit)).map(usize::from).collect();
+
+        for one in (0 .. cells.len()).filter(|&position| can_hold(position, 1)) {
+            for last in (0 .. cells.len()).filter(|&position| position != one && can_hold(position, largest)) {
+                let between = &values[one.min(last) + 1 .. one.max(last)];
+                let empty = between.iter().filter(|&&value| value == 0).count();
+                let placed: usize = between.iter().map(|&value| usize::from(value)).sum();
+
+                if empty > unused.len() {
+                    continue;
+                }
+
+                let low = placed + unused[.. empty].iter().sum::<usize>();
+                let high = placed + unused[unused.len() - empty ..].iter().sum::<usize>();
+
+                if (low ..= high).contains(&self.sum) {
+                    return true;
+                }
+            }
+        }
+
+        false
+    }
+}
+
+impl Constraint for Sandwich {
+    fn related(&self, layout: &Layout, index: usize) -> Vec<usize> {
+        others(self.cells(layout), index)
+    }
+
+    fn allows(&self, state: &Sudoku, index: usize, value: u8) -> bool {
+        self.fits(state, index, value, false)
+    }
+
+    fn prune(&self, state: &Sudoku, index: usize) -> Vec<u8> {
+        state.candidates(index).into_iter().filter(|&value| !self.fits(state, index, value, true)).collect()
+    }
+}
+
+// Cells at the given (row, column) offsets from a cell, leaving out those that fall outside of the grid
+fn offsets(layout: &Layout, index: usize, offsets: &[(isize, isize)]) -> Vec<usize> {
+    offsets.iter().filter_map(|&(rows, cols)| layout.offset(index, rows, cols)).collect()
+}
+
+// Cells of a thermometer or arrow have to be in the layout, on it once, and next to the one before them
+fn check_path(layout: &Layout, cells: &[usize]) -> Result<(), LayoutError> {
+    for (i, &cell) in cells.iter().enumerate() {
+        if cell >= layout.cells() || cells[.. i].contains(&cell) {
+            return Err(LayoutError::ClueCell(cell));
+        }
+    }
+
+    let adjacent = |a: usize, b: usize| {
+        let ((a_row, a_col), (b_row, b_col)) = (layout.position(a), layout.position(b));
+        a_row.abs_diff(b_row) <= 1 && a_col.abs_diff(b_col) <= 1
+    };
+
+    match cells.windows(2).find(|pair| !adjacent(pair[0], pair[1])) {
+        Some(pair) => Err(LayoutError::DisconnectedClue(pair[1])),
+        None => Ok(()),
+    }
+}
+
+// The other cells of a clue, if the given cell is one of them
+fn others(cells: &[usize], index: usize) -> Vec<usize> {
+    match cells.contains(&index) {
+        true  => cells.iter().copied().filter(|&cell| cell != index).collect(),
+        false => Vec::new(),
+    }
+}
+
+// Smallest and largest digits a cell can hold: its value once placed, otherwise any digit, or only its candidates when
+// those are taken into account
+fn bounds(state: &Sudoku, cell: usize, candidates: bool) -> (u8, u8) {
+    match state.get(cell).unwrap_or(0) {
+        0 => {
+            let values = if candidates { state.candidates(cell) } else { Vec::new() };
+            let size = state.layout().size() as u8;
+
+            (values.iter().copied().min().unwrap_or(1), values.iter().copied().max().unwrap_or(size))
+        },
+        value => (value, value),
+    }
+}
+
+// Whether none of the cells hold any of the values. Empty cells never count, even when 0 is one of the values.
+fn holds_none(state: &Sudoku, cells: &[usize], values: &[u8]) -> bool {
+    cells.iter().filter_map(|&cell| state.get(cell)).all(|value| value == 0 || !values.contains(&value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate :: {
+        cages   :: Cage,
+        puzzle  :: Puzzle,
+        solver  :: Solver,
+        grader  :: { validate_puzzle, ValidationError },
+    };
+
+    #[test]
+    fn solutions_follow_the_constraints() {
+        let layout = Layout::new(3, 2).unwrap().with_constraint(AntiKing).with_constraint(EvenOdd::new(&[0], &[1]));
+        let solved = Solver::new().solve_puzzle(&Puzzle::empty(&layout));
+        let cells = solved.result[0].solution.cells();
+
+        assert_eq!(cells[0] % 2, 0);
+        assert_eq!(cells[1] % 2, 1);
+        for cell in 0 .. cells.len() {
+            assert!(AntiKing.related(&layout, cell).iter().all(|&other| cells[other] != cells[cell]));
+        }
+    }
+
+    #[test]
+    fn givens_breaking_a_constraint_are_rejected() {
+        let layout = Layout::standard().with_constraint(AntiKnight);
+        let mut cells = vec![0; 81];
+        cells[2] = 4;
+        cells[13] = 4;
+
+        let error = validate_puzzle(&Puzzle::new(&layout, &cells).unwrap()).unwrap_err();
+        assert_eq!(error, ValidationError::Constraint(2));
+    }
+
+    #[test]
+    fn non_consecutive_allows_any_byte() {
+        let state = Sudoku::from(&Puzzle::empty(&Layout::standard().with_constraint(NonConsecutive)));
+
+        assert!(NonConsecutive.allows(&state, 0, 0));
+        assert!(NonConsecutive.allows(&state, 0, u8::MAX));
+    }
+
+    #[test]
+    fn thermometers_and_arrows_are_solved() {
+        let plain = Layout::new(2, 2).unwrap();
+        let thermometer = Thermometer::new(&plain, &[4, 5, 6, 7]).unwrap();
+        let arrow = Arrow::new(&plain, 0, &[1, 2]).unwrap();
+        let layout = plain.with_constraint(thermometer.clone()).with_constraint(arrow.clone());
+
+        let state = Sudoku::from(&Puzzle::empty(&layout));
+        assert!(!thermometer.allows(&state, 4, 2));
+        assert!(!arrow.allows(&state, 0, 1));
+
+        let solved = Solver::with_limit(2).solve_puzzle(&Puzzle::empty(&layout));
+        let cells = solved.result[0].solution.cells();
+
+        assert_eq!(&cells[4 .. 8], &[1, 2, 3, 4]);
+        assert_eq!(cells[0], cells[1] + cells[2]);
+    }
+
+    #[test]
+    fn erasing_a_value_gives_back_the_candidates_it_ruled_out() {
+        let cages = [Cage::new(7, &[0, 1]), Cage::new(11, &[20, 26])];
+        let caged = Layout::new(3, 2).unwrap().with_cages(&cages).unwrap();
+        let layout = caged
+            .with_constraint(Thermometer::new(&caged, &[8, 9, 10, 16]).unwrap())
+            .with_constraint(Arrow::new(&caged, 33, &[34, 35]).unwrap());
+
+        // Every other cell of a solution given, so that the cages and constraints rule out more than the houses do
+        let solved = Solver::new().solve_puzzle(&Puzzle::empty(&layout));
+        let cells: Vec<u8> = solved.result[0].solution.cells().iter().enumerate()
+            .map(|(cell, &value)| if cell % 2 == 0 { value } else { 0 })
+            .collect();
+
+        let state = Sudoku::from(&Puzzle::new(&layout, &cells).unwrap());
+        for cell in 0 .. layout.cells() {
+            for value in state.candidates(cell) {
+                let erased = state.set(cell, value).set(cell, 0);
+                assert!((0 .. layout.cells()).all(|other| erased.candidates(other) == state.candidates(other)));
+            }
+        }
+    }
+
+    #[test]
+    fn thermometers_and_arrows_are_checked_against_the_layout() {
+        let layout = Layout::new(2, 2).unwrap();
+
+        assert!(Thermometer::new(&layout, &[0, 5, 10, 14]).is_ok());
+        assert_eq!(Thermometer::new(&layout, &[14, 15, 16]).err(), Some(LayoutError::ClueCell(16)));
+        assert_eq!(Thermometer::new(&layout, &[0, 1, 5, 1]).err(), Some(LayoutError::ClueCell(1)));
+        assert_eq!(Thermometer::new(&layout, &[0, 1, 3]).err(), Some(LayoutError::DisconnectedClue(3)));
+        assert_eq!(Thermometer::new(&layout, &[3, 4]).err(), Some(LayoutError::DisconnectedClue(4)));
+
+        assert!(Arrow::new(&layout, 0, &[5, 6]).is_ok());
+        assert_eq!(Arrow::new(&layout, 16, &[12, 13]).err(), Some(LayoutError::ClueCell(16)));
+        assert_eq!(Arrow::new(&layout, 0, &[1, 0]).err(), Some(LayoutError::ClueCell(0)));
+        assert_eq!(Arrow::new(&layout, 0, &[2, 3]).err(), Some(LayoutError::DisconnectedClue(2)));
+    }
+
+    #[test]
+    fn sandwiches_are_checked_against_the_layout() {
+        let layout = Layout::standard();
+
+        assert!(Sandwich::row(&layout, 0, 0).is_ok());
+        assert!(Sandwich::column(&layout, 8, 35).is_ok());
+        assert_eq!(Sandwich::row(&layout, 0, 36).err(), Some(LayoutError::SandwichSum(36)));
+        assert_eq!(Sandwich::row(&layout, 0, 1000).err(), Some(LayoutError::SandwichSum(1000)));
+        assert_eq!(Sandwich::column(&layout, 9, 10).err(), Some(LayoutError::SandwichLine(9)));
+        assert_eq!(Sandwich::row(&Layout::new(2, 2).unwrap(), 0, 6).err(), Some(LayoutError::SandwichSum(6)));
+    }
+}
@@ -0,0 +1,8 @@
+// Puzzles shared by the tests of several modules
+
+// A 9x9 puzzle with a single solution, in the 81 digit notation
+pub(crate) const PUZZLE: &str = "000230070050000000400000850230900500060004093008000000040007030800000000020009061";
+
+pub(crate) fn puzzle() -> [u8; 81] {
+    crate::parse_puzzle(PUZZLE).unwrap()
+}
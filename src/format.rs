@@ -1,14 +1,12 @@
+use crate::{ layout::Layout, cages::label };
+
 #[allow(dead_code)]
+#[derive(Default, Copy, Clone)]
 pub enum Alignment {
+    #[default]
     Left, Right, Center
 }
 
-impl Default for Alignment {
-    fn default() -> Alignment {
-        Alignment::Left
-    }
-}
-
 macro_rules! box_format {
     ($name:ident, $left:expr, $right:expr, $center:expr) => {
         pub fn $name(&mut self, text: &str, align: Option<Alignment>) -> Result<&mut Self, std::fmt::Error> {
@@ -47,9 +45,355 @@ impl<'a, 'b: 'a> BoxFormat<'a, 'b> {
         self.content("", None)
     }
 
+    // Writes multi-line text (ex: a grid) one content line at a time
+    pub fn block(&mut self, text: &str, align: Option<Alignment>) -> Result<&mut Self, std::fmt::Error> {
+        for line in text.lines() {
+            self.content(line, align)?;
+        }
+
+        Ok(self)
+    }
+
     #[inline]
     pub fn line_break(&mut self) -> Result<&mut Self, std::fmt::Error> {
         self.section("", None)
     }
 }
 
+
+// Characters used to draw the lines of a grid: [left, horizontal, junction, right] for each type of line.
+pub struct Borders {
+    pub top:        [char; 4],
+    pub separator:  [char; 4],
+    pub bottom:     [char; 4],
+    pub vertical:   char,
+}
+
+// Matches the style of the boxes drawn by BoxFormat
+pub const ROUNDED: Borders = Borders {
+    top:        ['╭', '─', '┬', '╮'],
+    separator:  ['├', '┄', '┼', '┤'],
+    bottom:     ['╰', '─', '┴', '╯'],
+    vertical:   '│',
+};
+
+pub const ASCII: Borders = Borders {
+    top:        ['+', '-', '+', '+'],
+    separator:  ['+', '-', '+', '+'],
+    bottom:     ['+', '-', '+', '+'],
+    vertical:   '|',
+};
+
+// Draws the cells of a layout as a grid with lines around each box. Every column is padded to the width of its widest
+// cell.
+pub fn boxed_grid<W>(writer: &mut W, cells: &[String], layout: &Layout, borders: &Borders) -> std::fmt::Result
+where W: std::fmt::Write {
+    if layout.is_overlapping() {
+        return board(writer, cells, layout);
+    }
+
+    if layout.is_jigsaw() {
+        return jigsaw(writer, cells, layout, borders);
+    }
+
+    let (size, box_width, box_height) = (layout.size(), layout.box_width(), layout.box_height());
+
+    let mut widths = vec![1; size];
+    for (i, cell) in cells.iter().enumerate() {
+        widths[i % size] = std::cmp::max(widths[i % size], cell.chars().count());
+    }
+
+    let line = |writer: &mut W, [left, horizontal, junction, right]: [char; 4]| -> std::fmt::Result {
+        writer.write_char(left)?;
+        for (i, stack) in widths.chunks(box_width).enumerate() {
+            if i > 0 { writer.write_char(junction)?; }
+
+            let length = stack.iter().map(|w| w + 1).sum::<usize>() + 1;
+            (0..length).try_for_each(|_| writer.write_char(horizontal))?;
+        }
+        writer.write_char(right)?;
+        writeln!(writer)
+    };
+
+    line(writer, borders.top)?;
+
+    for (y, row) in cells.chunks(size).enumerate() {
+        if y > 0 && y % box_height == 0 {
+            line(writer, borders.separator)?;
+        }
+
+        for (x, cell) in row.iter().enumerate() {
+            if x % box_width == 0 { writer.write_char(borders.vertical)?; }
+            write!(writer, " {:<width$}", cell, width = widths[x])?;
+            if x % box_width == box_width - 1 { writer.write_char(' ')?; }
+        }
+
+        writer.write_char(borders.vertical)?;
+        writeln!(writer)?;
+    }
+
+    line(writer, borders.bottom)
+}
+
+// Jigsaw regions can't be drawn with straight lines, so every cell gets room for a line on each of its sides, drawn
+// wherever the cell on the other side is in another region (or outside of the grid). Junctions join the lines that
+// meet at them, using the corners and crossings of the borders.
+fn jigsaw<W>(writer: &mut W, cells: &[String], layout: &Layout, borders: &Borders) -> std::fmt::Result
+where W: std::fmt::Write {
+    let size = layout.size();
+    let regions = layout.regions();
+    let region = |row: usize, col: usize| match row < size && col < size {
+        true  => Some(regions[row * size + col]),
+        false => None
+    };
+
+    // Rows and columns of -1 (wrapped around to usize::MAX) fall outside of the grid, like those past the last one
+    let apart = |(row, col): (usize, usize), (other_row, other_col): (usize, usize)| {
+        region(row, col) != region(other_row, other_col)
+    };
+
+    let mut widths = vec![1; size];
+    for (i, cell) in cells.iter().enumerate() {
+        widths[i % size] = std::cmp::max(widths[i % size], cell.chars().count());
+    }
+
+    for row in 0 ..= size {
+        let [_, horizontal, _, _] = match row {
+            0                   => borders.top,
+            _ if row == size    => borders.bottom,
+            _                   => borders.separator,
+        };
+
+        // The line above the row
+        let above = row.wrapping_sub(1);
+        for (col, &width) in widths.iter().enumerate().chain(std::iter::once((size, &0))) {
+            let before = col.wrapping_sub(1);
+            let arms = (
+                apart((above, before), (above, col)),
+                apart((row, before), (row, col)),
+                apart((above, before), (row, before)),
+                apart((above, col), (row, col)),
+            );
+
+            writer.write_char(match arms {
+                (false, false, false, false)    => ' ',
+                (true, true, false, false)      => borders.vertical,
+                (false, false, true, true)      => horizontal,
+                (false, true, false, true)      => borders.top[0],
+                (false, true, true, false)      => borders.top[3],
+                (true, false, false, true)      => borders.bottom[0],
+                (true, false, true, false)      => borders.bottom[3],
+                (false, true, true, true)       => borders.top[2],
+                (true, false, true, true)       => borders.bottom[2],
+                (true, true, false, true)       => borders.separator[0],
+                (true, true, true, false)       => borders.separator[3],
+                _                               => borders.separator[2],
+            })?;
+
+            if col < size {
+                let line = if apart((above, col), (row, col)) { horizontal } else { ' ' };
+                (0 .. width + 2).try_for_each(|_| writer.write_char(line))?;
+            }
+        }
+
+        writeln!(writer)?;
+
+        if row == size {
+            break;
+        }
+
+        for (col, &width) in widths.iter().enumerate() {
+            let line = if apart((row, col.wrapping_sub(1)), (row, col)) { borders.vertical } else { ' ' };
+            write!(writer, "{} {:<width$} ", line, cells[row * size + col], width = width)?;
+        }
+
+        writer.write_char(borders.vertical)?;
+        writeln!(writer)?;
+    }
+
+    Ok(())
+}
+
+// Overlapping grids can't all be framed, so their board is drawn without lines instead: boxes are set apart by an extra
+// space, and bands by an empty line. Positions no grid covers are left blank. Every line is padded to the same width.
+fn board<W: std::fmt::Write>(writer: &mut W, cells: &[String], layout: &Layout) -> std::fmt::Result {
+    let (rows, cols) = layout.board_size();
+    let (box_width, box_height) = (layout.box_width(), layout.box_height());
+
+    let mut widths = vec![1; cols];
+    for (cell, text) in cells.iter().enumerate() {
+        let (_, col) = layout.position(cell);
+        widths[col] = std::cmp::max(widths[col], text.chars().count());
+    }
+
+    let width = widths.iter().map(|w| w + 1).sum::<usize>() + (cols - 1) / box_width;
+
+    for row in 0..rows {
+        if row > 0 && row % box_height == 0 {
+            writeln!(writer, "{:width$}", "", width = width)?;
+        }
+
+        for (col, &width) in widths.iter().enumerate() {
+            if col > 0 && col % box_width == 0 { writer.write_char(' ')?; }
+
+            let text = layout.cell_at(row, col).map_or("", |cell| cells[cell].as_str());
+            write!(writer, " {:<width$}", text, width = width)?;
+        }
+
+        writeln!(writer)?;
+    }
+
+    Ok(())
+}
+
+// Digits past 9 are written as letters: A for 10, B for 11 and so on
+#[inline]
+pub(crate) fn symbol(value: u8) -> char {
+    match value {
+        0..=9   => (b'0' + value) as char,
+        _       => (b'A' + value - 10) as char
+    }
+}
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GridStyle {
+    #[default]
+    Unicode,
+    Ascii,
+}
+
+// Displays a puzzle as a grid, 9x9 unless another layout is given, or as the board of an overlapping layout. When a
+// solution is included it is drawn to the right of the puzzle. Killer Sudoku cages are drawn below, as a grid of cage
+// labels followed by the sum of each cage.
+pub struct Grid<'a> {
+    layout:     Layout,
+    puzzle:     &'a [u8],
+    solution:   Option<&'a [u8]>,
+    style:      GridStyle,
+    blank:      char,
+}
+
+impl<'a> Grid<'a> {
+    pub fn new(puzzle: &'a [u8; 81]) -> Self {
+        Self::with_layout(&Layout::standard(), puzzle)
+    }
+
+    // The puzzle must have as many cells as the layout
+    pub fn with_layout(layout: &Layout, puzzle: &'a [u8]) -> Self {
+        Self { layout: layout.clone(), puzzle, solution: None, style: GridStyle::default(), blank: '.' }
+    }
+
+    pub fn solution(&mut self, solution: &'a [u8]) -> &mut Self {
+        self.solution = Some(solution);
+
+        self
+    }
+
+    pub fn style(&mut self, style: GridStyle) -> &mut Self {
+        self.style = style;
+
+        self
+    }
+
+    // Character used for empty cells
+    pub fn blank(&mut self, blank: char) -> &mut Self {
+        self.blank = blank;
+
+        self
+    }
+
+    fn borders(&self) -> &'static Borders {
+        match self.style {
+            GridStyle::Unicode  => &ROUNDED,
+            GridStyle::Ascii    => &ASCII,
+        }
+    }
+
+    fn lines(&self, values: &[u8]) -> Result<Vec<String>, std::fmt::Error> {
+        let cells: Vec<String> = values.iter().map(|&value| match value {
+            0 => self.blank.to_string(),
+            _ => symbol(value).to_string()
+        }).collect();
+
+        let mut output = String::new();
+        boxed_grid(&mut output, &cells, &self.layout, self.borders())?;
+
+        Ok(output.lines().map(String::from).collect())
+    }
+
+    // Ex: a=15 b=9 c=22, with as many cages per line as there are cells per side
+    fn cages(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut cells = vec![self.blank.to_string(); self.layout.cells()];
+        for (index, cage) in self.layout.cages().iter().enumerate() {
+            for &cell in cage.cells.iter() {
+                cells[cell] = label(index);
+            }
+        }
+
+        writeln!(formatter)?;
+        boxed_grid(formatter, &cells, &self.layout, self.borders())?;
+
+        let sums: Vec<String> = self.layout.cages().iter().enumerate()
+            .map(|(index, cage)| format!("{}={}", label(index), cage.sum))
+            .collect();
+
+        for line in sums.chunks(self.layout.size()) {
+            writeln!(formatter, "{}", line.join(" "))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> std::fmt::Display for Grid<'a> {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let puzzle = self.lines(self.puzzle)?;
+
+        match self.solution {
+            Some(solution) => {
+                for (left, right) in puzzle.iter().zip(self.lines(solution)?) {
+                    writeln!(formatter, "{}   {}", left, right)?;
+                }
+            },
+            None => {
+                for line in puzzle.iter() {
+                    writeln!(formatter, "{}", line)?;
+                }
+            }
+        }
+
+        if self.layout.has_cages() {
+            self.cages(formatter)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn jigsaw_regions_are_outlined() {
+        let layout = Layout::jigsaw(&[1, 2, 2, 2, 1, 1, 1, 2, 3, 3, 4, 4, 3, 3, 4, 4]).unwrap();
+        let cells: Vec<String> = (0 .. 16).map(|index| symbol(index % 4 + 1).to_string()).collect();
+
+        let mut grid = String::new();
+        boxed_grid(&mut grid, &cells, &layout, &ASCII).unwrap();
+
+        assert_eq!(grid, [
+            "+---+-----------+",
+            "| 1 | 2   3   4 |",
+            "|   +-------+   |",
+            "| 1   2   3 | 4 |",
+            "+-------+---+---+",
+            "| 1   2 | 3   4 |",
+            "|       |       |",
+            "| 1   2 | 3   4 |",
+            "+-------+-------+",
+            "",
+        ].join("\n"));
+    }
+}
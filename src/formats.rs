@@ -0,0 +1,684 @@
+use crate::parse::{ parse_puzzle, cell_value, is_comment, ParseError, ParseErrorKind };
+
+// A puzzle along with whatever metadata the file it came from was able to describe. Formats that have no place for a
+// particular field simply leave it empty when reading, and drop it when writing.
+#[derive(PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PuzzleRecord {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialize::grid"))]
+    pub puzzle:     [u8; 81],
+    pub title:      Option<String>,
+    pub author:     Option<String>,
+    pub difficulty: Option<String>,
+    pub comment:    Option<String>,
+}
+
+impl PuzzleRecord {
+    pub fn new(puzzle: [u8; 81]) -> Self {
+        Self { puzzle, title: None, author: None, difficulty: None, comment: None }
+    }
+}
+
+impl From<[u8; 81]> for PuzzleRecord {
+    fn from(puzzle: [u8; 81]) -> Self {
+        Self::new(puzzle)
+    }
+}
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Format {
+    // SadMan Sudoku: a 9x9 grid preceded by "#X" metadata lines (author, description, level, ...)
+    Sdk,
+    // Simple Sudoku: a 9x9 grid with | column separators and a dashed line between bands
+    SimpleSudoku,
+    // Puzzle collections: one 81 character puzzle per line, optionally followed by a rating column
+    Text,
+    // OpenSudoku XML collections
+    OpenSudoku,
+}
+
+impl Format {
+    pub fn from_extension(extension: &str) -> Option<Format> {
+        match extension.to_ascii_lowercase().as_str() {
+            "sdk"                   => Some(Format::Sdk),
+            "ss"                    => Some(Format::SimpleSudoku),
+            "txt"                   => Some(Format::Text),
+            "xml" | "opensudoku"    => Some(Format::OpenSudoku),
+            _                       => None
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Format::Sdk             => "sdk",
+            Format::SimpleSudoku    => "ss",
+            Format::Text            => "txt",
+            Format::OpenSudoku      => "opensudoku",
+        }
+    }
+
+    // The grid based formats only ever hold a single puzzle per file, but for convenience any number of them may be
+    // concatenated; each complete grid starts a new record.
+    pub fn read(self, input: &str) -> Result<Vec<PuzzleRecord>, ParseError> {
+        match self {
+            Format::Sdk             => sdk::read(input),
+            Format::SimpleSudoku    => simple::read(input),
+            Format::Text            => text::read(input),
+            Format::OpenSudoku      => opensudoku::read(input),
+        }
+    }
+
+    pub fn write(self, records: &[PuzzleRecord]) -> String {
+        match self {
+            Format::Sdk             => sdk::write(records),
+            Format::SimpleSudoku    => simple::write(records),
+            Format::Text            => text::write(records),
+            Format::OpenSudoku      => opensudoku::write(records),
+        }
+    }
+}
+
+// Splits the input into consecutive blocks of lines that together contain exactly 81 cells and parses each of them.
+// Lines for which `skip` returns true (ex: metadata) are not considered to be part of a grid, even in the middle of
+// one. Along with each puzzle the line number its grid started on is returned.
+fn read_grids<F>(input: &str, mut skip: F) -> Result<Vec<([u8; 81], usize)>, ParseError>
+where F: FnMut(usize, &str) -> bool {
+    let mut grids = vec![];
+    let mut block = String::new();
+    // Line number of each line of the block within the input
+    let mut lines = vec![];
+    let mut cells = 0;
+
+    for (i, line) in input.lines().enumerate() {
+        if skip(i + 1, line) {
+            continue;
+        }
+
+        if cells == 0 {
+            block.clear();
+            lines.clear();
+        }
+
+        block.push_str(line);
+        block.push('\n');
+        lines.push(i + 1);
+
+        cells += line.chars()
+            .take_while(|&c| !is_comment(c))
+            .filter(|&c| cell_value(c).is_some())
+            .count();
+
+        if cells >= 81 {
+            grids.push((parse_lines(&block, &lines)?, lines[0]));
+            cells = 0;
+        }
+    }
+
+    if cells > 0 {
+        parse_lines(&block, &lines)?;
+    }
+
+    Ok(grids)
+}
+
+// Same as parse_at, for a block made up of the lines of the input with the given numbers. Errors past its last line
+// (ex: too few cells) point at the line after it.
+fn parse_lines(block: &str, lines: &[usize]) -> Result<[u8; 81], ParseError> {
+    parse_puzzle(block).map_err(|mut err| {
+        err.line = match lines.get(err.line - 1) {
+            Some(&line) => line,
+            None => lines[lines.len() - 1] + err.line - lines.len()
+        };
+        err
+    })
+}
+
+// Parses a puzzle that was extracted from a larger document, translating error positions so they point into the
+// original input.
+fn parse_at(input: &str, line: usize, column: usize) -> Result<[u8; 81], ParseError> {
+    parse_puzzle(input).map_err(|mut err| {
+        if err.line == 1 {
+            err.column += column - 1;
+        }
+        err.line += line - 1;
+        err
+    })
+}
+
+fn malformed(line: usize, column: usize, reason: &str) -> ParseError {
+    ParseError::new(line, column, ParseErrorKind::Malformed(reason.to_string()))
+}
+
+pub(crate) use self::xml::escape;
+
+fn cell_char(value: u8, blank: char) -> char {
+    if value == 0 { blank } else { (b'0' + value) as char }
+}
+
+mod sdk {
+    use super::*;
+
+    pub fn read(input: &str) -> Result<Vec<PuzzleRecord>, ParseError> {
+        let mut metadata = vec![];
+
+        // Version 2 files split the document into [Sections]; only the [Puzzle] section holds the starting grid.
+        let mut in_puzzle = true;
+
+        let grids = read_grids(input, |line_number, line| {
+            let line = line.trim();
+
+            if line.starts_with('[') {
+                in_puzzle = line.eq_ignore_ascii_case("[puzzle]");
+                return true;
+            }
+
+            if !in_puzzle {
+                return true;
+            }
+
+            match line.strip_prefix('#') {
+                Some(meta) => {
+                    metadata.push((line_number, meta.to_string()));
+                    true
+                },
+                None => false
+            }
+        })?;
+
+        // Metadata lines always precede the grid they describe
+        let mut metadata = metadata.into_iter().peekable();
+        let mut records = vec![];
+
+        for (puzzle, start) in grids {
+            let mut record = PuzzleRecord::new(puzzle);
+
+            while let Some((_, meta)) = metadata.next_if(|(line_number, _)| *line_number < start) {
+                let mut chars = meta.chars();
+                let field = match chars.next() {
+                    Some('D') => &mut record.title,
+                    Some('A') => &mut record.author,
+                    Some('L') => &mut record.difficulty,
+                    Some('C') => &mut record.comment,
+                    _ => continue
+                };
+
+                *field = Some(chars.as_str().trim().to_string());
+            }
+
+            records.push(record);
+        }
+
+        Ok(records)
+    }
+
+    pub fn write(records: &[PuzzleRecord]) -> String {
+        let mut output = String::new();
+
+        for (i, record) in records.iter().enumerate() {
+            if i > 0 {
+                output.push('\n');
+            }
+
+            let meta = [
+                ('D', &record.title), ('A', &record.author), ('L', &record.difficulty), ('C', &record.comment)
+            ];
+
+            for (code, value) in meta.iter() {
+                if let Some(value) = value {
+                    output.push_str(&format!("#{}{}\n", code, single_line(value)));
+                }
+            }
+
+            for row in record.puzzle.chunks(9) {
+                output.extend(row.iter().map(|&v| cell_char(v, '.')));
+                output.push('\n');
+            }
+        }
+
+        output
+    }
+}
+
+mod simple {
+    use super::*;
+
+    pub fn read(input: &str) -> Result<Vec<PuzzleRecord>, ParseError> {
+        let grids = read_grids(input, |_, _| false)?;
+        Ok(grids.into_iter().map(|(puzzle, _)| PuzzleRecord::new(puzzle)).collect())
+    }
+
+    pub fn write(records: &[PuzzleRecord]) -> String {
+        let mut output = String::new();
+
+        for (i, record) in records.iter().enumerate() {
+            if i > 0 {
+                output.push('\n');
+            }
+
+            for (y, row) in record.puzzle.chunks(9).enumerate() {
+                if y > 0 && y % 3 == 0 {
+                    output.push_str("-----------\n");
+                }
+
+                for (x, &value) in row.iter().enumerate() {
+                    if x > 0 && x % 3 == 0 {
+                        output.push('|');
+                    }
+                    output.push(cell_char(value, '.'));
+                }
+                output.push('\n');
+            }
+        }
+
+        output
+    }
+}
+
+mod text {
+    use super::*;
+
+    pub fn read(input: &str) -> Result<Vec<PuzzleRecord>, ParseError> {
+        let mut records = vec![];
+
+        for (i, line) in input.lines().enumerate() {
+            let trimmed = line.trim_start();
+            if trimmed.is_empty() || trimmed.starts_with(is_comment) {
+                continue;
+            }
+
+            let indent = line.chars().count() - trimmed.chars().count();
+            let (puzzle, rating) = match trimmed.find(char::is_whitespace) {
+                Some(end) => (&trimmed[..end], trimmed[end..].trim()),
+                None => (trimmed, "")
+            };
+
+            let mut record = PuzzleRecord::new(parse_at(puzzle, i + 1, indent + 1)?);
+            if !rating.is_empty() {
+                record.difficulty = Some(rating.to_string());
+            }
+
+            records.push(record);
+        }
+
+        Ok(records)
+    }
+
+    pub fn write(records: &[PuzzleRecord]) -> String {
+        let mut output = String::new();
+
+        for record in records.iter() {
+            output.extend(record.puzzle.iter().map(|&v| cell_char(v, '.')));
+
+            if let Some(difficulty) = &record.difficulty {
+                output.push(' ');
+                output.push_str(&single_line(difficulty));
+            }
+
+            output.push('\n');
+        }
+
+        output
+    }
+}
+
+mod opensudoku {
+    use super::{ *, xml::{ Tokenizer, Token, escape } };
+
+    // OpenSudoku stores its metadata once per collection; individual games only carry their grid and an optional note.
+    pub fn read(input: &str) -> Result<Vec<PuzzleRecord>, ParseError> {
+        let mut records = vec![];
+        let mut collection = PuzzleRecord::new([0; 81]);
+        let mut notes = vec![];
+
+        let mut tokenizer = Tokenizer::new(input);
+        let mut path: Vec<String> = vec![];
+
+        while let Some(token) = tokenizer.next()? {
+            match token {
+                Token::Start { name, attributes, closed } => {
+                    if name == "game" {
+                        let (line, column) = tokenizer.position();
+                        let data = match attributes.iter().find(|(key, _)| key == "data") {
+                            Some((_, data)) => data,
+                            None => return Err(malformed(line, column, "game element is missing its data attribute"))
+                        };
+
+                        let puzzle = parse_puzzle(data).map_err(|err| {
+                            malformed(line, column, &format!("invalid game data: {}", err.kind))
+                        })?;
+
+                        records.push(puzzle);
+                        notes.push(attributes.into_iter().find(|(key, _)| key == "note").map(|(_, note)| note));
+                    }
+
+                    if !closed {
+                        path.push(name);
+                    }
+                },
+
+                Token::End(name) => {
+                    if path.pop().as_ref() != Some(&name) {
+                        let (line, column) = tokenizer.position();
+                        return Err(malformed(line, column, &format!("unexpected closing tag </{}>", name)));
+                    }
+                },
+
+                Token::Text(text) => {
+                    let text = text.trim();
+                    if text.is_empty() || path.len() != 2 || path[0] != "opensudoku" { continue; }
+
+                    let field = match path[1].as_str() {
+                        "name"          => &mut collection.title,
+                        "author"        => &mut collection.author,
+                        "level"         => &mut collection.difficulty,
+                        "description"   => &mut collection.comment,
+                        "comment"       => &mut collection.comment,
+                        _ => continue
+                    };
+
+                    *field = Some(text.to_string());
+                }
+            }
+        }
+
+        if let Some(name) = path.pop() {
+            let (line, column) = tokenizer.position();
+            return Err(malformed(line, column, &format!("missing closing tag </{}>", name)));
+        }
+
+        Ok(records.into_iter().zip(notes).map(|(puzzle, note)| {
+            PuzzleRecord {
+                puzzle,
+                comment: note.or_else(|| collection.comment.clone()),
+                ..collection.clone()
+            }
+        }).collect())
+    }
+
+    // Metadata shared by every record is written once for the whole collection, and read back into each of them. A
+    // comment that differs from one record to the next is kept as the note of each game instead, while titles, authors
+    // and levels that differ have no place in the format and are dropped.
+    pub fn write(records: &[PuzzleRecord]) -> String {
+        let mut output = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<opensudoku>\n");
+
+        let description = shared(records, |record| &record.comment);
+        let meta = [
+            ("name",        shared(records, |record| &record.title)),
+            ("author",      shared(records, |record| &record.author)),
+            ("level",       shared(records, |record| &record.difficulty)),
+            ("description", description),
+        ];
+
+        for (tag, value) in meta.iter() {
+            if let Some(value) = value {
+                output.push_str(&format!("  <{0}>{1}</{0}>\n", tag, escape(value)));
+            }
+        }
+
+        for record in records.iter() {
+            let data: String = record.puzzle.iter().map(|&v| cell_char(v, '0')).collect();
+            match record.comment.as_ref().filter(|_| description.is_none()) {
+                Some(note) => output.push_str(&format!("  <game data=\"{}\" note=\"{}\"/>\n", data, escape(note))),
+                None => output.push_str(&format!("  <game data=\"{}\"/>\n", data)),
+            }
+        }
+
+        output.push_str("</opensudoku>\n");
+        output
+    }
+
+    // The value of a field if every record has the same one
+    fn shared<F>(records: &[PuzzleRecord], field: F) -> Option<&String>
+    where F: Fn(&PuzzleRecord) -> &Option<String> {
+        let first = field(records.first()?).as_ref()?;
+
+        match records.iter().all(|record| field(record).as_ref() == Some(first)) {
+            true  => Some(first),
+            false => None
+        }
+    }
+}
+
+// Just enough of an XML reader to get through OpenSudoku files: elements, attributes, text and the standard entities.
+// Comments, processing instructions and doctype declarations are skipped.
+mod xml {
+    use super::*;
+
+    pub enum Token {
+        Start { name: String, attributes: Vec<(String, String)>, closed: bool },
+        End(String),
+        Text(String),
+    }
+
+    pub struct Tokenizer<'a> {
+        input:  &'a str,
+        offset: usize,
+        start:  usize,
+    }
+
+    impl<'a> Tokenizer<'a> {
+        pub fn new(input: &'a str) -> Self {
+            Self { input, offset: 0, start: 0 }
+        }
+
+        // Line and column of the start of the most recently read token
+        pub fn position(&self) -> (usize, usize) {
+            position(self.input, self.start)
+        }
+
+        pub fn next(&mut self) -> Result<Option<Token>, ParseError> {
+            loop {
+                self.start = self.offset;
+                let rest = &self.input[self.offset..];
+
+                if rest.is_empty() {
+                    return Ok(None);
+                }
+
+                if !rest.starts_with('<') {
+                    let end = rest.find('<').unwrap_or(rest.len());
+                    self.offset += end;
+                    return Ok(Some(Token::Text(unescape(&rest[..end]))));
+                }
+
+                let skip = [("<?", "?>"), ("<!--", "-->"), ("<!", ">")].iter()
+                    .find(|(open, _)| rest.starts_with(open))
+                    .map(|(_, close)| *close);
+
+                if let Some(close) = skip {
+                    self.offset += self.find(rest, close)? + close.len();
+                    continue;
+                }
+
+                let end = self.find(rest, ">")?;
+                self.offset += end + 1;
+                return self.tag(&rest[1..end]).map(Some);
+            }
+        }
+
+        fn find(&self, rest: &str, pattern: &str) -> Result<usize, ParseError> {
+            match rest.find(pattern) {
+                Some(index) => Ok(index),
+                None => {
+                    let (line, column) = self.position();
+                    Err(malformed(line, column, &format!("unterminated markup, expected {:?}", pattern)))
+                }
+            }
+        }
+
+        fn tag(&self, tag: &str) -> Result<Token, ParseError> {
+            if let Some(name) = tag.strip_prefix('/') {
+                return Ok(Token::End(name.trim().to_string()));
+            }
+
+            let closed = tag.ends_with('/');
+            let tag = tag.trim_end_matches('/');
+
+            let name_end = tag.find(char::is_whitespace).unwrap_or(tag.len());
+            let name = tag[..name_end].to_string();
+            let mut rest = tag[name_end..].trim_start();
+            let mut attributes = vec![];
+
+            while !rest.is_empty() {
+                let (line, column) = self.position();
+                let invalid = || malformed(line, column, &format!("invalid attribute in <{}>", name));
+
+                let eq = rest.find('=').ok_or_else(invalid)?;
+                let key = rest[..eq].trim().to_string();
+                let value = rest[eq + 1..].trim_start();
+
+                let quote = value.chars().next().filter(|&c| c == '"' || c == '\'').ok_or_else(invalid)?;
+                let end = value[1..].find(quote).ok_or_else(invalid)?;
+
+                attributes.push((key, unescape(&value[1..end + 1])));
+                rest = value[end + 2..].trim_start();
+            }
+
+            Ok(Token::Start { name, attributes, closed })
+        }
+    }
+
+    fn position(input: &str, offset: usize) -> (usize, usize) {
+        let before = &input[..offset];
+        let line = before.matches('\n').count() + 1;
+        let column = before.rsplit('\n').next().unwrap_or("").chars().count() + 1;
+        (line, column)
+    }
+
+    pub fn unescape(text: &str) -> String {
+        let mut output = String::with_capacity(text.len());
+        let mut rest = text;
+
+        while let Some(start) = rest.find('&') {
+            output.push_str(&rest[..start]);
+            rest = &rest[start..];
+
+            let end = match rest.find(';') {
+                Some(end) => end,
+                None => break
+            };
+
+            let entity = &rest[1..end];
+            let decoded = match entity {
+                "amp"   => Some('&'),
+                "lt"    => Some('<'),
+                "gt"    => Some('>'),
+                "quot"  => Some('"'),
+                "apos"  => Some('\''),
+                _ => entity.strip_prefix("#x")
+                    .map(|hex| u32::from_str_radix(hex, 16).ok())
+                    .unwrap_or_else(|| entity.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                    .and_then(std::char::from_u32)
+            };
+
+            match decoded {
+                Some(c) => {
+                    output.push(c);
+                    rest = &rest[end + 1..];
+                },
+                None => {
+                    output.push('&');
+                    rest = &rest[1..];
+                }
+            }
+        }
+
+        output.push_str(rest);
+        output
+    }
+
+    pub fn escape(text: &str) -> String {
+        text.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+    }
+}
+
+// Line based formats have no way of representing metadata that spans multiple lines
+fn single_line(text: &str) -> String {
+    text.lines().map(str::trim).collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::PUZZLE;
+
+    fn record(comment: Option<&str>) -> PuzzleRecord {
+        PuzzleRecord {
+            title:      Some(String::from("Daily")),
+            author:     Some(String::from("W & I")),
+            difficulty: Some(String::from("hard")),
+            comment:    comment.map(String::from),
+            ..PuzzleRecord::new(parse_puzzle(PUZZLE).unwrap())
+        }
+    }
+
+    #[test]
+    fn metadata_round_trips() {
+        let records = vec![record(Some("first")), record(None)];
+
+        let sdk = Format::Sdk.read(&Format::Sdk.write(&records)).unwrap();
+        assert_eq!(sdk, records);
+
+        let text = Format::Text.read(&Format::Text.write(&records)).unwrap();
+        assert_eq!(text[0].difficulty.as_deref(), Some("hard"));
+        assert_eq!(text[0].puzzle, records[0].puzzle);
+
+        let simple = Format::SimpleSudoku.read(&Format::SimpleSudoku.write(&records)).unwrap();
+        assert_eq!(simple.len(), 2);
+        assert_eq!(simple[1].puzzle, records[1].puzzle);
+    }
+
+    #[test]
+    fn opensudoku_keeps_collection_metadata_apart_from_notes() {
+        let input = format!(
+            "<opensudoku>\n  <name>Daily</name>\n  <description>Week 1</description>\n  \
+             <game data=\"{0}\"/>\n  <game data=\"{0}\"/>\n</opensudoku>\n",
+            PUZZLE
+        );
+
+        let records = Format::OpenSudoku.read(&input).unwrap();
+        let output = Format::OpenSudoku.write(&records);
+
+        assert_eq!(records[1].comment.as_deref(), Some("Week 1"));
+        assert!(output.contains("<description>Week 1</description>"));
+        assert!(!output.contains("note="));
+        assert_eq!(Format::OpenSudoku.read(&output).unwrap(), records);
+
+        let records = vec![record(Some("first")), record(None)];
+        let output = Format::OpenSudoku.write(&records);
+
+        assert!(output.contains("<author>W &amp; I</author>"));
+        assert!(!output.contains("<description>"));
+        assert_eq!(Format::OpenSudoku.read(&output).unwrap(), records);
+    }
+
+    #[test]
+    fn errors_point_into_the_document() {
+        let error = Format::Text.read(&format!("{}\n  {}x\n", PUZZLE, &PUZZLE[1..])).unwrap_err();
+        assert_eq!((error.line, error.column), (2, 83));
+
+        let error = Format::OpenSudoku.read("<opensudoku>\n  <game/>\n</opensudoku>").unwrap_err();
+        assert_eq!(error.line, 2);
+    }
+
+    #[test]
+    fn errors_count_the_metadata_lines_within_a_grid() {
+        let mut rows: Vec<String> = (0 .. 9).map(|row| PUZZLE[row * 9 .. row * 9 + 9].to_string()).collect();
+        rows[6].replace_range(4 .. 5, "x");
+        rows.insert(4, String::from("#Cbetween the rows"));
+        rows.insert(0, String::from("#Dtitle"));
+
+        let error = Format::Sdk.read(&rows.join("\n")).unwrap_err();
+        assert_eq!((error.line, error.column), (9, 5));
+
+        rows[8] = rows[8].replace('x', "0");
+        rows.pop();
+        let error = Format::Sdk.read(&rows.join("\n")).unwrap_err();
+        assert_eq!(error.kind, ParseErrorKind::TooFewCells { expected: 81, found: 72 });
+        assert_eq!(error.line, 11);
+    }
+}
@@ -0,0 +1,731 @@
+use crate :: {
+    sudoku      :: { Sudoku, traits :: SudokuState },
+    candidates  :: { CandidateSet, CandidateBits },
+    bitsets     :: traits :: BitSet,
+    grader      :: { self, Hint, ValidationError },
+    codec       :: { CodecError, BitReader, BitWriter, base64 },
+    indices,
+};
+
+use std :: { fmt, time :: Duration };
+
+use im_rc :: Vector;
+
+// A puzzle being played: its givens and solution, the digits and pencil marks the player has entered, and the history
+// of every change for undo and redo. Like Sudoku, each state of the board is persistent; entries and marks are kept in
+// im_rc vectors, so a state shares everything but the changed cell with the one before it, and keeping the full history
+// around costs very little.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Game {
+    givens:     [u8; 81],
+    solution:   [u8; 81],
+    board:      Board,
+    undo:       Vec<Board>,
+    redo:       Vec<Board>,
+    hints:      usize,
+    elapsed:    Duration,
+    auto_marks: bool,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+struct Board {
+    // 0 for cells without an entry, including every given
+    entries:    Vector<u8>,
+    marks:      Vector<CandidateSet>,
+    // Marks taken away by automatic maintenance rather than by the player, which come back once they're valid again
+    stripped:   Vector<CandidateSet>,
+}
+
+impl Board {
+    fn new() -> Board {
+        Board {
+            entries:    Vector::from(&[0u8; 81][..]),
+            marks:      Vector::from(&[CandidateSet::empty(); 81][..]),
+            stripped:   Vector::from(&[CandidateSet::empty(); 81][..]),
+        }
+    }
+
+    // Same as remove_candidates does for the solver's candidates, except that the cell's own marks are left alone
+    fn strip(&mut self, index: usize, value: u8) {
+        let value = CandidateSet::from(value);
+
+        for &peer in indices::rcb_containing(index).iter().filter(|&&peer| peer != index) {
+            if self.marks[peer].contains(value) {
+                self.marks.set(peer, self.marks[peer] - value);
+                self.stripped.set(peer, self.stripped[peer] | value);
+            }
+        }
+    }
+
+    // Puts back the marks stripped for a digit that was erased, unless another cell still rules them out
+    fn restore(&mut self, givens: &[u8; 81], index: usize, value: u8) {
+        let mark = CandidateSet::from(value);
+
+        for &peer in indices::rcb_containing(index).iter().filter(|&&peer| peer != index) {
+            if !self.stripped[peer].contains(mark) {
+                continue;
+            }
+
+            let blocked = indices::rcb_containing(peer).iter()
+                .any(|&other| other != peer && (givens[other] == value || self.entries[other] == value));
+
+            if !blocked {
+                self.marks.set(peer, self.marks[peer] | mark);
+                self.stripped.set(peer, self.stripped[peer] - mark);
+            }
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum GameError {
+    InvalidIndex(usize),
+    InvalidValue(u8),
+    // The cell holds one of the puzzle's givens, which can't be changed
+    Given(usize),
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameError::InvalidIndex(index) =>
+                write!(formatter, "cell index {} is out of range", index),
+            GameError::InvalidValue(value) =>
+                write!(formatter, "{} is not a digit from 1 to 9", value),
+            GameError::Given(index) =>
+                write!(formatter, "cell r{}c{} is one of the puzzle's givens", index / 9 + 1, index % 9 + 1),
+        }
+    }
+}
+
+impl std::error::Error for GameError { }
+
+impl Game {
+    // Starts a game of a puzzle, which must have a unique solution
+    pub fn new(puzzle: &[u8; 81]) -> Result<Game, ValidationError> {
+        let solution = grader::validate(puzzle)?;
+        Ok(Game::with_solution(puzzle, &solution))
+    }
+
+    // Starts a game of a puzzle whose solution is already known, ex: the output of the generator
+    pub fn with_solution(puzzle: &[u8; 81], solution: &[u8; 81]) -> Game {
+        Game {
+            givens:     *puzzle,
+            solution:   *solution,
+            board:      Board::new(),
+            undo:       vec![],
+            redo:       vec![],
+            hints:      0,
+            elapsed:    Duration::default(),
+            auto_marks: false,
+        }
+    }
+
+    pub fn givens(&self) -> [u8; 81] {
+        self.givens
+    }
+
+    pub fn solution(&self) -> [u8; 81] {
+        self.solution
+    }
+
+    // The givens and the player's entries together
+    pub fn board(&self) -> [u8; 81] {
+        let mut board = self.givens;
+        for (cell, &entry) in board.iter_mut().zip(self.board.entries.iter()) {
+            if entry > 0 {
+                *cell = entry;
+            }
+        }
+        board
+    }
+
+    pub fn is_given(&self, index: usize) -> bool {
+        self.givens.get(index).is_some_and(|&value| value > 0)
+    }
+
+    // The digit shown in a cell, whether it's a given or an entry; 0 if the cell is empty
+    pub fn value(&self, index: usize) -> u8 {
+        match self.givens.get(index) {
+            Some(&given) if given > 0 => given,
+            Some(_) => self.board.entries[index],
+            None => 0
+        }
+    }
+
+    // The pencil marks of a cell, in ascending order. Marks are kept while a cell holds a digit, and show up again when
+    // the digit is erased.
+    pub fn marks(&self, index: usize) -> Vec<u8> {
+        match self.board.marks.get(index) {
+            Some(marks) => marks.iter().map(u8::from).collect(),
+            None => vec![]
+        }
+    }
+
+    pub fn has_mark(&self, index: usize, value: u8) -> bool {
+        self.board.marks.get(index).is_some_and(|marks| value > 0 && marks.contains(CandidateSet::from(value)))
+    }
+
+    // Keeps pencil marks up to date as digits are placed and erased: placing a digit removes it from the marks of every
+    // cell in the same row, column and box, and erasing it puts those marks back once nothing else rules them out. Marks
+    // the player removed by hand are never put back, and marks changed by hand are left alone from then on. Turning
+    // this on doesn't change any marks by itself.
+    pub fn auto_marks(&mut self, enabled: bool) -> &mut Self {
+        self.auto_marks = enabled;
+
+        self
+    }
+
+    pub fn has_auto_marks(&self) -> bool {
+        self.auto_marks
+    }
+
+    pub fn place(&mut self, index: usize, value: u8) -> Result<(), GameError> {
+        self.check(index, Some(value))?;
+
+        let mut board = self.board.clone();
+        let previous = board.entries[index];
+        board.entries.set(index, value);
+
+        if self.auto_marks && previous != value {
+            if previous > 0 {
+                board.restore(&self.givens, index, previous);
+            }
+            board.strip(index, value);
+        }
+
+        self.commit(board);
+
+        Ok(())
+    }
+
+    pub fn erase(&mut self, index: usize) -> Result<(), GameError> {
+        self.check(index, None)?;
+
+        let mut board = self.board.clone();
+        let previous = board.entries[index];
+        board.entries.set(index, 0);
+
+        if self.auto_marks && previous > 0 {
+            board.restore(&self.givens, index, previous);
+        }
+
+        self.commit(board);
+
+        Ok(())
+    }
+
+    pub fn toggle_mark(&mut self, index: usize, value: u8) -> Result<(), GameError> {
+        self.check(index, Some(value))?;
+
+        let mut board = self.board.clone();
+        board.marks.set(index, board.marks[index] ^ CandidateSet::from(value));
+        board.stripped.set(index, board.stripped[index] - CandidateSet::from(value));
+        self.commit(board);
+
+        Ok(())
+    }
+
+    // Replaces all of a cell's pencil marks at once
+    pub fn set_marks(&mut self, index: usize, values: &[u8]) -> Result<(), GameError> {
+        self.check(index, None)?;
+
+        let mut marks = CandidateSet::empty();
+        for &value in values {
+            if !(1..=9).contains(&value) {
+                return Err(GameError::InvalidValue(value));
+            }
+            marks |= CandidateSet::from(value);
+        }
+
+        let mut board = self.board.clone();
+        board.marks.set(index, marks);
+        board.stripped.set(index, CandidateSet::empty());
+        self.commit(board);
+
+        Ok(())
+    }
+
+    // Returns whether there was anything to undo
+    pub fn undo(&mut self) -> bool {
+        match self.undo.pop() {
+            Some(board) => {
+                self.redo.push(std::mem::replace(&mut self.board, board));
+                true
+            },
+            None => false
+        }
+    }
+
+    pub fn redo(&mut self) -> bool {
+        match self.redo.pop() {
+            Some(board) => {
+                self.undo.push(std::mem::replace(&mut self.board, board));
+                true
+            },
+            None => false
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    // Entries that don't match the solution
+    pub fn mistakes(&self) -> Vec<usize> {
+        (0..81).filter(|&index| self.is_mistake(index)).collect()
+    }
+
+    pub fn is_mistake(&self, index: usize) -> bool {
+        match self.board.entries.get(index) {
+            Some(&entry) => entry > 0 && entry != self.solution[index],
+            None => false
+        }
+    }
+
+    // Cells (givens included) holding a digit that repeats within their row, column or box. Unlike mistakes, these are
+    // plain to see on the board, and can be pointed out without giving anything away.
+    pub fn conflicts(&self) -> Vec<usize> {
+        let board = self.board();
+        (0..81).filter(|&index| conflicts(&board, index)).collect()
+    }
+
+    pub fn is_conflict(&self, index: usize) -> bool {
+        index < 81 && conflicts(&self.board(), index)
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.board() == self.solution
+    }
+
+    // Finds the next digit to place, and counts it towards the hints taken. Hints are worked out from the board as it
+    // stands, so none are given while it holds mistakes.
+    pub fn hint(&mut self) -> Option<Hint> {
+        if !self.mistakes().is_empty() {
+            return None;
+        }
+
+        let hint = grader::hint(&Sudoku::new(&self.board()));
+        if hint.is_some() {
+            self.hints += 1;
+        }
+
+        hint
+    }
+
+    pub fn hints(&self) -> usize {
+        self.hints
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    // The game doesn't keep time itself, since there's no clock to read from in WASM; the time played is recorded here
+    // by the caller so that it's saved along with the rest of the session.
+    pub fn set_elapsed(&mut self, elapsed: Duration) {
+        self.elapsed = elapsed;
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut writer = BitWriter::default();
+        writer.write(u32::from(SAVE_VERSION), 8);
+        writer.write(if self.auto_marks { u32::from(AUTO_MARKS) } else { 0 }, 8);
+
+        for &given in self.givens.iter() {
+            writer.write(u32::from(given > 0), 1);
+        }
+        writer.write_digits(self.givens.iter().copied().filter(|&given| given > 0));
+        writer.write_digits((0..81).filter(|&index| self.givens[index] == 0).map(|index| self.solution[index]));
+
+        let milliseconds = std::cmp::min(self.elapsed.as_millis(), MAX_MILLISECONDS) as u64;
+        writer.write((milliseconds >> 32) as u32, 16);
+        writer.write(milliseconds as u32, 32);
+        writer.write(std::cmp::min(self.hints, MAX_COUNT) as u32, 16);
+
+        write_board(&mut writer, &self.board);
+
+        // Undo steps are written from the most recent one back, so that the oldest are the ones dropped past MAX_COUNT
+        let undo = std::cmp::min(self.undo.len(), MAX_COUNT);
+        writer.write(undo as u32, 16);
+
+        let mut next = &self.board;
+        for board in self.undo.iter().rev().take(undo) {
+            write_step(&mut writer, next, board);
+            next = board;
+        }
+
+        let redo = std::cmp::min(self.redo.len(), MAX_COUNT);
+        writer.write(redo as u32, 16);
+
+        let mut previous = &self.board;
+        for board in self.redo.iter().rev().take(redo) {
+            write_step(&mut writer, previous, board);
+            previous = board;
+        }
+
+        writer.bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Game, CodecError> {
+        let mut reader = BitReader::new(bytes);
+
+        let version = reader.read(8)? as u8;
+        if version == 0 || version > SAVE_VERSION {
+            return Err(CodecError::UnsupportedVersion(version));
+        }
+
+        let flags = match version {
+            1 => 0,
+            _ => reader.read(8)? as u8
+        };
+
+        let mut filled = vec![];
+        for index in 0..81 {
+            if reader.read(1)? == 1 {
+                filled.push(index);
+            }
+        }
+
+        let mut givens = [0; 81];
+        for (&index, value) in filled.iter().zip(reader.read_digits(filled.len())?) {
+            givens[index] = value;
+        }
+
+        let empty: Vec<usize> = (0..81).filter(|&index| givens[index] == 0).collect();
+        let mut solution = givens;
+        for (&index, value) in empty.iter().zip(reader.read_digits(empty.len())?) {
+            solution[index] = value;
+        }
+
+        let mut game = Game::with_solution(&givens, &solution);
+        game.auto_marks = flags & AUTO_MARKS > 0;
+
+        let milliseconds = u64::from(reader.read(16)?) << 32 | u64::from(reader.read(32)?);
+        game.elapsed = Duration::from_millis(milliseconds);
+        game.hints = reader.read(16)? as usize;
+        game.board = read_board(&mut reader, &givens, version)?;
+
+        for _ in 0..reader.read(16)? {
+            let board = read_step(&mut reader, game.undo.last().unwrap_or(&game.board), &givens, version)?;
+            game.undo.push(board);
+        }
+        game.undo.reverse();
+
+        for _ in 0..reader.read(16)? {
+            let board = read_step(&mut reader, game.redo.last().unwrap_or(&game.board), &givens, version)?;
+            game.redo.push(board);
+        }
+        game.redo.reverse();
+
+        reader.finish()?;
+
+        Ok(game)
+    }
+
+    // The saved session as URL safe base64, the same encoding used for CompactPuzzle
+    pub fn to_base64(&self) -> String {
+        base64::encode(&self.to_bytes())
+    }
+
+    pub fn from_base64(text: &str) -> Result<Game, CodecError> {
+        Game::from_bytes(&base64::decode(text)?)
+    }
+
+    fn check(&self, index: usize, value: Option<u8>) -> Result<(), GameError> {
+        if index >= 81 {
+            return Err(GameError::InvalidIndex(index));
+        }
+
+        if let Some(value) = value.filter(|value| !(1..=9).contains(value)) {
+            return Err(GameError::InvalidValue(value));
+        }
+
+        match self.is_given(index) {
+            true  => Err(GameError::Given(index)),
+            false => Ok(())
+        }
+    }
+
+    // Changes that leave the board as it was aren't recorded, and any new change drops the redo history
+    fn commit(&mut self, board: Board) {
+        if board == self.board {
+            return;
+        }
+
+        self.undo.push(std::mem::replace(&mut self.board, board));
+        self.redo.clear();
+    }
+}
+
+// Version 2 layout of a saved game, with all values packed most significant bit first as in CompactPuzzle:
+//
+//  - 1 byte holding the format version
+//  - 1 byte of flags (bit 0: pencil marks are kept up to date automatically)
+//  - 81 bits marking the givens, followed by their digits and then the solution's digits for the remaining cells, all
+//    packed three at a time as in CompactPuzzle
+//  - the elapsed time in milliseconds (48 bits) and the number of hints taken (16 bits)
+//  - the board: 81 bits marking the cells with an entry followed by their digits, then 81 bits marking the cells with
+//    pencil marks followed by 9 bits of marks for each of them, and the same again for the marks stripped automatically
+//  - the number of undo steps (16 bits), then each step from the most recent one back
+//  - the number of redo steps (16 bits), then each step from the next one forward
+//
+// Each step holds the cells that differ from the board before it in that order: their count (7 bits), then for each
+// cell its index (7 bits), entry (4 bits), marks (9 bits) and stripped marks (9 bits). A step usually changes a single
+// cell, and takes 36 bits.
+//
+// Version 1 has neither the flags nor anything about stripped marks, and is still read.
+const SAVE_VERSION: u8 = 2;
+const AUTO_MARKS: u8 = 0x01;
+const MAX_COUNT: usize = 0xFFFF;
+const MAX_MILLISECONDS: u128 = (1 << 48) - 1;
+
+fn write_board(writer: &mut BitWriter, board: &Board) {
+    for &entry in board.entries.iter() {
+        writer.write(u32::from(entry > 0), 1);
+    }
+    writer.write_digits(board.entries.iter().copied().filter(|&entry| entry > 0));
+
+    write_marks(writer, &board.marks);
+    write_marks(writer, &board.stripped);
+}
+
+fn write_marks(writer: &mut BitWriter, marks: &Vector<CandidateSet>) {
+    for cell in marks.iter() {
+        writer.write(u32::from(!cell.is_empty()), 1);
+    }
+    for &cell in marks.iter().filter(|cell| !cell.is_empty()) {
+        write_mark(writer, cell);
+    }
+}
+
+// Marks of a 9x9 grid only ever hold digits 1-9, one bit each, whatever the width of a candidate set
+fn write_mark(writer: &mut BitWriter, marks: CandidateSet) {
+    writer.write(marks.iter().fold(0, |bits, digit| bits | 1 << (u8::from(digit) - 1)), 9);
+}
+
+fn read_board(reader: &mut BitReader, givens: &[u8; 81], version: u8) -> Result<Board, CodecError> {
+    let mut board = Board::new();
+
+    let filled = read_mask(reader)?;
+    for (&index, entry) in filled.iter().zip(reader.read_digits(filled.len())?) {
+        check_entry(givens, index, entry)?;
+        board.entries.set(index, entry);
+    }
+
+    for index in read_mask(reader)? {
+        board.marks.set(index, read_marks(reader)?);
+    }
+
+    if version > 1 {
+        for index in read_mask(reader)? {
+            board.stripped.set(index, read_marks(reader)?);
+        }
+    }
+
+    Ok(board)
+}
+
+fn read_mask(reader: &mut BitReader) -> Result<Vec<usize>, CodecError> {
+    let mut indices = vec![];
+    for index in 0..81 {
+        if reader.read(1)? == 1 {
+            indices.push(index);
+        }
+    }
+
+    Ok(indices)
+}
+
+fn read_marks(reader: &mut BitReader) -> Result<CandidateSet, CodecError> {
+    CandidateSet::from_bits(CandidateBits::from(reader.read(9)?)).ok_or(CodecError::InvalidData)
+}
+
+// Givens are never changed, so saves holding entries for them are rejected
+fn check_entry(givens: &[u8; 81], index: usize, entry: u8) -> Result<(), CodecError> {
+    match entry > 9 || (entry > 0 && givens[index] > 0) {
+        true  => Err(CodecError::InvalidValue(index)),
+        false => Ok(())
+    }
+}
+
+fn write_step(writer: &mut BitWriter, from: &Board, to: &Board) {
+    let changed: Vec<usize> = (0..81)
+        .filter(|&index| {
+            from.entries[index] != to.entries[index] ||
+            from.marks[index] != to.marks[index] ||
+            from.stripped[index] != to.stripped[index]
+        })
+        .collect();
+
+    writer.write(changed.len() as u32, 7);
+    for index in changed {
+        writer.write(index as u32, 7);
+        writer.write(u32::from(to.entries[index]), 4);
+        write_mark(writer, to.marks[index]);
+        write_mark(writer, to.stripped[index]);
+    }
+}
+
+// Steps are applied to a copy of the board before them, which keeps sharing everything but the cells they change
+fn read_step(reader: &mut BitReader, from: &Board, givens: &[u8; 81], version: u8) -> Result<Board, CodecError> {
+    let mut board = from.clone();
+
+    for _ in 0..reader.read(7)? {
+        let index = reader.read(7)? as usize;
+        if index >= 81 {
+            return Err(CodecError::InvalidData);
+        }
+
+        let entry = reader.read(4)? as u8;
+        check_entry(givens, index, entry)?;
+
+        board.entries.set(index, entry);
+        board.marks.set(index, read_marks(reader)?);
+
+        if version > 1 {
+            board.stripped.set(index, read_marks(reader)?);
+        }
+    }
+
+    Ok(board)
+}
+
+fn conflicts(board: &[u8; 81], index: usize) -> bool {
+    let value = board[index];
+    value > 0 && indices::rcb_containing(index).iter().any(|&peer| peer != index && board[peer] == value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::puzzle;
+
+    fn game() -> Game {
+        Game::new(&puzzle()).unwrap()
+    }
+
+    #[test]
+    fn changes_are_undone_and_redone() {
+        let mut game = game();
+        let solution = game.solution();
+
+        game.place(0, solution[0]).unwrap();
+        game.toggle_mark(1, 3).unwrap();
+        assert!(game.can_undo() && !game.can_redo());
+
+        assert!(game.undo());
+        assert!(!game.has_mark(1, 3));
+        assert!(game.undo());
+        assert_eq!(game.value(0), 0);
+        assert!(!game.undo());
+
+        assert!(game.redo());
+        assert_eq!(game.value(0), solution[0]);
+
+        // A new change drops what was left to redo
+        game.erase(0).unwrap();
+        assert!(!game.can_redo());
+        assert!(game.undo());
+        assert_eq!(game.value(0), solution[0]);
+    }
+
+    #[test]
+    fn givens_and_invalid_values_are_refused() {
+        let mut game = game();
+
+        assert_eq!(game.place(3, 1), Err(GameError::Given(3)));
+        assert_eq!(game.place(0, 10), Err(GameError::InvalidValue(10)));
+        assert_eq!(game.erase(81), Err(GameError::InvalidIndex(81)));
+        assert!(!game.can_undo());
+    }
+
+    #[test]
+    fn mistakes_and_conflicts_are_told_apart() {
+        let mut game = game();
+        let solution = game.solution();
+
+        // 2 is a given of both the first row and the first column
+        game.place(0, 2).unwrap();
+        assert_eq!(game.mistakes(), vec![0]);
+        assert_eq!(game.conflicts(), vec![0, 3, 27]);
+
+        game.place(0, solution[0]).unwrap();
+        assert!(game.mistakes().is_empty() && game.conflicts().is_empty());
+        assert!(!game.is_complete());
+    }
+
+    #[test]
+    fn auto_marks_follow_the_entries() {
+        let mut game = game();
+        game.auto_marks(true);
+
+        game.set_marks(1, &[1, 6, 9]).unwrap();
+        game.place(0, 1).unwrap();
+        assert_eq!(game.marks(1), vec![6, 9]);
+
+        game.erase(0).unwrap();
+        assert_eq!(game.marks(1), vec![1, 6, 9]);
+    }
+
+    #[test]
+    fn saves_keep_the_whole_session() {
+        let mut game = game();
+        let solution = game.solution();
+
+        game.auto_marks(true);
+        game.set_marks(1, &[1, 6, 9]).unwrap();
+        game.place(0, solution[0]).unwrap();
+        game.place(2, solution[2]).unwrap();
+        game.undo();
+        game.set_elapsed(Duration::from_secs(90));
+        game.hint();
+
+        assert_eq!(Game::from_base64(&game.to_base64()), Ok(game));
+    }
+
+    #[test]
+    fn version_1_saves_are_read() {
+        let mut game = game();
+        let solution = game.solution();
+        game.place(0, solution[0]).unwrap();
+
+        let givens = game.givens();
+        let mut writer = BitWriter::default();
+        writer.write(1, 8);
+        givens.iter().for_each(|&given| writer.write(u32::from(given > 0), 1));
+        writer.write_digits(givens.iter().copied().filter(|&given| given > 0));
+        writer.write_digits((0 .. 81).filter(|&index| givens[index] == 0).map(|index| solution[index]));
+        writer.write(0, 16);
+        writer.write(0, 32);
+        writer.write(0, 16);
+
+        // The board, with an entry in the first cell and no marks
+        (0 .. 81).for_each(|index| writer.write(u32::from(index == 0), 1));
+        writer.write_digits(std::iter::once(solution[0]));
+        (0 .. 81).for_each(|_| writer.write(0, 1));
+
+        // A single undo step emptying the first cell again, and nothing to redo
+        writer.write(1, 16);
+        writer.write(1, 7);
+        writer.write(0, 7);
+        writer.write(0, 4);
+        writer.write(0, 9);
+        writer.write(0, 16);
+
+        assert_eq!(Game::from_bytes(&writer.bytes), Ok(game));
+    }
+
+    #[test]
+    fn saves_with_trailing_bytes_are_rejected() {
+        let mut bytes = game().to_bytes();
+        assert!(Game::from_bytes(&bytes).is_ok());
+
+        bytes.push(0);
+        assert_eq!(Game::from_bytes(&bytes).err(), Some(CodecError::TrailingBytes));
+    }
+}
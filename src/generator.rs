@@ -1,7 +1,10 @@
 use crate::{
     sudoku  :: { Sudoku, traits  :: SudokuState },
     solver  :: { Solver },
-    random  :: { traits :: Random }
+    random  :: { traits :: Random },
+    layout  :: { Layout, LayoutError },
+    puzzle  :: { Puzzle },
+    cages,
 };
 
 use self :: { output::* };
@@ -9,14 +12,17 @@ use self :: { output::* };
 use std::collections::HashSet;
 
 lazy_static! {
-    static ref INDICES: HashSet<usize>  = (0..81).collect();
     static ref DIGITS:  HashSet<u8>     = (1..10).collect();
 }
 
+// Solver iterations per cell spent filling a layout, across all seeded arrangements, before it's taken to have no
+// solution. The most constrained layouts supported (ex: non-consecutive 9x9 grids) can need around 100 000 of them.
+const FILL_ITERATIONS: usize = 1 << 18;
+
 pub struct Generator {
-    samples:            u8,
-    sample_iterations:  u8,
-    iteration_removals: u8
+    pub(crate) samples:             u8,
+    pub(crate) sample_iterations:   u8,
+    pub(crate) iteration_removals:  u8
 }
 
 impl Default for Generator {
@@ -65,14 +71,116 @@ impl Generator {
     }
 
     pub fn generate(&self) -> GeneratorOutput {
-        let solver = Solver::with_limit(2);
         let solve_output = Solver::with_limit(1).solve(&prefill_solution());
-        let starting_state = Sudoku::new(&solve_output.result[0].solution);
-        let mut random_index = INDICES.iter().random();
+        let solution = solve_output.result[0].solution;
+        let (puzzle, branches) = self.reduce(&Sudoku::new(&solution), 0);
+
+        GeneratorOutput {
+            difficulty: branches * 100 + puzzle.remaining(),
+            solution,
+            puzzle:     puzzle.into(),
+        }
+    }
+
+    // Generates puzzles of any layout, with the same settings as generate. Fails with LayoutError::Unsolvable if no
+    // solution of the layout could be found, as for jigsaw regions or constraints that can't all be satisfied.
+    pub fn generate_puzzle(&self, layout: &Layout) -> Result<PuzzleGeneratorOutput, LayoutError> {
+        if layout.is_standard() {
+            return Ok(self.generate().into());
+        }
+
+        // Proving a sparse puzzle of a large layout has a unique solution can take the solver a very long time, so
+        // removals it can't settle within a number of iterations are given up on, as if they had made the puzzle
+        // ambiguous.
+        let solution = fill(layout)?;
+        let (puzzle, branches) = self.reduce(&solution, layout.cells() * 2);
+
+        Ok(PuzzleGeneratorOutput {
+            difficulty: branches * 100 + puzzle.remaining(),
+            solution:   Puzzle::from(&solution),
+            puzzle:     Puzzle::from(&puzzle),
+        })
+    }
+
+    // Generates Killer Sudoku puzzles on top of any layout, replacing whatever cages it had with random ones. Without
+    // givens, cages are split up until they are enough to pin down a single solution: cages of cells where a second
+    // solution differs from the first one are split first, then the largest cages if the solver can't settle it within
+    // a number of iterations. With givens, the cages are kept as they are and values are removed like in
+    // generate_puzzle. Fails like generate_puzzle when no solution of the layout could be found.
+    pub fn generate_killer(&self, layout: &Layout, givens: bool) -> Result<PuzzleGeneratorOutput, LayoutError> {
+        let plain = layout.with_cages(&[]).unwrap();
+        let solution = fill(&plain)?;
+        let values: Vec<u8> = solution.iter().copied().collect();
+        let mut random_cages = cages::random(&plain, &values);
+
+        let limit = layout.cells() * 16;
+        let solver = Solver::with_limit(2);
+
+        // Cages are summed from the solution, so they always fit the layout
+        let caged = |cages: &[cages::Cage]| plain.with_cages(cages).unwrap();
+
+        if givens {
+            let solution = Sudoku::build(caged(&random_cages), &values);
+            let (puzzle, branches) = self.reduce(&solution, limit);
+
+            return Ok(PuzzleGeneratorOutput {
+                difficulty: branches * 100 + puzzle.remaining(),
+                solution:   Puzzle::from(&solution),
+                puzzle:     Puzzle::from(&puzzle),
+            });
+        }
+
+        loop {
+            let killer = caged(&random_cages);
+            let empty = Sudoku::build(killer.clone(), &vec![0; layout.cells()]);
+
+            let cell = match solver.search_within(&empty, limit) {
+                Some(search) if search.found.len() == 1 => {
+                    return Ok(PuzzleGeneratorOutput {
+                        difficulty: search.found[0].2 * 100 + empty.remaining(),
+                        solution:   Puzzle::from(&Sudoku::build(killer, &values)),
+                        puzzle:     Puzzle::from(&empty),
+                    });
+                },
+                Some(search) if search.found.len() > 1 => {
+                    let (first, second) = (&search.found[0].0, &search.found[1].0);
+                    let differing: Vec<usize> = (0 .. layout.cells())
+                        .filter(|&cell| first.get(cell) != second.get(cell))
+                        .collect();
+
+                    *differing.iter().random().next().unwrap()
+                },
+                // Either no solution was reached in time, or the search ran out of iterations before it could find a
+                // second one
+                _ => {
+                    let largest = random_cages.iter().max_by_key(|cage| cage.cells.len()).unwrap();
+                    *largest.cells.iter().random().next().unwrap()
+                }
+            };
+
+            cages::split(&plain, &mut random_cages, &values, cell);
+        }
+    }
+
+    // Removes values from a solution for as long as the puzzle stays uniquely solvable, returning the puzzle along with
+    // the branches its solver had to take. Checks running past the iteration limit count as ambiguous.
+    fn reduce(&self, starting_state: &Sudoku, limit: usize) -> (Sudoku, usize) {
+        let solver = Solver::with_limit(2);
+        let indices: Vec<usize> = (0 .. starting_state.layout().cells()).collect();
+        let mut random_index = indices.iter().random();
+
+        // The settings are tuned for 9x9 grids, where removing more than 64 values is pointless. Other layouts scale
+        // that cap to their number of cells, which also keeps a sample from running out of cells to remove.
+        let cap = std::cmp::max(indices.len() * 64 / 81, 1);
+        let sample_iterations = std::cmp::min(self.sample_iterations as usize, cap);
+        let iteration_removals = std::cmp::min(
+            self.iteration_removals as usize,
+            std::cmp::max(cap / std::cmp::max(sample_iterations, 1), 1)
+        );
 
         let mut best_state = starting_state.clone();
         let mut best_difficulty = 0;
-        let mut best_solve_output = solve_output;
+        let mut best_branches = 0;
     
         // Somewhat counter intuitively, rather than continually iterating on the best puzzle found so far, starting
         // each sample directly from the solution often leads to better results. After a certain point the amount of
@@ -82,30 +190,33 @@ impl Generator {
         for _ in 0 .. self.samples {
             let mut sample_state = starting_state.clone();
             let mut sample_difficulty = 0;
+            let mut sample_branches = 0;
 
-            for _ in 0 .. self.sample_iterations {
+            for _ in 0 .. sample_iterations {
                 let mut state = sample_state.set(*random_index.next().unwrap(), 0);
     
-                for _ in 0 .. self.iteration_removals - 1 {
+                for _ in 0 .. iteration_removals - 1 {
                     state = state.set(*random_index.next().unwrap(), 0);
                 }
     
-                let output = solver.solve_state(&state);
-                
-                if output.result.len() > 1 {
-                    continue;
-                }
+                let search = match solver.search_within(&state, limit) {
+                    Some(search) if search.found.len() == 1 => search,
+                    _ => continue
+                };
         
-                let difficulty = output.result[0].branches * 100 + state.remaining();
+                let branches = search.found[0].2;
+                let difficulty = branches * 100 + state.remaining();
                 if sample_difficulty < difficulty {
                     sample_state        = state;
-                    sample_difficulty   = difficulty
+                    sample_difficulty   = difficulty;
+                    sample_branches     = branches;
                 }
             }
     
             if best_difficulty < sample_difficulty  {
                 best_state      = sample_state;
                 best_difficulty = sample_difficulty;
+                best_branches   = sample_branches;
             }
 
             random_index.reset();
@@ -118,20 +229,140 @@ impl Generator {
             }
     
             let new_state = best_state.set(index, 0);
-            let output = solver.solve_state(&new_state);
-            
-            if output.result.len() == 1 {
-                best_state = new_state;
-                best_solve_output = output;
+            if let Some(search) = solver.search_within(&new_state, limit) {
+                if search.found.len() == 1 {
+                    best_state = new_state;
+                    best_branches = search.found[0].2;
+                }
             }
         }
-    
-        GeneratorOutput {
-            difficulty: best_solve_output.result[0].branches * 100 + best_state.remaining(),
-            solution:   best_solve_output.result[0].solution,
-            puzzle:     best_state.into(),
+
+        (best_state, best_branches)
+    }
+}
+
+// A random solution for any layout. Solving an empty grid is quick but always gives the same solution, so it's
+// shuffled afterwards: digits are relabeled, rows are moved around within their band and bands among themselves, and
+// likewise for columns and stacks. None of these can break a solution.
+pub(crate) fn fill(layout: &Layout) -> Result<Sudoku, LayoutError> {
+    let shapes = layout.has_diagonals() || layout.has_windows() || layout.is_jigsaw() || layout.is_overlapping();
+    if shapes || layout.has_cages() || layout.has_constraints() {
+        return fill_seeded(layout);
+    }
+
+    let size = layout.size();
+    let mut search = Solver::with_limit(1).search(&Sudoku::build(layout.clone(), &vec![0; layout.cells()]));
+    let (solution, _, _) = search.found.pop().unwrap();
+    let solution: Vec<u8> = solution.iter().copied().collect();
+
+    let digits: Vec<u8> = (1 ..= size as u8).collect();
+    let labels: Vec<u8> = digits.iter().random().copied().collect();
+    let rows = shuffled_lines(size, layout.box_height());
+    let cols = shuffled_lines(size, layout.box_width());
+
+    let mut cells = vec![0; layout.cells()];
+    for (i, cell) in cells.iter_mut().enumerate() {
+        let value = solution[rows[i / size] * size + cols[i % size]];
+        *cell = labels[usize::from(value) - 1];
+    }
+
+    Ok(Sudoku::build(layout.clone(), &cells))
+}
+
+// Moving rows and columns around would take cells off the diagonals, out of their window, jigsaw region or cage, or
+// apart from the grids they're shared with, and could break other constraints, so these layouts are instead solved
+// from random digits along the first row. Arrangements the solver can't complete quickly are passed over, with the
+// solver given more iterations as they keep failing, until FILL_ITERATIONS per cell have been spent. Layouts the
+// solver can tell have no solution at all (ex: jigsaw regions that can't be filled in) are given up on right away, and
+// the solution it finds while checking is kept, relabeled, for layouts none of the arrangements can be completed for.
+fn fill_seeded(layout: &Layout) -> Result<Sudoku, LayoutError> {
+    fill_within(layout, layout.cells() * FILL_ITERATIONS)
+}
+
+// Same as fill_seeded, with the number of solver iterations to spend. It must be above 0.
+fn fill_within(layout: &Layout, mut iterations: usize) -> Result<Sudoku, LayoutError> {
+    let limit = layout.cells() * 16;
+    let empty = Sudoku::build(layout.clone(), &vec![0; layout.cells()]);
+
+    let budget = std::cmp::min(limit * 16, iterations);
+    let fallback = match Solver::with_limit(1).search_within(&empty, budget) {
+        Some(mut search) => match search.found.pop() {
+            Some((solution, _, _)) => {
+                iterations -= search.iterations;
+                Some(solution)
+            },
+            None => return Err(LayoutError::Unsolvable)
+        },
+        None => {
+            iterations -= budget;
+            None
+        }
+    };
+
+    let mut attempt = 0;
+    while iterations > 0 {
+        let mut state = empty.clone();
+
+        // Digits are picked from each cell's candidates, so the seeds can't break any cage or constraint. Cells left
+        // without candidates are left empty, for the solver to give up on. Overlapping layouts get a seeded row in
+        // each grid, since the solver would otherwise fill the others the same way every time.
+        let seeds: Vec<usize> = (0 .. layout.grids().len())
+            .flat_map(|grid| (0 .. layout.size()).map(move |col| layout.grid_cell(grid, 0, col)))
+            .collect();
+
+        for cell in seeds {
+            let candidates = state.candidates(cell);
+            if !candidates.is_empty() {
+                state = state.set(cell, *candidates.iter().random().next().unwrap());
+            }
+        }
+
+        // A search that fails takes at least one iteration, so this always comes to an end
+        let budget = std::cmp::min(limit << std::cmp::min(attempt / 64, 8), iterations);
+        match Solver::with_limit(1).search_within(&state, budget) {
+            Some(mut search) => match search.found.pop() {
+                Some((solution, _, _)) => return Ok(solution),
+                None => iterations -= search.iterations
+            },
+            None => iterations -= budget
+        }
+        attempt += 1;
+    }
+
+    fallback.map(|solution| relabeled(layout, &solution)).ok_or(LayoutError::Unsolvable)
+}
+
+// A solution with its digits swapped around at random, which keeps every house and cage free of repeats. Cage sums and
+// constraints may not hold for every relabeling though, so a few are tried before settling for the solution as it is.
+fn relabeled(layout: &Layout, solution: &Sudoku) -> Sudoku {
+    let digits: Vec<u8> = (1 ..= layout.size() as u8).collect();
+
+    for _ in 0 .. 16 {
+        let labels: Vec<u8> = digits.iter().random().copied().collect();
+        let cells: Vec<u8> = solution.iter().map(|&value| labels[usize::from(value) - 1]).collect();
+        let state = Sudoku::build(layout.clone(), &cells);
+
+        let sums = layout.cages().iter()
+            .all(|cage| cage.cells.iter().map(|&cell| usize::from(cells[cell])).sum::<usize>() == cage.sum);
+        let constraints = layout.constraints().iter()
+            .all(|constraint| (0 .. cells.len()).all(|i| constraint.allows(&state, i, cells[i])));
+
+        if sums && constraints {
+            return state;
         }
     }
+
+    solution.clone()
+}
+
+// A random order of the lines along one side of a grid, keeping each group of lines (bands or stacks) together
+fn shuffled_lines(size: usize, group: usize) -> Vec<usize> {
+    let groups: Vec<usize> = (0 .. size / group).collect();
+    let lines: Vec<usize> = (0 .. group).collect();
+
+    groups.iter().random()
+        .flat_map(|&g| lines.iter().random().map(move |&l| g * group + l))
+        .collect()
 }
 
 fn prefill_solution() -> [u8; 81] {
@@ -150,14 +381,14 @@ fn prefill_solution() -> [u8; 81] {
     output
 }
 
-fn fill_box_one(grid: &mut Vec<Vec<u8>>) {
+fn fill_box_one(grid: &mut [Vec<u8>]) {
     let mut choices = DIGITS.iter().random();
     for row in grid.iter_mut().take(3) {
         (0..3).for_each(|_| row.push(*choices.next().unwrap()))
     }
 }
 
-fn fill_box_two(grid: &mut Vec<Vec<u8>>) {
+fn fill_box_two(grid: &mut [Vec<u8>]) {
     let box1_row1: HashSet<u8> = grid[0].iter().take(3).copied().collect();
     let box1_row2: HashSet<u8> = grid[1].iter().take(3).copied().collect();
     let box1_row3: HashSet<u8> = grid[2].iter().take(3).copied().collect();
@@ -196,7 +427,7 @@ fn fill_box_two(grid: &mut Vec<Vec<u8>>) {
     }
 }
 
-fn fill_box_three(grid: &mut Vec<Vec<u8>>) {
+fn fill_box_three(grid: &mut [Vec<u8>]) {
     for row in grid.iter_mut().take(3) {
         row.extend(
             DIGITS.difference(&row.iter().take(6).copied().collect()).random()
@@ -205,7 +436,7 @@ fn fill_box_three(grid: &mut Vec<Vec<u8>>) {
 }
 
 // Sets first row value to a random number and fills the rest of the row with 0s
-fn fill_remaining(grid: &mut Vec<Vec<u8>>) {
+fn fill_remaining(grid: &mut [Vec<u8>]) {
     let box1_col1 = grid.iter().take(3).map(|r| r[0]).collect();
     let mut choices = DIGITS.difference(&box1_col1).random();
 
@@ -217,16 +448,50 @@ fn fill_remaining(grid: &mut Vec<Vec<u8>>) {
 
 pub mod output {
     use std::fmt::Write;
-    use crate :: format  :: { BoxFormat, Alignment };
+    use crate :: { format :: { BoxFormat, Alignment, Grid }, puzzle :: Puzzle };
 
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct GeneratorOutput {
+        #[cfg_attr(feature = "serde", serde(with = "crate::serialize::grid"))]
         pub puzzle: [u8; 81],
+        #[cfg_attr(feature = "serde", serde(with = "crate::serialize::grid"))]
         pub solution: [u8; 81],
         pub difficulty: usize,
     }
 
+    // A generated puzzle of any layout
+    #[derive(Debug)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct PuzzleGeneratorOutput {
+        pub puzzle: Puzzle,
+        pub solution: Puzzle,
+        pub difficulty: usize,
+    }
+
+    impl GeneratorOutput {
+        // The puzzle and its solution side by side
+        pub fn grid(&self) -> Grid<'_> {
+            let mut grid = Grid::new(&self.puzzle);
+            grid.solution(&self.solution);
+            grid
+        }
+    }
+
+    // The alternate flag ({:#} and {:#?}) draws the puzzle, and solution, as 9x9 grids rather than lines of digits.
     impl std::fmt::Display for GeneratorOutput {
         fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            if formatter.alternate() {
+                let puzzle = Grid::new(&self.puzzle).to_string();
+
+                BoxFormat::new(formatter)
+                    .header("Waffle-Iron", None)?
+                    .line_break()?
+                    .block(&puzzle, Alignment::Center.into())?
+                    .footer("", None)?;
+
+                return Ok(());
+            }
+
             let mut puzzle = String::new(); 
             for val in self.puzzle.iter() {
                 write!(&mut puzzle, "{}", val)?;
@@ -256,6 +521,21 @@ pub mod output {
 
             let summary = format!("Difficulty: {}", self.difficulty);
 
+            if formatter.alternate() {
+                let grids = self.grid().to_string();
+
+                BoxFormat::new(formatter)
+                    .header("Waffle-Iron", None)?
+                    .empty_line()?
+                    .block(&grids, Alignment::Center.into())?
+                    .empty_line()?
+                    .section("Summary", None)?
+                    .content(&summary, Alignment::Center.into())?
+                    .footer("", None)?;
+
+                return Ok(());
+            }
+
             BoxFormat::new(formatter)
                 .header("Waffle-Iron", None)?
                 .empty_line()?
@@ -272,4 +552,182 @@ pub mod output {
             Ok(())
         }
     }
-}
\ No newline at end of file
+
+    impl PuzzleGeneratorOutput {
+        // The puzzle and its solution side by side
+        pub fn grid(&self) -> Grid<'_> {
+            let mut grid = self.puzzle.grid();
+            grid.solution(self.solution.cells());
+            grid
+        }
+    }
+
+    impl From<GeneratorOutput> for PuzzleGeneratorOutput {
+        fn from(output: GeneratorOutput) -> Self {
+            Self {
+                puzzle:     output.puzzle.into(),
+                solution:   output.solution.into(),
+                difficulty: output.difficulty,
+            }
+        }
+    }
+
+    // The alternate flag ({:#}) draws the puzzle as a grid rather than a line of digits and letters.
+    impl std::fmt::Display for PuzzleGeneratorOutput {
+        fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            let (puzzle, align) = match formatter.alternate() {
+                true  => (format!("{:#}", self.puzzle), Alignment::Center.into()),
+                false => (format!("{}", self.puzzle), None)
+            };
+
+            BoxFormat::new(formatter)
+                .header("Waffle-Iron", None)?
+                .line_break()?
+                .block(&puzzle, align)?
+                .footer("", None)?;
+
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraints::{ AntiKing, NonConsecutive, Sandwich };
+
+    fn assert_unique(output: &PuzzleGeneratorOutput) {
+        let solved = Solver::with_limit(2).solve_puzzle(&output.puzzle);
+
+        assert_eq!(solved.result.len(), 1);
+        assert_eq!(solved.result[0].solution.cells(), output.solution.cells());
+    }
+
+    #[test]
+    fn unsolvable_jigsaw_regions_are_reported() {
+        let layout = Layout::jigsaw(&[1, 1, 1, 2, 1, 3, 2, 2, 3, 3, 4, 2, 3, 4, 4, 4]).unwrap();
+
+        assert_eq!(fill(&layout).err(), Some(LayoutError::Unsolvable));
+        assert_eq!(Generator::new().generate_puzzle(&layout).err(), Some(LayoutError::Unsolvable));
+        assert_eq!(Generator::new().generate_killer(&layout, false).err(), Some(LayoutError::Unsolvable));
+    }
+
+    #[test]
+    fn unsolvable_constraints_are_reported() {
+        // The four digits of a 2x2 box can't all sit next to digits they don't follow
+        let layout = Layout::new(2, 2).unwrap().with_constraint(NonConsecutive);
+
+        assert_eq!(fill(&layout).err(), Some(LayoutError::Unsolvable));
+        assert_eq!(Generator::new().generate_puzzle(&layout).err(), Some(LayoutError::Unsolvable));
+    }
+
+    #[test]
+    fn fills_give_up_once_their_iterations_are_spent() {
+        let layout = Layout::new(3, 3).unwrap().with_constraint(NonConsecutive);
+
+        assert_eq!(fill_within(&layout, 100).err(), Some(LayoutError::Unsolvable));
+    }
+
+    #[test]
+    fn solutions_kept_from_the_first_search_are_relabeled() {
+        let layout = Layout::with_diagonals(2, 2).unwrap();
+        let empty = Sudoku::build(layout.clone(), &vec![0; layout.cells()]);
+        let (solution, _, _) = Solver::with_limit(1).search(&empty).found.pop().unwrap();
+
+        let mut seen = HashSet::new();
+        for _ in 0 .. 20 {
+            let relabeled = relabeled(&layout, &solution);
+
+            for (set_type, set_index) in layout.houses() {
+                let mut values: Vec<u8> = layout.house(set_type, set_index).iter()
+                    .map(|&cell| relabeled.get(cell).unwrap())
+                    .collect();
+                values.sort_unstable();
+                assert_eq!(values, [1, 2, 3, 4]);
+            }
+            seen.insert(relabeled.iter().copied().collect::<Vec<u8>>());
+        }
+
+        assert!(seen.len() > 1);
+    }
+
+    #[test]
+    fn jigsaw_regions_the_solver_struggles_with_are_filled() {
+        // Most arrangements of the first row can't be completed within the solver's first few iterations here
+        let layout = Layout::jigsaw(&[
+            3, 3, 3, 4, 4, 4,
+            3, 3, 1, 4, 4, 4,
+            3, 1, 1, 1, 2, 2,
+            5, 5, 1, 2, 2, 2,
+            5, 5, 1, 6, 2, 6,
+            5, 5, 6, 6, 6, 6,
+        ]).unwrap();
+
+        for _ in 0 .. 5 {
+            let solution = fill(&layout).unwrap();
+
+            for (set_type, set_index) in layout.houses() {
+                let mut values: Vec<u8> = layout.house(set_type, set_index).iter()
+                    .map(|&cell| solution.get(cell).unwrap())
+                    .collect();
+                values.sort_unstable();
+                assert_eq!(values, [1, 2, 3, 4, 5, 6]);
+            }
+        }
+    }
+
+    #[test]
+    fn puzzles_of_any_box_shape_have_a_unique_solution() {
+        for (width, height) in [(2, 2), (3, 2), (2, 3)] {
+            let layout = Layout::new(width, height).unwrap();
+            let output = Generator::new().generate_puzzle(&layout).unwrap();
+
+            assert_eq!(output.solution.layout(), &layout);
+            assert!(output.solution.cells().iter().all(|&value| (1 ..= layout.size() as u8).contains(&value)));
+            assert_unique(&output);
+        }
+    }
+
+    #[test]
+    fn variant_puzzles_have_a_unique_solution() {
+        let layouts = [
+            Layout::with_diagonals(2, 2).unwrap(),
+            Layout::new(2, 2).unwrap().with_windows().unwrap(),
+            Layout::random_jigsaw(6).unwrap(),
+            Layout::overlapping(2, 2, &[(0, 0), (2, 2)]).unwrap(),
+            Layout::new(3, 2).unwrap().with_constraint(AntiKing),
+        ];
+
+        for layout in &layouts {
+            let output = Generator::new().generate_puzzle(layout).unwrap();
+
+            assert_eq!(output.puzzle.layout(), layout);
+            assert_unique(&output);
+        }
+    }
+
+    #[test]
+    fn killer_puzzles_have_a_unique_solution() {
+        let layout = Layout::new(3, 2).unwrap();
+
+        for givens in [false, true] {
+            let output = Generator::new().generate_killer(&layout, givens).unwrap();
+
+            assert!(output.puzzle.layout().has_cages());
+            assert_unique(&output);
+        }
+    }
+
+    #[test]
+    fn sandwich_clues_without_a_solution_are_reported() {
+        let plain = Layout::new(2, 2).unwrap();
+        let layout = plain
+            .with_constraint(Sandwich::row(&plain, 0, 0).unwrap())
+            .with_constraint(Sandwich::row(&plain, 0, 5).unwrap());
+
+        assert_eq!(Generator::new().generate_puzzle(&layout).err(), Some(LayoutError::Unsolvable));
+
+        let layout = plain.with_constraint(Sandwich::column(&plain, 1, 5).unwrap());
+        assert_unique(&Generator::new().generate_puzzle(&layout).unwrap());
+    }
+}
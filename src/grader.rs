@@ -0,0 +1,477 @@
+use crate :: {
+    sudoku      :: { Sudoku, traits :: SudokuState },
+    candidates  :: { CandidateSet, traits :: CandidatesRead },
+    bitsets     :: traits :: BitSet,
+    solver      :: { Solver, output :: SolverOutput },
+    indices     :: { row_at, col_at, box_at, row_index, col_index, box_index },
+    puzzle      :: Puzzle,
+};
+
+use std :: { fmt, collections :: BTreeMap };
+
+// The deductions a person would use to work through a puzzle, ordered from easiest to hardest. Guess is used whenever
+// none of the others make any progress, and stands in for any technique this grader doesn't know about.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Technique {
+    NakedSingle,
+    HiddenSingle,
+    LockedCandidates,
+    NakedPair,
+    Guess,
+}
+
+impl Technique {
+    pub fn level(self) -> Level {
+        match self {
+            Technique::NakedSingle | Technique::HiddenSingle => Level::Easy,
+            Technique::LockedCandidates => Level::Medium,
+            Technique::NakedPair        => Level::Hard,
+            Technique::Guess            => Level::Expert,
+        }
+    }
+}
+
+impl fmt::Display for Technique {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.pad(match self {
+            Technique::NakedSingle      => "Naked Single",
+            Technique::HiddenSingle     => "Hidden Single",
+            Technique::LockedCandidates => "Locked Candidates",
+            Technique::NakedPair        => "Naked Pair",
+            Technique::Guess            => "Guess",
+        })
+    }
+}
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Level {
+    Easy,
+    Medium,
+    Hard,
+    Expert,
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.pad(match self {
+            Level::Easy     => "Easy",
+            Level::Medium   => "Medium",
+            Level::Hard     => "Hard",
+            Level::Expert   => "Expert",
+        })
+    }
+}
+
+// Difficulty uses the same score as the generator (solver branches * 100 + empty cells), while the level and
+// techniques describe what it takes to solve the puzzle by hand.
+#[derive(PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Grade {
+    pub difficulty: usize,
+    pub level:      Level,
+    pub techniques: BTreeMap<Technique, usize>,
+}
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Hint {
+    pub index:      usize,
+    pub value:      u8,
+    pub technique:  Technique,
+}
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ValidationError {
+    // Two givens sharing a row, column, box, or cage, as the (row, column) of each on the board, numbered from 0
+    Conflict((usize, usize), (usize, usize)),
+    // Index of a Killer Sudoku cage whose cells are all given, but don't add up to its sum
+    CageSum(usize),
+    // Index of a given that breaks one of the layout's constraints
+    Constraint(usize),
+    Unsolvable,
+    MultipleSolutions,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::Conflict((a_row, a_col), (b_row, b_col)) => write!(
+                formatter, "cells r{}c{} and r{}c{} hold the same value", a_row + 1, a_col + 1, b_row + 1, b_col + 1
+            ),
+            ValidationError::CageSum(index) =>
+                write!(formatter, "the givens of cage {} don't add up to its sum", index),
+            ValidationError::Constraint(index) =>
+                write!(formatter, "the given at cell {} breaks a constraint", index),
+            ValidationError::Unsolvable =>
+                write!(formatter, "puzzle has no solution"),
+            ValidationError::MultipleSolutions =>
+                write!(formatter, "puzzle has more than one solution"),
+        }
+    }
+}
+
+impl std::error::Error for ValidationError { }
+
+// Checks that a puzzle is well formed: none of its givens conflict, and it has exactly one solution, which is returned.
+pub fn validate(puzzle: &[u8; 81]) -> Result<[u8; 81], ValidationError> {
+    check(puzzle).map(|output| output.result[0].solution)
+}
+
+// Same as validate, for puzzles of any layout. Givens are checked against every house of the layout, including its
+// diagonals and windows, and against its cages and constraints.
+pub fn validate_puzzle(puzzle: &Puzzle) -> Result<Puzzle, ValidationError> {
+    let layout = puzzle.layout();
+    let cells = puzzle.cells();
+
+    // Digits can't repeat within a cage either
+    let houses = layout.houses().map(|(set_type, set_index)| layout.house(set_type, set_index));
+    let cages = layout.cages().iter().map(|cage| &cage.cells[..]);
+
+    for house in houses.chain(cages) {
+        let mut seen = vec![None; layout.size() + 1];
+
+        for &index in house {
+            let value = usize::from(cells[index]);
+            if value == 0 {
+                continue;
+            }
+
+            if let Some(first) = seen[value] {
+                return Err(ValidationError::Conflict(layout.position(first), layout.position(index)));
+            }
+            seen[value] = Some(index);
+        }
+    }
+
+    // The solver only checks the sums of cages it fills in itself
+    for (index, cage) in layout.cages().iter().enumerate() {
+        let values: Vec<usize> = cage.cells.iter().map(|&cell| usize::from(cells[cell])).collect();
+        if values.iter().all(|&value| value > 0) && values.iter().sum::<usize>() != cage.sum {
+            return Err(ValidationError::CageSum(index));
+        }
+    }
+
+    let state = Sudoku::from(puzzle);
+    for constraint in layout.constraints() {
+        if let Some(index) = (0 .. cells.len()).find(|&i| cells[i] > 0 && !constraint.allows(&state, i, cells[i])) {
+            return Err(ValidationError::Constraint(index));
+        }
+    }
+
+    let mut output = Solver::with_limit(2).solve_puzzle(puzzle);
+    match output.result.len() {
+        0 => Err(ValidationError::Unsolvable),
+        1 => Ok(output.result.remove(0).solution),
+        _ => Err(ValidationError::MultipleSolutions)
+    }
+}
+
+pub fn grade(puzzle: &[u8; 81]) -> Result<Grade, ValidationError> {
+    let output = check(puzzle)?;
+    let solution = &output.result[0].solution;
+
+    let mut state = Sudoku::new(puzzle);
+    let mut techniques = BTreeMap::new();
+
+    while state.remaining() > 0 {
+        let (next, technique) = match next_step(&state) {
+            Some(step) => (step.apply(&state), step.technique()),
+            None => {
+                let index = fewest_candidates(&state);
+                (state.set(index, solution[index]), Technique::Guess)
+            }
+        };
+
+        *techniques.entry(technique).or_insert(0) += 1;
+        state = next;
+    }
+
+    Ok(Grade {
+        difficulty: output.result[0].branches * 100 + output.steps,
+        level:      techniques.keys().max().map_or(Level::Easy, |technique| technique.level()),
+        techniques
+    })
+}
+
+// Finds the next value that can be placed in the given state, along with the hardest technique needed to find it.
+// Returns None when the state can't be solved, for example because of an incorrect value. Techniques are only looked
+// for in 9x9 grids, so states of any other layout get None as well.
+pub fn hint(state: &Sudoku) -> Option<Hint> {
+    if !state.layout().is_standard() {
+        return None;
+    }
+
+    let mut scratch = state.clone();
+    let mut hardest = Technique::NakedSingle;
+
+    while let Some(step) = next_step(&scratch) {
+        hardest = std::cmp::max(hardest, step.technique());
+
+        if let Step::Place(index, value, _) = step {
+            return Some(Hint { index, value, technique: hardest });
+        }
+
+        scratch = step.apply(&scratch);
+    }
+
+    if scratch.remaining() == 0 {
+        return None;
+    }
+
+    let output = Solver::with_limit(1).solve_state(&scratch);
+    let solution = &output.result.first()?.solution;
+    let index = fewest_candidates(&scratch);
+
+    Some(Hint { index, value: solution.cells()[index], technique: Technique::Guess })
+}
+
+fn check(puzzle: &[u8; 81]) -> Result<SolverOutput, ValidationError> {
+    for unit in units() {
+        let mut seen = [None; 10];
+
+        for &index in unit.iter() {
+            let value = puzzle[index] as usize;
+            if value == 0 || value > 9 {
+                continue;
+            }
+
+            if let Some(first) = seen[value] {
+                return Err(ValidationError::Conflict((first / 9, first % 9), (index / 9, index % 9)));
+            }
+            seen[value] = Some(index);
+        }
+    }
+
+    let output = Solver::with_limit(2).solve(puzzle);
+    match output.result.len() {
+        0 => Err(ValidationError::Unsolvable),
+        1 => Ok(output),
+        _ => Err(ValidationError::MultipleSolutions)
+    }
+}
+
+enum Step {
+    Place(usize, u8, Technique),
+    Eliminate(Vec<(usize, u8)>, Technique),
+}
+
+impl Step {
+    fn technique(&self) -> Technique {
+        match self {
+            Step::Place(_, _, technique) | Step::Eliminate(_, technique) => *technique
+        }
+    }
+
+    fn apply(&self, state: &Sudoku) -> Sudoku {
+        match self {
+            Step::Place(index, value, _) => state.set(*index, *value),
+            Step::Eliminate(eliminations, _) => eliminations.iter().fold(
+                state.clone(), |state, &(index, value)| state.eliminate(index, value)
+            )
+        }
+    }
+}
+
+// Techniques are always tried from easiest to hardest, so that the grade reflects the simplest way through the puzzle.
+fn next_step(state: &Sudoku) -> Option<Step> {
+    naked_single(state)
+        .or_else(|| hidden_single(state))
+        .or_else(|| locked_candidates(state))
+        .or_else(|| naked_pair(state))
+}
+
+fn naked_single(state: &Sudoku) -> Option<Step> {
+    (0..81).find_map(|index| {
+        let set = candidates(state, index);
+        match set.len() {
+            1 => Some(Step::Place(index, u8::from(set), Technique::NakedSingle)),
+            _ => None
+        }
+    })
+}
+
+fn hidden_single(state: &Sudoku) -> Option<Step> {
+    for unit in units() {
+        for value in 1..=9 {
+            let mut positions = positions(state, &unit, value);
+
+            if let (Some(index), None) = (positions.next(), positions.next()) {
+                return Some(Step::Place(index, value, Technique::HiddenSingle));
+            }
+        }
+    }
+
+    None
+}
+
+// When all of a box's candidates for a value fall on a single row or column, the value can be removed from the rest of
+// that line (pointing). Likewise, when a line's candidates all fall within one box, the value can be removed from the
+// rest of the box (claiming).
+fn locked_candidates(state: &Sudoku) -> Option<Step> {
+    for value in 1..=9 {
+        for b in 0..9 {
+            let cells: Vec<usize> = positions(state, &box_at(b), value).collect();
+            if cells.is_empty() {
+                continue;
+            }
+
+            if cells.iter().all(|&index| row_index(index) == row_index(cells[0])) {
+                let step = eliminate(state, &row_at(row_index(cells[0])), &cells, &[value], Technique::LockedCandidates);
+                if step.is_some() { return step; }
+            }
+
+            if cells.iter().all(|&index| col_index(index) == col_index(cells[0])) {
+                let step = eliminate(state, &col_at(col_index(cells[0])), &cells, &[value], Technique::LockedCandidates);
+                if step.is_some() { return step; }
+            }
+        }
+
+        for line in (0..9).map(row_at).chain((0..9).map(col_at)) {
+            let cells: Vec<usize> = positions(state, &line, value).collect();
+            if cells.is_empty() {
+                continue;
+            }
+
+            if cells.iter().all(|&index| box_index(index) == box_index(cells[0])) {
+                let step = eliminate(state, &box_at(box_index(cells[0])), &cells, &[value], Technique::LockedCandidates);
+                if step.is_some() { return step; }
+            }
+        }
+    }
+
+    None
+}
+
+fn naked_pair(state: &Sudoku) -> Option<Step> {
+    for unit in units() {
+        let pairs: Vec<usize> = unit.iter().copied().filter(|&index| candidates(state, index).len() == 2).collect();
+
+        for (i, &a) in pairs.iter().enumerate() {
+            for &b in pairs[i + 1..].iter() {
+                let set = candidates(state, a);
+                if set != candidates(state, b) {
+                    continue;
+                }
+
+                let values: Vec<u8> = set.iter().map(u8::from).collect();
+                let step = eliminate(state, &unit, &[a, b], &values, Technique::NakedPair);
+                if step.is_some() { return step; }
+            }
+        }
+    }
+
+    None
+}
+
+// Removes the values from every cell of the unit other than the excluded ones; None if there was nothing to remove.
+fn eliminate(state: &Sudoku, unit: &[usize; 9], excluded: &[usize], values: &[u8], technique: Technique)
+-> Option<Step> {
+    let mut eliminations = vec![];
+
+    for &index in unit.iter().filter(|index| !excluded.contains(index)) {
+        let set = candidates(state, index);
+        for &value in values.iter().filter(|&&value| set.contains(CandidateSet::from(value))) {
+            eliminations.push((index, value));
+        }
+    }
+
+    match eliminations.is_empty() {
+        true  => None,
+        false => Some(Step::Eliminate(eliminations, technique))
+    }
+}
+
+fn fewest_candidates(state: &Sudoku) -> usize {
+    (0..81)
+        .filter(|&index| SudokuState::get(state, index) == Some(0))
+        .min_by_key(|&index| candidates(state, index).len())
+        .unwrap_or(0)
+}
+
+#[inline]
+fn candidates(state: &Sudoku, index: usize) -> CandidateSet {
+    CandidatesRead::get(state, index).copied().unwrap_or_default()
+}
+
+fn positions<'a>(state: &'a Sudoku, unit: &'a [usize; 9], value: u8) -> impl Iterator<Item = usize> + 'a {
+    unit.iter().copied().filter(move |&index| candidates(state, index).contains(CandidateSet::from(value)))
+}
+
+fn units() -> impl Iterator<Item = [usize; 9]> {
+    (0..9).map(row_at).chain((0..9).map(col_at)).chain((0..9).map(box_at))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ layout::Layout, fixtures::puzzle };
+
+    #[test]
+    fn grades_count_the_techniques_used() {
+        let puzzle = puzzle();
+        let grade = grade(&puzzle).unwrap();
+
+        // Every empty cell is filled in by a single or a guess, while other techniques only rule out candidates
+        let empty = puzzle.iter().filter(|&&value| value == 0).count();
+        let placed = [Technique::NakedSingle, Technique::HiddenSingle, Technique::Guess].iter()
+            .filter_map(|technique| grade.techniques.get(technique))
+            .sum::<usize>();
+        assert_eq!(placed, empty);
+        assert_eq!(grade.level, grade.techniques.keys().max().unwrap().level());
+
+        // A single missing value is a naked single
+        let mut almost = validate(&puzzle).unwrap();
+        almost[40] = 0;
+        let grade = super::grade(&almost).unwrap();
+
+        assert_eq!(grade.level, Level::Easy);
+        assert_eq!(grade.techniques.get(&Technique::NakedSingle), Some(&1));
+    }
+
+    #[test]
+    fn invalid_puzzles_are_rejected() {
+        let mut conflict = puzzle();
+        conflict[1] = 2;
+        assert_eq!(validate(&conflict), Err(ValidationError::Conflict((0, 1), (0, 3))));
+
+        let mut unsolvable = [0; 81];
+        (0 .. 8).for_each(|index| unsolvable[index] = index as u8 + 1);
+        unsolvable[17] = 9;
+        assert_eq!(validate(&unsolvable), Err(ValidationError::Unsolvable));
+
+        assert_eq!(grade(&[0; 81]), Err(ValidationError::MultipleSolutions));
+    }
+
+    #[test]
+    fn hints_place_a_value_of_the_solution() {
+        let puzzle = puzzle();
+        let solution = validate(&puzzle).unwrap();
+
+        let hint = hint(&Sudoku::new(&puzzle)).unwrap();
+        assert_eq!(puzzle[hint.index], 0);
+        assert_eq!(hint.value, solution[hint.index]);
+
+        assert_eq!(super::hint(&Sudoku::new(&solution)), None);
+    }
+
+    #[test]
+    fn hint_ignores_other_layouts() {
+        let layout = Layout::new(2, 2).unwrap();
+        assert_eq!(hint(&Sudoku::from(&Puzzle::empty(&layout))), None);
+    }
+
+    #[test]
+    fn conflicts_are_reported_on_the_board() {
+        let layout = Layout::new(4, 4).unwrap();
+        let mut cells = vec![0; layout.cells()];
+        cells[16] = 5;
+        cells[17] = 5;
+
+        let error = validate_puzzle(&Puzzle::new(&layout, &cells).unwrap()).unwrap_err();
+        assert_eq!(error, ValidationError::Conflict((1, 0), (1, 1)));
+        assert_eq!(error.to_string(), "cells r2c1 and r2c2 hold the same value");
+    }
+}
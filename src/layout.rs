@@ -0,0 +1,775 @@
+use crate :: {
+    candidates  :: { CandidateSet, CandidateBits },
+    values      :: value_key :: SetType,
+    generator   :: fill,
+    cages       :: { Cage, combinations },
+    constraints :: Constraint,
+    regions,
+};
+
+use std :: { collections :: HashSet, fmt, sync :: Arc };
+
+lazy_static! {
+    static ref STANDARD: Layout = Layout::new(3, 3).unwrap();
+}
+
+// The shape of a puzzle: how many digits it uses, and the houses (rows, columns, boxes or jigsaw regions, and for some
+// variants diagonals or Hyper windows) that must each hold every digit exactly once. The solver only ever looks at a
+// puzzle through its layout, which is what lets it work with any size of grid. Every state of a puzzle shares the same
+// tables, so they're built once and kept behind an Arc; cloning a layout is cheap, and it can be shared between
+// threads.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Layout(Arc<Tables>);
+
+#[derive(Clone, PartialEq, Eq)]
+struct Tables {
+    size:           usize,
+    // Both 0 for jigsaw layouts, whose regions take the place of boxes
+    box_width:      usize,
+    box_height:     usize,
+    diagonals:      bool,
+    // Cells of each house, by set type and then set index
+    houses:         Vec<Vec<Vec<usize>>>,
+    // The houses each cell belongs to
+    memberships:    Vec<Vec<(SetType, usize)>>,
+    // Every cell sharing a house or a cage with each cell, including the cell itself
+    peers:          Vec<HashSet<usize>>,
+    // Killer Sudoku cages, and the cage of each cell. Cages aren't houses since they don't hold every digit.
+    cages:          Vec<Cage>,
+    caged:          Vec<Option<usize>>,
+    constraints:    Constraints,
+    // Groups of cells at the same position within their box, one set per grid, and the other cells sharing a group
+    // with each cell. Kept for the DisjointGroups constraint, and empty for jigsaw layouts.
+    disjoint:       Vec<Vec<usize>>,
+    disjoint_peers: Vec<Vec<usize>>,
+    board:          Board,
+}
+
+// Where cells sit: a layout is made up of one or more grids placed on a board, each given by the (row, column) of its
+// top left cell. Grids share the cells where they overlap, and cells are numbered in the board's row order, skipping
+// the positions no grid covers. Single grid layouts have a board of the same size, with cells numbered as usual.
+#[derive(Clone, PartialEq, Eq)]
+struct Board {
+    grids:      Vec<(usize, usize)>,
+    rows:       usize,
+    cols:       usize,
+    // The (row, column) of each cell, and the cell at each position of the board
+    positions:  Vec<(usize, usize)>,
+    cells:      Vec<Option<usize>>,
+}
+
+impl Board {
+    fn new(size: usize, grids: &[(usize, usize)]) -> Board {
+        let rows = grids.iter().map(|&(row, _)| row + size).max().unwrap_or(0);
+        let cols = grids.iter().map(|&(_, col)| col + size).max().unwrap_or(0);
+
+        let mut covered = vec![false; rows * cols];
+        for &(top, left) in grids.iter() {
+            for i in 0 .. size * size {
+                covered[(top + i / size) * cols + left + i % size] = true;
+            }
+        }
+
+        let positions: Vec<(usize, usize)> = (0 .. rows * cols)
+            .filter(|&i| covered[i])
+            .map(|i| (i / cols, i % cols))
+            .collect();
+
+        let mut cells = vec![None; rows * cols];
+        for (cell, &(row, col)) in positions.iter().enumerate() {
+            cells[row * cols + col] = Some(cell);
+        }
+
+        Board { grids: grids.to_vec(), rows, cols, positions, cells }
+    }
+
+    // The cell at a position within one of the grids
+    #[inline]
+    fn cell(&self, grid: usize, row: usize, col: usize) -> usize {
+        let (top, left) = self.grids[grid];
+        self.cells[(top + row) * self.cols + left + col].unwrap()
+    }
+}
+
+// Constraints are trait objects, so they're told apart by how they print
+#[derive(Clone, Default)]
+struct Constraints(Vec<Arc<dyn Constraint>>);
+
+impl PartialEq for Constraints {
+    fn eq(&self, other: &Constraints) -> bool {
+        self.0.len() == other.0.len()
+            && self.0.iter().zip(other.0.iter()).all(|(a, b)| format!("{:?}", a) == format!("{:?}", b))
+    }
+}
+
+impl Eq for Constraints { }
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LayoutError {
+    // Box dimensions giving fewer than 2 or more than Layout::MAX_SIZE digits
+    UnsupportedBox(usize, usize),
+    CellCount { expected: usize, found: usize },
+    // A cell holding a value larger than the layout's number of digits
+    InvalidValue(usize),
+    // Jigsaw layouts with fewer than 2 or more than Layout::MAX_SIZE cells per side
+    UnsupportedSize(usize),
+    // A region map whose number of cells isn't the square of a supported size
+    MapSize(usize),
+    RegionCount { expected: usize, found: usize },
+    // The first cell of a region that doesn't hold as many cells as there are digits
+    RegionSize(usize),
+    // The first cell of a region whose cells aren't all connected to each other
+    DisconnectedRegion(usize),
+    // Overlapping layouts need at least one grid
+    NoGrids,
+    // Hyper windows need the number of cells per side to be a square (ex: 9 for 3x3 windows)
+    UnsupportedWindows(usize),
+    // A cell that is outside of the grid or in more than one cage
+    CageCell(usize),
+    // The index of a cage without any cells, or with more cells than there are digits
+    CageSize(usize),
+    // The index of a cage whose sum can't be made up of its number of different digits
+    CageSum(usize),
+    DisconnectedCage(usize),
+    // The index of a row or column a sandwich clue is for, past the last one of the layout
+    SandwichLine(usize),
+    // A sandwich sum larger than the digits between the 1 and the largest digit add up to
+    SandwichSum(usize),
+    // A cell of a thermometer or arrow that is outside of the grid or on it more than once
+    ClueCell(usize),
+    // The first cell of a thermometer or arrow that isn't next to the one before it
+    DisconnectedClue(usize),
+    // Layouts without any solution, or none the generator could find (ex: jigsaw regions that can't be filled in)
+    Unsolvable,
+}
+
+impl Layout {
+    // Candidates are kept one bit per digit, so no layout can have more digits than a candidate set has bits (32)
+    pub const MAX_SIZE: usize = CandidateSet::MAX_DIGITS as usize;
+
+    // The classic 9x9 grid with 3x3 boxes
+    pub fn standard() -> Layout {
+        STANDARD.clone()
+    }
+
+    // A square grid with box_width * box_height cells per side, split into boxes box_width cells wide and box_height
+    // cells tall. Ex: 2 by 2 gives a 4x4 grid, 3 by 2 a 6x6 grid, 4 by 4 a 16x16 grid and 5 by 5 a 25x25 grid. Boxes
+    // of more than Layout::MAX_SIZE (32) cells are rejected, so 6 by 6 isn't supported.
+    pub fn new(box_width: usize, box_height: usize) -> Result<Layout, LayoutError> {
+        Layout::build(box_width, box_height, false)
+    }
+
+    // Same as new, with both main diagonals also holding every digit once (Sudoku X)
+    pub fn with_diagonals(box_width: usize, box_height: usize) -> Result<Layout, LayoutError> {
+        Layout::build(box_width, box_height, true)
+    }
+
+    // A square grid whose boxes are replaced by the given regions, one entry per cell in row order. Any value can be
+    // used to tell regions apart, as long as there are as many regions as cells per side, each holding that many
+    // connected cells. Ex: 81 entries of 1-9 for a 9x9 jigsaw puzzle. Regions are numbered in the order they first
+    // appear. Whether the regions can be filled in at all isn't checked: many arrangements can't, and generating
+    // puzzles for them fails with LayoutError::Unsolvable.
+    pub fn jigsaw(map: &[u8]) -> Result<Layout, LayoutError> {
+        let size = (1 ..= Layout::MAX_SIZE).find(|size| size * size >= map.len()).unwrap_or(0);
+        if size < 2 || size * size != map.len() {
+            return Err(LayoutError::MapSize(map.len()));
+        }
+
+        Ok(Layout::with_boxes(size, 0, 0, false, regions::from_map(size, map)?))
+    }
+
+    // A jigsaw layout with random regions. They're reshaped from the boxes of a random solution (or from its rows, for
+    // sizes that can't be split into boxes) in a way that keeps that solution valid, since many arrangements of
+    // regions can't be filled in at all.
+    pub fn random_jigsaw(size: usize) -> Result<Layout, LayoutError> {
+        if !(2 ..= Layout::MAX_SIZE).contains(&size) {
+            return Err(LayoutError::UnsupportedSize(size));
+        }
+
+        let box_height = (1 ..= size).rev()
+            .find(|&height| size % height == 0 && height * height <= size)
+            .unwrap_or(1);
+        let box_width = size / box_height;
+
+        let solution: Vec<u8> = fill(&Layout::new(box_width, box_height)?)?.into_iter().collect();
+        let regions = regions::random(size, box_width, box_height, &solution);
+
+        Ok(Layout::with_boxes(size, 0, 0, false, regions))
+    }
+
+    // Five 9x9 grids sharing their corner boxes with a grid in the middle (Samurai Sudoku), on a 21x21 board
+    pub fn samurai() -> Layout {
+        Layout::overlapping(3, 3, &[(0, 0), (0, 12), (6, 6), (12, 0), (12, 12)]).unwrap()
+    }
+
+    // Several grids with the same shape of boxes, placed on a larger board by the (row, column) of their top left cell.
+    // Cells where grids overlap belong to the rows, columns and boxes of each of them, and houses that end up with the
+    // exact same cells are only kept once. Cells are numbered in the board's row order, skipping positions no grid
+    // covers. Grids should overlap along their boxes, as in Layout::samurai, for shared boxes to line up.
+    pub fn overlapping(box_width: usize, box_height: usize, grids: &[(usize, usize)]) -> Result<Layout, LayoutError> {
+        let size = box_width * box_height;
+        if !(2 ..= Layout::MAX_SIZE).contains(&size) {
+            return Err(LayoutError::UnsupportedBox(box_width, box_height));
+        }
+
+        if grids.is_empty() {
+            return Err(LayoutError::NoGrids);
+        }
+
+        let board = Board::new(size, grids);
+        let boxes = regions::boxes(size, box_width, box_height);
+        let mut houses = vec![vec![]; SetType::ALL.len()];
+
+        for grid in 0 .. grids.len() {
+            for line in 0 .. size {
+                houses[SetType::Row as usize].push((0 .. size).map(|col| board.cell(grid, line, col)).collect());
+                houses[SetType::Col as usize].push((0 .. size).map(|row| board.cell(grid, row, line)).collect());
+            }
+
+            for cells in boxes.iter() {
+                let cells = cells.iter().map(|&i| board.cell(grid, i / size, i % size)).collect();
+                houses[SetType::Box as usize].push(cells);
+            }
+        }
+
+        for sets in houses.iter_mut() {
+            distinct(sets);
+        }
+
+        Ok(Layout(Arc::new(Tables::new(size, box_width, box_height, false, houses, board))))
+    }
+
+    // A copy of the layout with the given Killer Sudoku cages, replacing any it already had. Cages don't need to cover
+    // the whole grid, but a cell can only be in one of them.
+    pub fn with_cages(&self, cages: &[Cage]) -> Result<Layout, LayoutError> {
+        let mut caged = vec![None; self.cells()];
+
+        for (index, cage) in cages.iter().enumerate() {
+            if cage.cells.is_empty() || cage.cells.len() > self.size() {
+                return Err(LayoutError::CageSize(index));
+            }
+
+            for &cell in cage.cells.iter() {
+                if cell >= self.cells() || caged[cell].is_some() {
+                    return Err(LayoutError::CageCell(cell));
+                }
+                caged[cell] = Some(index);
+            }
+
+            if !regions::connected(&cage.cells, |cell| self.neighbours(cell)) {
+                return Err(LayoutError::DisconnectedCage(index));
+            }
+
+            if combinations(self.digits(), cage.cells.len(), cage.sum).is_empty() {
+                return Err(LayoutError::CageSum(index));
+            }
+        }
+
+        let tables = &self.0;
+        let mut copy = Tables::new(
+            tables.size, tables.box_width, tables.box_height, tables.diagonals,
+            tables.houses.clone(), tables.board.clone()
+        );
+
+        for cage in cages.iter() {
+            for &cell in cage.cells.iter() {
+                copy.peers[cell].extend(cage.cells.iter().copied());
+            }
+        }
+
+        copy.cages = cages.to_vec();
+        copy.caged = caged;
+        copy.constraints = tables.constraints.clone();
+
+        Ok(Layout(Arc::new(copy)))
+    }
+
+    // A copy of the layout with Hyper (Windoku) windows: four extra 3x3 houses at rows and columns 2-4 and 6-8 of a 9x9
+    // grid, each of which must also hold every digit once. Rows and columns outside of the windows then make up five
+    // more implied houses, which are included after the windows. Other sizes get windows the same way, as long as their
+    // side is a square: one fewer window than the square root along each side, with a line of cells around each one.
+    // Each grid of an overlapping layout gets its own windows.
+    pub fn with_windows(&self) -> Result<Layout, LayoutError> {
+        let size = self.size();
+        let root = (1 ..= size).find(|root| root * root >= size).unwrap_or(0);
+        if root < 2 || root * root != size {
+            return Err(LayoutError::UnsupportedWindows(size));
+        }
+
+        // Lines covered by each window, followed by the lines left between them
+        let mut groups: Vec<Vec<usize>> = (0 .. root - 1)
+            .map(|window| (0 .. root).map(|line| window * (root + 1) + 1 + line).collect())
+            .collect();
+        groups.push((0 .. root).map(|gap| gap * (root + 1)).collect());
+
+        let mut pairs: Vec<(usize, usize)> = (0 .. root)
+            .flat_map(|rows| (0 .. root).map(move |cols| (rows, cols)))
+            .collect();
+        pairs.sort_by_key(|&(rows, cols)| rows == root - 1 || cols == root - 1);
+
+        let tables = &self.0;
+        let board = &tables.board;
+
+        let mut windows: Vec<Vec<usize>> = vec![];
+        for grid in 0 .. board.grids.len() {
+            for &(rows, cols) in pairs.iter() {
+                let mut cells: Vec<usize> = groups[rows].iter()
+                    .flat_map(|&row| groups[cols].iter().map(move |&col| board.cell(grid, row, col)))
+                    .collect();
+                cells.sort_unstable();
+                windows.push(cells);
+            }
+        }
+        distinct(&mut windows);
+
+        let mut houses = tables.houses.clone();
+        houses[SetType::Hyper as usize] = windows;
+
+        let mut copy = Tables::new(size, tables.box_width, tables.box_height, tables.diagonals, houses, board.clone());
+        copy.constraints = tables.constraints.clone();
+
+        match self.has_cages() {
+            true  => Layout(Arc::new(copy)).with_cages(self.cages()),
+            false => Ok(Layout(Arc::new(copy)))
+        }
+    }
+
+    // A copy of the layout that also follows the given constraint, on top of the rules it already had. Ex:
+    // Layout::standard().with_constraint(AntiKnight) for anti-knight Sudoku.
+    pub fn with_constraint<C: Constraint + 'static>(&self, constraint: C) -> Layout {
+        let mut copy = Tables::clone(&self.0);
+        copy.constraints.0.push(Arc::new(constraint));
+
+        Layout(Arc::new(copy))
+    }
+
+    fn build(box_width: usize, box_height: usize, diagonals: bool) -> Result<Layout, LayoutError> {
+        let size = box_width * box_height;
+        if !(2 ..= Layout::MAX_SIZE).contains(&size) {
+            return Err(LayoutError::UnsupportedBox(box_width, box_height));
+        }
+
+        Ok(Layout::with_boxes(size, box_width, box_height, diagonals, regions::boxes(size, box_width, box_height)))
+    }
+
+    fn with_boxes(size: usize, box_width: usize, box_height: usize, diagonals: bool, boxes: Vec<Vec<usize>>) -> Layout {
+        let mut houses = vec![vec![vec![]; size]; 2];
+
+        for cell in 0..size * size {
+            houses[SetType::Row as usize][cell / size].push(cell);
+            houses[SetType::Col as usize][cell % size].push(cell);
+        }
+
+        houses.push(boxes);
+
+        // The diagonal running down from the top left corner comes first, then the one running down from the top right
+        houses.push(match diagonals {
+            true  => vec![
+                (0..size).map(|i| i * size + i).collect(),
+                (0..size).map(|i| i * size + size - 1 - i).collect()
+            ],
+            false => vec![]
+        });
+
+        // Hyper windows are added afterwards, by with_windows
+        houses.push(vec![]);
+
+        Layout(Arc::new(Tables::new(size, box_width, box_height, diagonals, houses, Board::new(size, &[(0, 0)]))))
+    }
+
+    // Number of digits, and of cells along each side of the grid
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.0.size
+    }
+
+    #[inline]
+    pub fn cells(&self) -> usize {
+        self.0.board.positions.len()
+    }
+
+    // The (row, column) of each grid's top left cell on the board, (0, 0) for single grid layouts
+    pub fn grids(&self) -> &[(usize, usize)] {
+        &self.0.board.grids
+    }
+
+    pub fn is_overlapping(&self) -> bool {
+        self.0.board.grids.len() > 1
+    }
+
+    // Number of rows and columns of the board holding every grid
+    pub fn board_size(&self) -> (usize, usize) {
+        (self.0.board.rows, self.0.board.cols)
+    }
+
+    // The (row, column) of the cell on the board
+    #[inline]
+    pub fn position(&self, cell: usize) -> (usize, usize) {
+        self.0.board.positions[cell]
+    }
+
+    // The cell at the given row and column of the board, if any grid covers it
+    #[inline]
+    pub fn cell_at(&self, row: usize, col: usize) -> Option<usize> {
+        let board = &self.0.board;
+        match row < board.rows && col < board.cols {
+            true  => board.cells[row * board.cols + col],
+            false => None
+        }
+    }
+
+    // The cell at the given offset from another one on the board, if any
+    pub(crate) fn offset(&self, cell: usize, rows: isize, cols: isize) -> Option<usize> {
+        let (row, col) = self.position(cell);
+        let (row, col) = (row.checked_add_signed(rows)?, col.checked_add_signed(cols)?);
+        self.cell_at(row, col)
+    }
+
+    // Cells sharing a side with the given one
+    pub(crate) fn neighbours(&self, cell: usize) -> Vec<usize> {
+        [(-1, 0), (1, 0), (0, -1), (0, 1)].iter().filter_map(|&(rows, cols)| self.offset(cell, rows, cols)).collect()
+    }
+
+    // Cell at a row and column of one of the grids
+    #[inline]
+    pub(crate) fn grid_cell(&self, grid: usize, row: usize, col: usize) -> usize {
+        self.0.board.cell(grid, row, col)
+    }
+
+    // 0 for jigsaw layouts, same as box_height
+    pub fn box_width(&self) -> usize {
+        self.0.box_width
+    }
+
+    pub fn box_height(&self) -> usize {
+        self.0.box_height
+    }
+
+    pub fn has_diagonals(&self) -> bool {
+        self.0.diagonals
+    }
+
+    pub fn has_windows(&self) -> bool {
+        !self.0.houses[SetType::Hyper as usize].is_empty()
+    }
+
+    pub fn cages(&self) -> &[Cage] {
+        &self.0.cages
+    }
+
+    pub fn has_cages(&self) -> bool {
+        !self.0.cages.is_empty()
+    }
+
+    // Index of the cell's cage within cages
+    #[inline]
+    pub(crate) fn cage_of(&self, cell: usize) -> Option<usize> {
+        self.0.caged[cell]
+    }
+
+    pub fn constraints(&self) -> &[Arc<dyn Constraint>] {
+        &self.0.constraints.0
+    }
+
+    pub fn has_constraints(&self) -> bool {
+        !self.0.constraints.0.is_empty()
+    }
+
+    pub fn is_jigsaw(&self) -> bool {
+        self.0.box_width == 0
+    }
+
+    // The box or jigsaw region of each cell, in row order, numbered from 0
+    pub fn regions(&self) -> Vec<u8> {
+        let mut map = vec![0; self.cells()];
+        for (region, cells) in self.0.houses[SetType::Box as usize].iter().enumerate() {
+            for &cell in cells.iter() {
+                map[cell] = region as u8;
+            }
+        }
+        map
+    }
+
+    pub fn is_standard(&self) -> bool {
+        *self == *STANDARD
+    }
+
+    // Every digit of the layout, as a candidate set
+    #[inline]
+    pub(crate) fn digits(&self) -> CandidateSet {
+        CandidateSet::from_bits_truncate(CandidateBits::MAX >> (Layout::MAX_SIZE - self.0.size))
+    }
+
+    #[inline]
+    pub(crate) fn house(&self, set_type: SetType, set_index: usize) -> &[usize] {
+        &self.0.houses[set_type as usize][set_index]
+    }
+
+    // Same as house, or None past the last house of its type
+    #[inline]
+    pub(crate) fn find_house(&self, set_type: SetType, set_index: usize) -> Option<&[usize]> {
+        self.0.houses[set_type as usize].get(set_index).map(|house| &house[..])
+    }
+
+    pub(crate) fn houses(&self) -> impl Iterator<Item = (SetType, usize)> + '_ {
+        self.0.houses.iter()
+            .zip(SetType::ALL.iter())
+            .flat_map(|(houses, &set_type)| (0..houses.len()).map(move |set_index| (set_type, set_index)))
+    }
+
+    #[inline]
+    pub(crate) fn memberships(&self, cell: usize) -> &[(SetType, usize)] {
+        &self.0.memberships[cell]
+    }
+
+    #[inline]
+    pub(crate) fn peers(&self, cell: usize) -> &HashSet<usize> {
+        &self.0.peers[cell]
+    }
+
+    pub(crate) fn disjoint_groups(&self) -> &[Vec<usize>] {
+        &self.0.disjoint
+    }
+
+    #[inline]
+    pub(crate) fn disjoint_peers(&self, cell: usize) -> &[usize] {
+        &self.0.disjoint_peers[cell]
+    }
+}
+
+impl Tables {
+    fn new(size: usize, box_width: usize, box_height: usize, diagonals: bool, houses: Vec<Vec<Vec<usize>>>,
+    board: Board) -> Tables {
+        let cells = board.positions.len();
+        let mut memberships = vec![vec![]; cells];
+        let mut peers = vec![HashSet::new(); cells];
+
+        for (set_type, sets) in SetType::ALL.iter().zip(houses.iter()) {
+            for (set_index, house) in sets.iter().enumerate() {
+                for &cell in house.iter() {
+                    memberships[cell].push((*set_type, set_index));
+                    peers[cell].extend(house.iter().copied());
+                }
+            }
+        }
+
+        let disjoint = disjoint_groups(size, box_width, box_height, &board);
+        let mut disjoint_peers = vec![vec![]; cells];
+        for group in disjoint.iter() {
+            for &cell in group.iter() {
+                disjoint_peers[cell].extend(group.iter().copied().filter(|&other| other != cell));
+            }
+        }
+        for others in disjoint_peers.iter_mut() {
+            others.sort_unstable();
+            others.dedup();
+        }
+
+        Tables {
+            size, box_width, box_height, diagonals, houses, memberships, peers,
+            cages: vec![], caged: vec![None; cells], constraints: Constraints::default(),
+            disjoint, disjoint_peers, board
+        }
+    }
+}
+
+// Every group of every grid, each holding one cell per box. Jigsaw layouts have no boxes, so they have no groups.
+fn disjoint_groups(size: usize, width: usize, height: usize, board: &Board) -> Vec<Vec<usize>> {
+    if width == 0 {
+        return Vec::new();
+    }
+
+    let mut groups = vec![];
+    for grid in 0 .. board.grids.len() {
+        for (row, col) in (0 .. height).flat_map(|row| (0 .. width).map(move |col| (row, col))) {
+            let boxes = (0 .. size / height).flat_map(|band| (0 .. size / width).map(move |stack| (band, stack)));
+            let cells = boxes.map(|(band, stack)| (band * height + row, stack * width + col));
+            groups.push(cells.map(|(r, c)| board.cell(grid, r, c)).collect());
+        }
+    }
+
+    groups
+}
+
+// Drops houses holding the exact same cells as an earlier one, ex: the corner boxes shared by two Samurai grids
+fn distinct(houses: &mut Vec<Vec<usize>>) {
+    let mut seen = HashSet::new();
+    houses.retain(|house| {
+        let mut cells = house.clone();
+        cells.sort_unstable();
+        seen.insert(cells)
+    });
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Layout::standard()
+    }
+}
+
+// Ex: 6x6 (3x2 boxes), 9x9 (3x3 boxes, diagonals), 9x9 (jigsaw), 9x9 (3x3 boxes, 29 cages, AntiKnight) or 21x21 (5
+// overlapping 9x9 grids, 3x3 boxes)
+impl fmt::Display for Layout {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (rows, cols) = self.board_size();
+        write!(formatter, "{}x{} (", rows, cols)?;
+        if self.is_overlapping() {
+            write!(formatter, "{0} overlapping {1}x{1} grids, ", self.grids().len(), self.size())?;
+        }
+        match self.is_jigsaw() {
+            true  => write!(formatter, "jigsaw")?,
+            false => write!(formatter, "{}x{} boxes", self.box_width(), self.box_height())?
+        };
+        if self.has_diagonals() {
+            write!(formatter, ", diagonals")?;
+        }
+        if self.has_windows() {
+            write!(formatter, ", windows")?;
+        }
+        if self.has_cages() {
+            write!(formatter, ", {} cages", self.cages().len())?;
+        }
+        for constraint in self.constraints() {
+            write!(formatter, ", {:?}", constraint)?;
+        }
+        write!(formatter, ")")
+    }
+}
+
+impl fmt::Debug for Layout {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(formatter, "Layout({})", self)
+    }
+}
+
+impl fmt::Display for LayoutError {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LayoutError::UnsupportedBox(width, height) => write!(
+                formatter, "{}x{} boxes give {} digits, but layouts have between 2 and {} digits",
+                width, height, width * height, Layout::MAX_SIZE
+            ),
+            LayoutError::CellCount { expected, found } =>
+                write!(formatter, "expected {} cells, found {}", expected, found),
+            LayoutError::InvalidValue(index) =>
+                write!(formatter, "invalid value at cell {}", index),
+            LayoutError::UnsupportedSize(size) =>
+                write!(formatter, "{0}x{0} grids aren't supported, sides need 2 to {1} cells", size, Layout::MAX_SIZE),
+            LayoutError::MapSize(count) =>
+                write!(formatter, "a region map of {} cells doesn't make a square grid", count),
+            LayoutError::RegionCount { expected, found } =>
+                write!(formatter, "expected {} regions, found {}", expected, found),
+            LayoutError::RegionSize(index) =>
+                write!(formatter, "the region at cell {} doesn't hold as many cells as there are digits", index),
+            LayoutError::DisconnectedRegion(index) =>
+                write!(formatter, "the region at cell {} isn't connected", index),
+            LayoutError::NoGrids =>
+                write!(formatter, "overlapping layouts need at least one grid"),
+            LayoutError::UnsupportedWindows(size) =>
+                write!(formatter, "{0}x{0} grids can't have Hyper windows, their side needs to be a square", size),
+            LayoutError::CageCell(index) =>
+                write!(formatter, "cell {} is outside of the grid or in more than one cage", index),
+            LayoutError::CageSize(index) =>
+                write!(formatter, "cage {} has no cells, or more cells than there are digits", index),
+            LayoutError::CageSum(index) =>
+                write!(formatter, "cage {} has a sum its cells can't add up to", index),
+            LayoutError::DisconnectedCage(index) =>
+                write!(formatter, "cage {} isn't connected", index),
+            LayoutError::SandwichLine(index) =>
+                write!(formatter, "the layout has no row or column {} for a sandwich clue", index),
+            LayoutError::SandwichSum(sum) =>
+                write!(formatter, "the digits of a sandwich can't add up to {}", sum),
+            LayoutError::ClueCell(index) =>
+                write!(formatter, "cell {} is outside of the grid or on a thermometer or arrow twice", index),
+            LayoutError::DisconnectedClue(index) =>
+                write!(formatter, "cell {} of a thermometer or arrow isn't next to the one before it", index),
+            LayoutError::Unsolvable =>
+                write!(formatter, "no solution could be found for the layout"),
+        }
+    }
+}
+
+impl std::error::Error for LayoutError { }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ solver::Solver, puzzle::Puzzle, grader::{ validate_puzzle, ValidationError } };
+
+    #[test]
+    fn layouts_are_capped_at_32_digits() {
+        assert_eq!(Layout::new(4, 8).map(|layout| layout.size()), Ok(32));
+        assert_eq!(Layout::new(6, 6), Err(LayoutError::UnsupportedBox(6, 6)));
+        assert_eq!(
+            LayoutError::UnsupportedBox(6, 6).to_string(),
+            "6x6 boxes give 36 digits, but layouts have between 2 and 32 digits"
+        );
+    }
+
+    #[test]
+    fn diagonals_hold_every_digit_once() {
+        let layout = Layout::with_diagonals(3, 3).unwrap();
+        let cells: Vec<u8> = fill(&layout).unwrap().into_iter().collect();
+
+        for diagonal in [(0 .. 9).map(|i| i * 10).collect::<Vec<_>>(), (0 .. 9).map(|i| i * 8 + 8).collect()] {
+            let mut values: Vec<u8> = diagonal.iter().map(|&cell| cells[cell]).collect();
+            values.sort_unstable();
+            assert_eq!(values, (1 ..= 9).collect::<Vec<u8>>());
+        }
+
+        let mut cells = vec![0; 81];
+        cells[0] = 5;
+        cells[10] = 5;
+        let error = validate_puzzle(&Puzzle::new(&layout, &cells).unwrap()).unwrap_err();
+        assert_eq!(error, ValidationError::Conflict((0, 0), (1, 1)));
+    }
+
+    #[test]
+    fn windows_and_the_regions_they_imply_are_houses() {
+        assert_eq!(Layout::new(3, 2).unwrap().with_windows().err(), Some(LayoutError::UnsupportedWindows(6)));
+
+        let layout = Layout::standard().with_windows().unwrap();
+        let mut cells: Vec<usize> = (0 .. 9).flat_map(|index| layout.house(SetType::Hyper, index).to_vec()).collect();
+        cells.sort_unstable();
+        assert_eq!(cells, (0 .. 81).collect::<Vec<_>>());
+        assert_eq!(layout.house(SetType::Hyper, 0), &[10, 11, 12, 19, 20, 21, 28, 29, 30]);
+
+        let layout = Layout::new(2, 2).unwrap().with_windows().unwrap();
+        let solved = Solver::new().solve_puzzle(&Puzzle::empty(&layout));
+        let solution = solved.result[0].solution.cells();
+
+        let mut window: Vec<u8> = [5, 6, 9, 10].iter().map(|&cell| solution[cell]).collect();
+        window.sort_unstable();
+        assert_eq!(window, vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn overlapping_grids_share_cells() {
+        let layout = Layout::samurai();
+        assert_eq!((layout.cells(), layout.board_size()), (369, (21, 21)));
+        assert_eq!(layout.cell_at(9, 0), None);
+
+        for cell in 0 .. layout.cells() {
+            let (row, col) = layout.position(cell);
+            assert_eq!(layout.cell_at(row, col), Some(cell));
+        }
+
+        // The corner boxes of the middle grid are in the rows of two grids
+        let shared = layout.cell_at(6, 6).unwrap();
+        let rows = layout.memberships(shared).iter().filter(|&&(set_type, _)| set_type == SetType::Row).count();
+        assert_eq!(rows, 2);
+
+        let layout = Layout::overlapping(2, 2, &[(0, 0), (2, 2)]).unwrap();
+        let solved = Solver::new().solve_puzzle(&Puzzle::empty(&layout));
+        let solution = solved.result[0].solution.cells();
+
+        for grid in 0 .. 2 {
+            for row in 0 .. 4 {
+                let mut values: Vec<u8> = (0 .. 4).map(|col| solution[layout.grid_cell(grid, row, col)]).collect();
+                values.sort_unstable();
+                assert_eq!(values, vec![1, 2, 3, 4]);
+            }
+        }
+    }
+}
@@ -13,13 +13,57 @@ mod bitsets;
 mod random;
 mod indices;
 mod format;
+mod parse;
+mod formats;
+mod pencilmarks;
+mod codec;
+mod svg;
+mod book;
+mod grader;
+mod transform;
+mod game;
+mod layout;
+mod regions;
+mod cages;
+mod constraints;
+mod puzzle;
+
+#[cfg(feature = "serde")]
+mod serialize;
+
+#[cfg(test)]
+mod fixtures;
 
 pub use {
     sudoku      :: { Sudoku },
     solver      :: { Solver },
     generator   :: { Generator },
+    parse       :: { parse_puzzle, ParseError, ParseErrorKind },
+    formats     :: { Format, PuzzleRecord },
+    pencilmarks :: { PencilMarks, parse_pencil_marks },
+    codec       :: { CompactPuzzle, CodecError },
+    svg         :: { SvgRenderer, Theme },
+    book        :: { Book, PageSize },
+    format      :: { Grid, GridStyle },
+    grader      :: { grade, hint, validate, validate_puzzle, Grade, Hint, Level, Technique, ValidationError },
+    transform   :: { canonicalize, Transform },
+    game        :: { Game, GameError },
+    layout      :: { Layout, LayoutError },
+    cages       :: { Cage },
+    constraints :: {
+        Constraint, AntiKnight, AntiKing, NonConsecutive, EvenOdd, DisjointGroups, Thermometer, Arrow, Sandwich
+    },
+    puzzle      :: { Puzzle },
 };
 
+pub mod output {
+    use super::{ solver, generator };
+    pub use {
+        solver      :: output :: { SolverOutput, SolutionRecord, PuzzleSolverOutput, PuzzleSolution },
+        generator   :: output :: { GeneratorOutput, PuzzleGeneratorOutput },
+    };
+}
+
 pub mod traits {
     use super::sudoku;
     pub use { 
@@ -0,0 +1,224 @@
+use crate::{ layout::Layout, format::symbol };
+
+use std::fmt;
+
+// Accepts the notations puzzles are commonly shared in:
+//
+//  - 81 characters on a single line, using 1-9 for values and any of 0 . _ * for empty cells
+//  - the same, broken up across several lines and/or padded with whitespace
+//  - 9x9 ASCII (or box-drawing) grids that use | - + = as separators
+//  - any of the above followed by a comment, either introduced by # or ; or simply separated from the last cell by
+//    whitespace (ex: rating columns in puzzle collections)
+//
+// Don't use regex to validate the input, the lib is way too big to include.
+pub fn parse_puzzle(input: &str) -> Result<[u8; 81], ParseError> {
+    let mut puzzle = [0; 81];
+    puzzle.copy_from_slice(&parse_cells(input, &Layout::standard())?);
+
+    Ok(puzzle)
+}
+
+// Same as parse_puzzle, for any layout. Layouts with more than 9 digits continue with letters (A for 10, B for 11...),
+// in either case; 0 still stands for an empty cell.
+pub(crate) fn parse_cells(input: &str, layout: &Layout) -> Result<Vec<u8>, ParseError> {
+    let expected = layout.cells();
+    let size = layout.size();
+    let mut cells = Vec::with_capacity(expected);
+
+    let mut line = 1;
+    let mut column = 0;
+
+    let mut chars = input.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        column += 1;
+
+        if c == '\n' {
+            line += 1;
+            column = 0;
+            continue;
+        }
+
+        if is_comment(c) {
+            skip_line(&mut chars);
+            continue;
+        }
+
+        if c.is_whitespace() || is_separator(c) {
+            continue;
+        }
+
+        let value = match symbol_value(c, size) {
+            Some(value) => value,
+            None => return Err(ParseError::new(line, column, ParseErrorKind::InvalidCharacter(c)))
+        };
+
+        if cells.len() == expected {
+            return Err(ParseError::new(line, column, ParseErrorKind::TooManyCells { expected }));
+        }
+
+        cells.push(value);
+
+        // Once the grid is complete, anything remaining on the same line is treated as a trailing comment -- unless it
+        // immediately continues with more cells, in which case the input is simply too long.
+        if cells.len() == expected {
+            match chars.peek() {
+                Some(&next) if symbol_value(next, size).is_some() =>
+                    return Err(ParseError::new(line, column + 1, ParseErrorKind::TooManyCells { expected })),
+                _ => skip_line(&mut chars)
+            }
+        }
+    }
+
+    if cells.len() < expected {
+        return Err(ParseError::new(line, column + 1, ParseErrorKind::TooFewCells { expected, found: cells.len() }));
+    }
+
+    Ok(cells)
+}
+
+// Value of a cell for a layout with the given number of digits
+#[inline]
+pub(crate) fn symbol_value(c: char, size: usize) -> Option<u8> {
+    let value = match c {
+        'a'..='z'   => c as u8 - b'a' + 10,
+        'A'..='Z'   => c as u8 - b'A' + 10,
+        _           => cell_value(c)?
+    };
+
+    match usize::from(value) <= size {
+        true  => Some(value),
+        false => None
+    }
+}
+
+#[inline]
+pub(crate) fn cell_value(c: char) -> Option<u8> {
+    match c {
+        '1'..='9'               => Some(c as u8 - b'0'),
+        '0' | '.' | '_' | '*'   => Some(0),
+        _                       => None
+    }
+}
+
+#[inline]
+pub(crate) fn is_separator(c: char) -> bool {
+    // Box-drawing characters are included so that the output of the library's own formatters can be read back in.
+    matches!(c, '|' | '-' | '+' | '=' | '\u{2500}'..='\u{257F}')
+}
+
+#[inline]
+pub(crate) fn is_comment(c: char) -> bool {
+    c == '#' || c == ';'
+}
+
+// Consumes characters up to, but not including, the next line break so that line counting remains accurate.
+fn skip_line(chars: &mut std::iter::Peekable<std::str::Chars>) {
+    while let Some(&c) = chars.peek() {
+        if c == '\n' { break; }
+        chars.next();
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ParseErrorKind {
+    InvalidCharacter(char),
+    TooFewCells { expected: usize, found: usize },
+    TooManyCells { expected: usize },
+    // A placed value already placed in the same house or cage
+    Conflict(u8),
+    Malformed(String),
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ParseError {
+    pub line:   usize,
+    pub column: usize,
+    pub kind:   ParseErrorKind
+}
+
+impl ParseError {
+    pub fn new(line: usize, column: usize, kind: ParseErrorKind) -> Self {
+        Self { line, column, kind }
+    }
+}
+
+impl fmt::Display for ParseErrorKind {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseErrorKind::InvalidCharacter(c) => write!(formatter, "unexpected character {:?}", c),
+            ParseErrorKind::TooFewCells { expected, found } =>
+                write!(formatter, "expected {} cells, found {}", expected, found),
+            ParseErrorKind::TooManyCells { expected } =>
+                write!(formatter, "expected {} cells, found more", expected),
+            ParseErrorKind::Conflict(value) =>
+                write!(formatter, "{} is already placed in the same row, column or box", symbol(*value)),
+            ParseErrorKind::Malformed(reason)   => write!(formatter, "{}", reason),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(formatter, "line {}, column {}: {}", self.line, self.column, self.kind)
+    }
+}
+
+impl std::error::Error for ParseError { }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::PUZZLE;
+
+    #[test]
+    fn grids_and_comments_are_read() {
+        let grid = "\
+            0 0 0 | 2 3 0 | 0 7 0\n\
+            0 5 0 | 0 0 0 | 0 0 0\n\
+            4 0 0 | 0 0 0 | 8 5 0\n\
+            ------+-------+------\n\
+            2 3 0 | 9 0 0 | 5 0 0\n\
+            0 6 0 | 0 0 4 | 0 9 3\n\
+            0 0 8 | 0 0 0 | 0 0 0\n\
+            ------+-------+------\n\
+            0 4 0 | 0 0 7 | 0 3 0\n\
+            8 0 0 | 0 0 0 | 0 0 0\n\
+            0 2 0 | 0 0 9 | 0 6 1";
+
+        let expected = parse_puzzle(PUZZLE).unwrap();
+        assert_eq!(parse_puzzle(grid).unwrap(), expected);
+        assert_eq!(parse_puzzle(&format!("# Rated\n{} 3.4 hard", PUZZLE)).unwrap(), expected);
+        assert_eq!(parse_puzzle(&PUZZLE.replace('0', ".")).unwrap(), expected);
+    }
+
+    #[test]
+    fn errors_point_at_the_line_and_column() {
+        let input = format!("{}\n{}x{}", &PUZZLE[.. 9], &PUZZLE[9 .. 11], &PUZZLE[12 ..]);
+        let error = parse_puzzle(&input).unwrap_err();
+
+        assert_eq!(error, ParseError::new(2, 3, ParseErrorKind::InvalidCharacter('x')));
+        assert_eq!(error.to_string(), "line 2, column 3: unexpected character 'x'");
+
+        let error = parse_puzzle(&PUZZLE[.. 80]).unwrap_err();
+        assert_eq!(error, ParseError::new(1, 81, ParseErrorKind::TooFewCells { expected: 81, found: 80 }));
+
+        let error = parse_puzzle(&format!("{}1", PUZZLE)).unwrap_err();
+        assert_eq!(error, ParseError::new(1, 82, ParseErrorKind::TooManyCells { expected: 81 }));
+    }
+
+    #[test]
+    fn letters_stand_for_digits_past_9() {
+        let layout = Layout::new(4, 4).unwrap();
+        let mut input = String::from("Ag");
+        input.push_str(&"0".repeat(254));
+
+        let cells = parse_cells(&input, &layout).unwrap();
+        assert_eq!(&cells[.. 3], &[10, 16, 0]);
+
+        let error = parse_cells(&input.replacen('g', "h", 1), &layout).unwrap_err();
+        assert_eq!(error, ParseError::new(1, 2, ParseErrorKind::InvalidCharacter('h')));
+    }
+}
@@ -0,0 +1,198 @@
+use crate :: {
+    sudoku  :: { Sudoku, traits :: SudokuState },
+    layout  :: Layout,
+    parse   :: { is_comment, is_separator, symbol_value, ParseError, ParseErrorKind },
+    format  :: { boxed_grid, symbol, ROUNDED },
+};
+
+use std::fmt;
+
+// Displays a state as a grid of candidates, the layout used by HoDoKu and most sudoku forums to share a puzzle
+// mid-solve. Cells that hold a value are written as that single digit, and cells left without any candidates as 0.
+// Grids of other layouts are drawn the same way as their puzzles, with digits past 9 written as letters.
+pub struct PencilMarks<'a>(&'a Sudoku);
+
+impl Sudoku {
+    pub fn pencil_marks(&self) -> PencilMarks<'_> {
+        PencilMarks(self)
+    }
+}
+
+impl<'a> fmt::Display for PencilMarks<'a> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cells: Vec<String> = self.0.iter().enumerate().map(|(index, &value)| {
+            if value > 0 {
+                return symbol(value).to_string();
+            }
+
+            match self.0.candidates(index) {
+                candidates if candidates.is_empty() => String::from("0"),
+                candidates => candidates.iter().map(|&c| symbol(c)).collect()
+            }
+        }).collect();
+
+        boxed_grid(formatter, &cells, self.0.layout(), &ROUNDED)
+    }
+}
+
+// Reads a candidate grid of the given layout back into a state. Every run of digits (and letters, for layouts with more
+// than 9 digits) is one cell: a single digit is a placed value, while longer runs list the cell's remaining candidates.
+// As with HoDoKu, an empty cell that is down to a single candidate is indistinguishable from a placed value, and will
+// be read back as one. Any border characters around or between the cells are ignored, including the . and : used by
+// HoDoKu. Placed values repeating one in the same house or cage are rejected, at the position of the later one, while
+// candidates that conflict with placed values can't be restored and are dropped.
+pub fn parse_pencil_marks(layout: &Layout, input: &str) -> Result<Sudoku, ParseError> {
+    let expected = layout.cells();
+    let mut cells: Vec<Vec<u8>> = Vec::with_capacity(expected);
+    let mut token: Option<Vec<u8>> = None;
+
+    // Line and column each cell starts at
+    let mut starts: Vec<(usize, usize)> = Vec::with_capacity(expected);
+
+    let mut line = 1;
+    let mut column = 0;
+
+    let mut chars = input.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        column += 1;
+
+        if c.is_ascii_alphanumeric() {
+            let digit = match symbol_value(c, layout.size()) {
+                Some(digit) => digit,
+                None => return Err(ParseError::new(line, column, ParseErrorKind::InvalidCharacter(c)))
+            };
+
+            if cells.len() == expected {
+                return Err(ParseError::new(line, column, ParseErrorKind::TooManyCells { expected }));
+            }
+
+            if token.is_none() {
+                starts.push((line, column));
+            }
+            let digits = token.get_or_insert_with(Vec::new);
+
+            // 0 is only meaningful on its own, as a cell that has no candidates left
+            if (digit == 0 && !digits.is_empty()) || digits.contains(&0) {
+                return Err(ParseError::new(line, column, ParseErrorKind::InvalidCharacter(c)));
+            }
+
+            digits.push(digit);
+            continue;
+        }
+
+        if let Some(digits) = token.take() {
+            cells.push(digits);
+        }
+
+        if c == '\n' {
+            line += 1;
+            column = 0;
+        }
+        else if is_comment(c) {
+            while chars.peek().is_some_and(|&c| c != '\n') {
+                chars.next();
+            }
+        }
+        else if !(c.is_whitespace() || is_separator(c) || c == '.' || c == ':') {
+            return Err(ParseError::new(line, column, ParseErrorKind::InvalidCharacter(c)));
+        }
+    }
+
+    if let Some(digits) = token.take() {
+        cells.push(digits);
+    }
+
+    if cells.len() < expected {
+        let kind = ParseErrorKind::TooFewCells { expected, found: cells.len() };
+        return Err(ParseError::new(line, column + 1, kind));
+    }
+
+    let mut puzzle = vec![0; expected];
+    for (i, digits) in cells.iter().enumerate() {
+        if let [value] = digits[..] {
+            if value > 0 && layout.peers(i).iter().any(|&peer| peer < i && puzzle[peer] == value) {
+                let (line, column) = starts[i];
+                return Err(ParseError::new(line, column, ParseErrorKind::Conflict(value)));
+            }
+            puzzle[i] = value;
+        }
+    }
+
+    let mut state = Sudoku::build(layout.clone(), &puzzle);
+    for (index, digits) in cells.iter().enumerate() {
+        if state.get(index) != Some(0) {
+            continue;
+        }
+
+        for candidate in state.candidates(index) {
+            if !digits.contains(&candidate) {
+                state = state.eliminate(index, candidate);
+            }
+        }
+    }
+
+    Ok(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::puzzle;
+
+    #[test]
+    fn candidates_round_trip() {
+        let state = Sudoku::new(&puzzle()).eliminate(0, 1);
+        let parsed = parse_pencil_marks(&Layout::standard(), &state.pencil_marks().to_string()).unwrap();
+
+        for index in 0..81 {
+            assert_eq!(parsed.get(index), state.get(index));
+            assert_eq!(parsed.candidates(index), state.candidates(index));
+        }
+    }
+
+    #[test]
+    fn other_layouts_are_drawn_in_their_own_boxes() {
+        let layout = Layout::new(2, 2).unwrap();
+        let state = Sudoku::with_layout(&layout, &[1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]).unwrap();
+        let text = state.pencil_marks().to_string();
+
+        assert_eq!(text.lines().count(), 7);
+        assert_eq!(text.lines().nth(1), Some("│ 1   234  │ 234  234  │"));
+
+        let parsed = parse_pencil_marks(&layout, &text).unwrap();
+        assert_eq!(parsed.candidates(1), vec![2, 3, 4]);
+    }
+
+    #[test]
+    fn digits_past_9_are_letters() {
+        let layout = Layout::new(4, 4).unwrap();
+        let state = Sudoku::with_layout(&layout, &vec![0; 256]).unwrap();
+        let text = state.pencil_marks().to_string();
+
+        assert!(text.contains("123456789ABCDEFG"));
+        assert_eq!(parse_pencil_marks(&layout, &text).unwrap().candidates(0).len(), 16);
+    }
+
+    #[test]
+    fn input_of_another_size_is_rejected() {
+        let state = Sudoku::with_layout(&Layout::new(2, 2).unwrap(), &[0; 16]).unwrap();
+        let error = parse_pencil_marks(&Layout::standard(), &state.pencil_marks().to_string()).err().unwrap();
+        assert_eq!(error.kind, ParseErrorKind::TooFewCells { expected: 81, found: 16 });
+
+        let error = parse_pencil_marks(&Layout::standard(), "1A").err().unwrap();
+        assert_eq!(error.kind, ParseErrorKind::InvalidCharacter('A'));
+    }
+
+    #[test]
+    fn repeated_values_are_rejected_where_they_repeat() {
+        let layout = Layout::new(2, 2).unwrap();
+        let rows = ["1 234 1 234", "34 34 12 12", "1 234 234 234", "234 234 234 234"];
+
+        let error = parse_pencil_marks(&layout, &rows.join("\n")).err().unwrap();
+        assert_eq!(error, ParseError::new(1, 7, ParseErrorKind::Conflict(1)));
+
+        let error = parse_pencil_marks(&layout, &rows.join("\n").replacen("1 234 1", "1 234 2", 1)).err().unwrap();
+        assert_eq!(error, ParseError::new(3, 1, ParseErrorKind::Conflict(1)));
+    }
+}
@@ -0,0 +1,106 @@
+use crate :: {
+    sudoku  :: { Sudoku, traits :: SudokuState },
+    layout  :: { Layout, LayoutError },
+    parse   :: { parse_cells, ParseError },
+    format  :: { Grid, symbol },
+};
+
+use std :: fmt;
+
+// A puzzle of any layout, with its cells in row order and 0 for empty cells. The [u8; 81] arrays used throughout the
+// rest of the library are the standard layout's puzzles; this is what the solver, generator and formatter take for the
+// others. Digits past 9 are written as letters, so 16x16 puzzles use 1-9 and A-G, and 25x25 puzzles 1-9 and A-P.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Puzzle {
+    layout: Layout,
+    cells:  Vec<u8>,
+}
+
+impl Puzzle {
+    pub fn new(layout: &Layout, cells: &[u8]) -> Result<Puzzle, LayoutError> {
+        if cells.len() != layout.cells() {
+            return Err(LayoutError::CellCount { expected: layout.cells(), found: cells.len() });
+        }
+
+        if let Some(index) = cells.iter().position(|&value| usize::from(value) > layout.size()) {
+            return Err(LayoutError::InvalidValue(index));
+        }
+
+        Ok(Puzzle { layout: layout.clone(), cells: cells.to_vec() })
+    }
+
+    pub fn empty(layout: &Layout) -> Puzzle {
+        Puzzle { layout: layout.clone(), cells: vec![0; layout.cells()] }
+    }
+
+    // Accepts the same notations as parse_puzzle, with as many cells as the layout has
+    pub fn parse(layout: &Layout, input: &str) -> Result<Puzzle, ParseError> {
+        Ok(Puzzle { layout: layout.clone(), cells: parse_cells(input, layout)? })
+    }
+
+    pub fn layout(&self) -> &Layout {
+        &self.layout
+    }
+
+    pub fn cells(&self) -> &[u8] {
+        &self.cells
+    }
+
+    // Number of cells holding a value
+    pub fn givens(&self) -> usize {
+        self.cells.iter().filter(|&&value| value > 0).count()
+    }
+
+    pub fn grid(&self) -> Grid<'_> {
+        Grid::with_layout(&self.layout, &self.cells)
+    }
+
+    // The values of a state, along with its layout
+    pub(crate) fn of<T: SudokuState>(state: &T) -> Puzzle {
+        Puzzle { layout: state.layout().clone(), cells: state.iter().copied().collect() }
+    }
+
+    // The cells as an array, for puzzles of the standard layout
+    pub fn to_array(&self) -> Option<[u8; 81]> {
+        match self.layout.is_standard() {
+            true  => {
+                let mut array = [0; 81];
+                array.copy_from_slice(&self.cells);
+                Some(array)
+            },
+            false => None
+        }
+    }
+}
+
+impl From<[u8; 81]> for Puzzle {
+    fn from(puzzle: [u8; 81]) -> Puzzle {
+        Puzzle { layout: Layout::standard(), cells: puzzle.to_vec() }
+    }
+}
+
+impl From<&Puzzle> for Sudoku {
+    fn from(puzzle: &Puzzle) -> Sudoku {
+        Sudoku::build(puzzle.layout.clone(), &puzzle.cells)
+    }
+}
+
+impl From<&Sudoku> for Puzzle {
+    fn from(state: &Sudoku) -> Puzzle {
+        Puzzle::of(state)
+    }
+}
+
+// A single line of digits and letters, or with the alternate flag ({:#}) a grid
+impl fmt::Display for Puzzle {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if formatter.alternate() {
+            return write!(formatter, "{}", self.grid());
+        }
+
+        for &value in self.cells.iter() {
+            write!(formatter, "{}", symbol(value))?;
+        }
+        Ok(())
+    }
+}
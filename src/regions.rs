@@ -0,0 +1,179 @@
+use crate :: {
+    layout :: LayoutError,
+    random :: traits :: Random,
+};
+
+// Cells of each box of a regular layout, numbered left to right and then top to bottom
+pub(crate) fn boxes(size: usize, box_width: usize, box_height: usize) -> Vec<Vec<usize>> {
+    let stacks = size / box_width;
+    let mut boxes = vec![vec![]; size];
+
+    for cell in 0..size * size {
+        let (row, col) = (cell / size, cell % size);
+        boxes[row / box_height * stacks + col / box_width].push(cell);
+    }
+
+    boxes
+}
+
+// Cells of each region of a jigsaw map, with regions numbered in the order they first appear
+pub(crate) fn from_map(size: usize, map: &[u8]) -> Result<Vec<Vec<usize>>, LayoutError> {
+    let mut labels: Vec<u8> = vec![];
+    let mut regions: Vec<Vec<usize>> = vec![];
+
+    for (cell, &label) in map.iter().enumerate() {
+        match labels.iter().position(|&l| l == label) {
+            Some(region) => regions[region].push(cell),
+            None => {
+                labels.push(label);
+                regions.push(vec![cell]);
+            }
+        }
+    }
+
+    if regions.len() != size {
+        return Err(LayoutError::RegionCount { expected: size, found: regions.len() });
+    }
+
+    for region in regions.iter() {
+        if region.len() != size {
+            return Err(LayoutError::RegionSize(region[0]));
+        }
+
+        if !connected(region, |cell| neighbours(size, cell)) {
+            return Err(LayoutError::DisconnectedRegion(region[0]));
+        }
+    }
+
+    Ok(regions)
+}
+
+// Random connected regions, shuffled from the boxes of the given solution. A cell is repeatedly handed over to a
+// neighbouring region, which gives back one of its own cells holding the same digit and bordering the first region, so
+// every region keeps holding each digit once and the solution stays valid. Trades that would split either region are
+// undone.
+pub(crate) fn random(size: usize, box_width: usize, box_height: usize, solution: &[u8]) -> Vec<Vec<usize>> {
+    let mut map = vec![0; size * size];
+
+    for (region, cells) in boxes(size, box_width, box_height).iter().enumerate() {
+        for &cell in cells.iter() {
+            map[cell] = region;
+        }
+    }
+
+    let cells: Vec<usize> = (0 .. size * size).collect();
+
+    for _ in 0 .. size * 4 {
+        for &cell in cells.iter().random() {
+            let given = map[cell];
+
+            // Picking from an empty list isn't possible, so cells only bordering their own region are skipped
+            let bordering: Vec<usize> = neighbours(size, cell).filter(|&n| map[n] != given).collect();
+            if bordering.is_empty() {
+                continue;
+            }
+
+            let taker = map[*bordering.iter().random().next().unwrap()];
+            map[cell] = taker;
+
+            let returned: Vec<usize> = (0 .. size * size)
+                .filter(|&c| c != cell && map[c] == taker && solution[c] == solution[cell])
+                .filter(|&c| neighbours(size, c).any(|n| map[n] == given))
+                .collect();
+
+            if returned.is_empty() {
+                map[cell] = given;
+                continue;
+            }
+
+            let back = *returned.iter().random().next().unwrap();
+            map[back] = given;
+
+            let step = |cell| neighbours(size, cell);
+            if !connected(&region(&map, given), step) || !connected(&region(&map, taker), step) {
+                map[cell] = given;
+                map[back] = taker;
+            }
+        }
+    }
+
+    (0 .. size).map(|r| region(&map, r)).collect()
+}
+
+fn region(map: &[usize], region: usize) -> Vec<usize> {
+    (0 .. map.len()).filter(|&cell| map[cell] == region).collect()
+}
+
+// Whether every cell can be reached from the first one by only stepping to the neighbours of each cell within the cells
+pub(crate) fn connected<I>(cells: &[usize], neighbours: impl Fn(usize) -> I) -> bool
+where I: IntoIterator<Item = usize> {
+    let mut reached = vec![cells[0]];
+    let mut pending = vec![cells[0]];
+
+    while let Some(cell) = pending.pop() {
+        for next in neighbours(cell) {
+            if cells.contains(&next) && !reached.contains(&next) {
+                reached.push(next);
+                pending.push(next);
+            }
+        }
+    }
+
+    reached.len() == cells.len()
+}
+
+// Cells sharing a side with the given one, in a square grid with size cells per side
+fn neighbours(size: usize, cell: usize) -> impl Iterator<Item = usize> {
+    let (row, col) = (cell / size, cell % size);
+
+    vec![
+        (row > 0).then(|| cell - size),
+        (row + 1 < size).then(|| cell + size),
+        (col > 0).then(|| cell - 1),
+        (col + 1 < size).then(|| cell + 1),
+    ].into_iter().flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_are_checked() {
+        let regions = from_map(4, &[1, 1, 2, 2, 1, 1, 2, 2, 3, 3, 4, 4, 3, 3, 4, 4]).unwrap();
+        assert_eq!(regions, boxes(4, 2, 2));
+
+        assert_eq!(
+            from_map(4, &[1; 16]),
+            Err(LayoutError::RegionCount { expected: 4, found: 1 })
+        );
+        assert_eq!(
+            from_map(4, &[1, 1, 1, 2, 1, 2, 2, 2, 3, 3, 4, 4, 3, 3, 4, 3]),
+            Err(LayoutError::RegionSize(8))
+        );
+        assert_eq!(
+            from_map(4, &[1, 1, 2, 2, 3, 3, 4, 4, 1, 1, 2, 2, 3, 3, 4, 4]),
+            Err(LayoutError::DisconnectedRegion(0))
+        );
+    }
+
+    #[test]
+    fn random_regions_keep_the_solution_valid() {
+        let solution = [
+            1, 2, 3, 4, 5, 6,
+            4, 5, 6, 1, 2, 3,
+            2, 3, 1, 5, 6, 4,
+            5, 6, 4, 2, 3, 1,
+            3, 1, 2, 6, 4, 5,
+            6, 4, 5, 3, 1, 2,
+        ];
+
+        for region in random(6, 3, 2, &solution) {
+            let mut values: Vec<u8> = region.iter().map(|&cell| solution[cell]).collect();
+            values.sort_unstable();
+
+            assert_eq!(values, vec![1, 2, 3, 4, 5, 6]);
+            assert!(connected(&region, |cell| neighbours(6, cell)));
+        }
+    }
+}
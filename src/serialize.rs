@@ -0,0 +1,194 @@
+use crate :: {
+    sudoku      :: { Sudoku, traits :: SudokuState },
+    generator   :: Generator,
+    parse       :: parse_puzzle,
+    codec       :: CompactPuzzle,
+    game        :: Game,
+    layout      :: Layout,
+    puzzle      :: Puzzle,
+    cages       :: Cage,
+};
+
+use serde :: { Serialize, Serializer, Deserialize, Deserializer, de::Error };
+
+// Grids are written as 81 character strings rather than arrays of numbers. Any notation accepted by parse_puzzle can be
+// read back in.
+pub mod grid {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(grid: &[u8; 81], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&to_string(grid.iter()))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<[u8; 81], D::Error> {
+        let text = <std::borrow::Cow<str>>::deserialize(deserializer)?;
+        parse_puzzle(&text).map_err(D::Error::custom)
+    }
+
+    pub fn to_string<'a>(values: impl Iterator<Item = &'a u8>) -> String {
+        values.map(|&value| (b'0' + value) as char).collect()
+    }
+}
+
+// Only the values of a state are stored; candidates are recalculated when it is read back in.
+impl Serialize for Sudoku {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&grid::to_string(self.iter()))
+    }
+}
+
+impl<'de> Deserialize<'de> for Sudoku {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        grid::deserialize(deserializer).map(|puzzle| Sudoku::new(&puzzle))
+    }
+}
+
+// Compact puzzles are stored in their base64 form, same as when they are shared in URLs.
+impl Serialize for CompactPuzzle {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_base64().map_err(serde::ser::Error::custom)?)
+    }
+}
+
+impl<'de> Deserialize<'de> for CompactPuzzle {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = <std::borrow::Cow<str>>::deserialize(deserializer)?;
+        CompactPuzzle::from_base64(&text).map_err(D::Error::custom)
+    }
+}
+
+// Games are stored in their saved base64 form, so a session can be moved between Rust and WASM whichever way it was
+// written.
+impl Serialize for Game {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_base64())
+    }
+}
+
+impl<'de> Deserialize<'de> for Game {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = <std::borrow::Cow<str>>::deserialize(deserializer)?;
+        Game::from_base64(&text).map_err(D::Error::custom)
+    }
+}
+
+// Layouts are stored as their box dimensions and whether they have diagonals, or as their region map for jigsaw
+// layouts or the position of each grid for overlapping layouts, then whether they have Hyper windows, along with any
+// Killer Sudoku cages. The rest is rebuilt when they are read back in. Constraints can be defined outside of the
+// library, so layouts with constraints can't be written out.
+#[derive(Serialize, Deserialize)]
+struct LayoutSettings {
+    #[serde(default, skip_serializing_if = "is_zero")]
+    box_width:  usize,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    box_height: usize,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    diagonals:  bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    regions:    Option<Vec<u8>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    grids:      Vec<(usize, usize)>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    windows:    bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    cages:      Vec<Cage>,
+}
+
+fn is_zero(value: &usize) -> bool {
+    *value == 0
+}
+
+impl Serialize for Layout {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if self.has_constraints() {
+            return Err(serde::ser::Error::custom("layouts with constraints can't be serialized"));
+        }
+
+        LayoutSettings {
+            box_width:  self.box_width(),
+            box_height: self.box_height(),
+            diagonals:  self.has_diagonals(),
+            regions:    if self.is_jigsaw() { Some(self.regions()) } else { None },
+            grids:      if self.is_overlapping() { self.grids().to_vec() } else { vec![] },
+            windows:    self.has_windows(),
+            cages:      self.cages().to_vec(),
+        }.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Layout {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let settings = LayoutSettings::deserialize(deserializer)?;
+        let layout = match (settings.regions, settings.diagonals) {
+            _ if !settings.grids.is_empty() =>
+                Layout::overlapping(settings.box_width, settings.box_height, &settings.grids),
+            (Some(regions), _)  => Layout::jigsaw(&regions),
+            (None, true)        => Layout::with_diagonals(settings.box_width, settings.box_height),
+            (None, false)       => Layout::new(settings.box_width, settings.box_height)
+        };
+
+        let (windows, cages) = (settings.windows, settings.cages);
+        let layout = layout.and_then(|layout| match windows {
+            true  => layout.with_windows(),
+            false => Ok(layout)
+        });
+
+        layout.and_then(|layout| match cages.is_empty() {
+            true  => Ok(layout),
+            false => layout.with_cages(&cages)
+        }).map_err(D::Error::custom)
+    }
+}
+
+// Puzzles keep their layout next to their cells, which are written the same way as grids.
+#[derive(Serialize, Deserialize)]
+struct PuzzleFields<'a> {
+    layout: Layout,
+    #[serde(borrow)]
+    cells:  std::borrow::Cow<'a, str>,
+}
+
+impl Serialize for Puzzle {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        PuzzleFields { layout: self.layout().clone(), cells: self.to_string().into() }.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Puzzle {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let fields = PuzzleFields::deserialize(deserializer)?;
+        Puzzle::parse(&fields.layout, &fields.cells).map_err(D::Error::custom)
+    }
+}
+
+// The generator's settings are read back in through its setters so that the same limits are applied to them.
+#[derive(Serialize, Deserialize)]
+struct GeneratorSettings {
+    samples:    u8,
+    iterations: u8,
+    removals:   u8,
+}
+
+impl Serialize for Generator {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        GeneratorSettings {
+            samples:    self.samples,
+            iterations: self.sample_iterations,
+            removals:   self.iteration_removals
+        }.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Generator {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let settings = GeneratorSettings::deserialize(deserializer)?;
+        let mut generator = Generator::new();
+
+        generator
+            .samples(settings.samples)
+            .iterations(settings.iterations)
+            .removals(settings.removals);
+
+        Ok(generator)
+    }
+}
@@ -1,7 +1,9 @@
-use crate :: sudoku :: { Sudoku, traits :: SudokuState };
+use crate :: { sudoku :: { Sudoku, traits :: SudokuState }, puzzle :: Puzzle };
 use self :: { output :: * };
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Solver {
+    #[cfg_attr(feature = "serde", serde(rename = "limit"))]
     solution_limit: usize
 }
 
@@ -21,75 +23,139 @@ impl Solver {
     }
 
     pub fn solve(&self, puzzle: &[u8; 81]) -> SolverOutput {
-        self.solve_state(&Sudoku::new(puzzle))
+        let state = Sudoku::new(puzzle);
+        let search = self.search(&state);
+
+        let result = search.found.into_iter().map(|(state, iteration, branches)| {
+            let mut solution = [0u8; 81];
+            for (i, &value) in state.iter().enumerate() {
+                solution[i] = value;
+            }
+
+            SolutionRecord { solution, iteration, branches }
+        }).collect();
+
+        SolverOutput { steps: state.remaining(), iterations: search.iterations, result }
+    }
+
+    // Solves states of any layout, so solutions come back as puzzles of the state's layout rather than 9x9 arrays
+    pub fn solve_state<T: SudokuState>(&self, state: &T) -> PuzzleSolverOutput {
+        let search = self.search(state);
+
+        let result = search.found.iter().map(|(state, iteration, branches)| PuzzleSolution {
+            solution: Puzzle::of(state),
+            iteration: *iteration,
+            branches: *branches,
+        }).collect();
+
+        PuzzleSolverOutput { steps: state.remaining(), iterations: search.iterations, result }
+    }
+
+    // Solves puzzles of any layout
+    pub fn solve_puzzle(&self, puzzle: &Puzzle) -> PuzzleSolverOutput {
+        self.solve_state(&Sudoku::from(puzzle))
+    }
+
+    pub(crate) fn search<T: SudokuState>(&self, state: &T) -> Search<T> {
+        let mut search = Search { iterations: 0, limit: 0, found: Vec::default() };
+
+        self.solve_rec(state, 0, &mut search);
+
+        search
     }
 
-    pub fn solve_state<T: SudokuState>(&self, state: &T) -> SolverOutput {
-        let mut output = SolverOutput { 
-            steps: state.remaining(),
-            iterations: 0,
-            result: Vec::default()
-        };
+    // Same as search, but gives up once the given number of iterations is reached (unless it is 0)
+    pub(crate) fn search_within<T: SudokuState>(&self, state: &T, limit: usize) -> Option<Search<T>> {
+        let mut search = Search { iterations: 0, limit, found: Vec::default() };
 
-        self.solve_rec(state, 0, &mut output);
-        
-        output
+        self.solve_rec(state, 0, &mut search);
+
+        match limit == 0 || search.iterations < limit {
+            true  => Some(search),
+            false => None
+        }
     }
 
-    fn solve_rec<T: SudokuState>(&self, state: &T, bf: usize, output: &mut SolverOutput) {
+    fn solve_rec<T: SudokuState>(&self, state: &T, bf: usize, search: &mut Search<T>) {
         if state.remaining() == 0 {
-            let mut solution = [0u8; 81];
-            for (i, &value) in state.iter().enumerate() {
-                solution[i] = value;
-            }
-    
-            let rec = SolutionRecord {
-                solution,
-                iteration: output.iterations,
-                branches: bf,
-            };
-    
-            output.result.push(rec);
+            search.found.push((state.clone(), search.iterations, bf));
         }
     
-        if self.solution_limit > 0 && output.result.len() >= self.solution_limit {
+        if self.solution_limit > 0 && search.found.len() >= self.solution_limit {
+            return;
+        }
+
+        if search.limit > 0 && search.iterations >= search.limit {
             return;
         }
     
-        output.iterations += 1;
+        search.iterations += 1;
     
         let choices = state.next_choice();
         let bc = choices.len();
     
         for (index, value) in choices {
-            self.solve_rec(&state.set(index, value), bf + (bc - 1).pow(2), output);
+            self.solve_rec(&state.set(index, value), bf + (bc - 1).pow(2), search);
         }
     }
 }
 
+// Solved states in the order they were found, along with the iteration and branches that led to each. A limit of 0
+// lets the search run for as many iterations as it needs.
+pub(crate) struct Search<T> {
+    pub iterations: usize,
+    pub limit:      usize,
+    pub found:      Vec<(T, usize, usize)>,
+}
+
 pub mod output {
-    use crate :: format :: { BoxFormat, Alignment };
+    use crate :: { format :: { BoxFormat, Alignment, Grid }, puzzle :: Puzzle };
 
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct SolverOutput {
         pub steps: usize,
         pub iterations: usize,
         pub result: Vec<SolutionRecord>,
     }
 
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct SolutionRecord {
         pub iteration: usize,
         pub branches: usize,
+        #[cfg_attr(feature = "serde", serde(with = "crate::serialize::grid"))]
         pub solution: [u8; 81],
     }
 
+    // Solutions of a puzzle of any layout
+    #[derive(Debug)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct PuzzleSolverOutput {
+        pub steps: usize,
+        pub iterations: usize,
+        pub result: Vec<PuzzleSolution>,
+    }
+
+    #[derive(Debug)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct PuzzleSolution {
+        pub iteration: usize,
+        pub branches: usize,
+        pub solution: Puzzle,
+    }
+
+    // The alternate flag ({:#} and {:#?}) draws each solution as a 9x9 grid rather than a single line of digits.
     impl std::fmt::Display for SolverOutput {
         fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            let alternate = formatter.alternate();
             let mut boxf = BoxFormat::new(formatter);
 
             boxf.header("Waffle-Iron", None)?;
             for solution in self.result.iter() {
-                boxf.line_break()?
-                    .content(&format!("{}", solution), None)?;
+                boxf.line_break()?;
+                match alternate {
+                    true  => boxf.block(&format!("{:#}", solution), Alignment::Center.into())?,
+                    false => boxf.content(&format!("{}", solution), None)?
+                };
             }
             boxf.footer("", None)?;
 
@@ -103,15 +169,19 @@ pub mod output {
                 "Iterations: {}, Steps: {}", self.iterations, self.steps
             );
 
+            let alternate = formatter.alternate();
             let mut boxf = BoxFormat::new(formatter);
             boxf.header("Waffle-Iron", None)?;
             
             for result in self.result.iter() {
                 let summary = format!("Iteration: {}, Branches: {}", result.iteration, result.branches);
                 boxf.section(&summary, Alignment::Center.into())?
-                    .empty_line()?
-                    .content(&format!("{}", result), None)?
                     .empty_line()?;
+                match alternate {
+                    true  => boxf.block(&format!("{:#}", result), Alignment::Center.into())?,
+                    false => boxf.content(&format!("{}", result), None)?
+                };
+                boxf.empty_line()?;
             }
 
             boxf.section("Summary", None)?
@@ -122,8 +192,18 @@ pub mod output {
         }
     }
 
+    impl SolutionRecord {
+        pub fn grid(&self) -> Grid<'_> {
+            Grid::new(&self.solution)
+        }
+    }
+
     impl std::fmt::Display for SolutionRecord {
         fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            if formatter.alternate() {
+                return write!(formatter, "{}", self.grid());
+            }
+
             for num in self.solution.iter() {
                 write!(formatter, "{}", num)?;
             }
@@ -134,11 +214,15 @@ pub mod output {
     impl std::fmt::Debug for SolutionRecord {
         fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
             let summary = format!("Iteration: {}, Branches: {}", self.iteration, self.branches);
+            let (solution, align) = match formatter.alternate() {
+                true  => (format!("{:#}", self), Alignment::Center.into()),
+                false => (format!("{}", self), None)
+            };
 
             BoxFormat::new(formatter)
                 .header("Waffle-Iron", None)?
                 .empty_line()?
-                .content(&format!("{}", self), None)?
+                .block(&solution, align)?
                 .empty_line()?
                 .section("Summary", None)?
                 .content(&summary, Alignment::Center.into())?
@@ -147,4 +231,55 @@ pub mod output {
             Ok(())
         }
     }
-}
\ No newline at end of file
+
+    // The alternate flag ({:#}) draws each solution as a grid rather than a single line of digits and letters.
+    impl std::fmt::Display for PuzzleSolverOutput {
+        fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            let alternate = formatter.alternate();
+            let mut boxf = BoxFormat::new(formatter);
+
+            boxf.header("Waffle-Iron", None)?;
+            for result in self.result.iter() {
+                boxf.line_break()?;
+                match alternate {
+                    true  => boxf.block(&format!("{:#}", result.solution), Alignment::Center.into())?,
+                    false => boxf.content(&format!("{}", result.solution), None)?
+                };
+            }
+            boxf.footer("", None)?;
+
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::Layout;
+
+    #[test]
+    fn solve_state_keeps_the_layout() {
+        for (width, height) in [(2, 2), (3, 2)] {
+            let layout = Layout::new(width, height).unwrap();
+            let state = Sudoku::from(&Puzzle::empty(&layout));
+            let output = Solver::new().solve_state(&state);
+
+            let solution = &output.result[0].solution;
+            assert_eq!(solution.layout(), &layout);
+            assert_eq!(solution.cells().len(), layout.cells());
+            assert!(solution.cells().iter().all(|&value| value > 0));
+        }
+    }
+
+    #[test]
+    fn solve_matches_solve_puzzle_for_9x9() {
+        let puzzle = crate::fixtures::puzzle();
+
+        let array = Solver::with_limit(2).solve(&puzzle);
+        let general = Solver::with_limit(2).solve_puzzle(&Puzzle::from(puzzle));
+
+        assert_eq!(array.result.len(), 1);
+        assert_eq!(&array.result[0].solution[..], general.result[0].solution.cells());
+    }
+}
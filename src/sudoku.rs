@@ -2,6 +2,9 @@ use crate :: {
     candidates  :: { CandidateSet, traits :: * },
     values      :: { LocationSet, value_key :: ValueKey, traits :: * },
     bitsets     :: traits :: BitSet,
+    layout      :: { Layout, LayoutError },
+    cages       :: combinations,
+    constraints :: Constraint,
 };
 
 use self :: {
@@ -18,27 +21,30 @@ use im_rc :: { Vector, HashMap, OrdSet };
 type _StaticHasher = BuildHasherDefault<DefaultHasher>;
 type RandomHasher = RandomState;
 
-type Puzzle         = im_rc::Vector<u8>;
+type Cells          = im_rc::Vector<u8>;
 type PuzzleIter<'a> = im_rc::vector::Iter<'a, u8>;
 type CandidateMap   = im_rc::HashMap<usize, CandidateSet, RandomHasher>;
 type ValueMap       = im_rc::HashMap<ValueKey, LocationSet, RandomHasher>;
 type ChoiceQueue    = im_rc::OrdSet<Choice>;
 
 pub mod traits {
-    use super::PuzzleIter;
+    use super::{ PuzzleIter, Layout };
 
     pub trait SudokuState: Clone {
+        // A state of the standard 9x9 layout
         fn new(puzzle: &[u8; 81]) -> Self;
+        fn layout(&self) -> &Layout;
         fn next_choice(&self) -> Vec<(usize, u8)>;
         fn get(&self, index: usize) -> Option<u8>;
         fn set(&self, index: usize, value: u8) -> Self;
-        fn iter(&self) -> PuzzleIter;
+        fn iter(&self) -> PuzzleIter<'_>;
         fn remaining(&self) -> usize;
     }
 }
 
 pub struct Sudoku {
-    puzzle: Puzzle,
+    layout: Layout,
+    puzzle: Cells,
     remaining: usize,
 
     // Stores set of all allowable candidate values at each non-empty cell index. This is calculated based on existing
@@ -60,6 +66,45 @@ pub struct Sudoku {
 }
 
 impl Sudoku {
+    // A state of any layout, from its cells in row order (0 for empty cells)
+    pub fn with_layout(layout: &Layout, puzzle: &[u8]) -> Result<Self, LayoutError> {
+        if puzzle.len() != layout.cells() {
+            return Err(LayoutError::CellCount { expected: layout.cells(), found: puzzle.len() });
+        }
+
+        if let Some(index) = puzzle.iter().position(|&value| usize::from(value) > layout.size()) {
+            return Err(LayoutError::InvalidValue(index));
+        }
+
+        Ok(Sudoku::build(layout.clone(), puzzle))
+    }
+
+    pub(crate) fn build(layout: Layout, puzzle: &[u8]) -> Self {
+        let mut yaws = Sudoku {
+            layout,
+            puzzle:         Vector::from(puzzle),
+            remaining:      puzzle.iter().fold(0, |c, &v| if v == 0 { c + 1 } else { c }),
+
+            candidate_map:  HashMap::default(),
+            value_map:      HashMap::default(),
+            choices:        OrdSet::default(),
+        };
+
+        yaws.init_candidates();
+        yaws.init_values();
+
+        for cage in 0..yaws.layout.cages().len() {
+            yaws.prune_cage(cage);
+        }
+
+        let layout = yaws.layout.clone();
+        for constraint in layout.constraints() {
+            yaws.constrain(constraint.as_ref(), 0..layout.cells());
+        }
+
+        yaws
+    }
+
     fn update(&mut self, index: usize, new_value: u8) {
         let old_value = self.puzzle[index];
 
@@ -74,6 +119,12 @@ impl Sudoku {
 
         self.puzzle[index] = new_value;
 
+        // Erasing a value can give candidates back to any cell a cage or constraint reaches, not just its peers
+        if new_value == 0 && (self.layout.has_cages() || self.layout.has_constraints()) {
+            self.restore(index);
+            return;
+        }
+
         // TODO: This is an ugly hack necessary because the value map is depended on the state of the candidate map. 
         // Depending on whether a value is being added or removed the value_map expects the candidate map to either have
         // or not have the new state. Not sure how to clean this uglyness up yet.
@@ -84,24 +135,144 @@ impl Sudoku {
             self.update_values(index, new_value, old_value);
             self.update_candidates(index, new_value, old_value);
         }
+
+        let layout = self.layout.clone();
+
+        if let Some(cage) = layout.cage_of(index) {
+            self.prune_cage(cage);
+        }
+
+        for constraint in layout.constraints() {
+            self.constrain(constraint.as_ref(), constraint.related(&layout, index));
+        }
     }
-}
 
-impl SudokuState for Sudoku {
-    fn new(puzzle: &[u8; 81]) -> Self {
-        let mut yaws = Sudoku {
-            puzzle:         Vector::from(&puzzle[..]),
-            remaining:      puzzle.iter().fold(0, |c, &v| if v == 0 { c + 1 } else { c }),
+    // Works out the candidates of every cell erasing the value at the given index can give candidates back to, the same
+    // way as Sudoku::build does, then prunes them again with the cages and constraints reaching them. Those are its
+    // peers and the cells constraints relate to it, along with every cell sharing a cage with any of them or related to
+    // them by a constraint that reads candidates, and so on. Other cells keep their candidates, since none of the
+    // values or candidates that ruled those out changed.
+    fn restore(&mut self, index: usize) {
+        let layout = self.layout.clone();
+        let constraints = layout.constraints();
+
+        let mut reached = vec![false; layout.cells()];
+        let mut pending: Vec<usize> = layout.peers(index).iter().copied()
+            .chain(constraints.iter().flat_map(|constraint| constraint.related(&layout, index)))
+            .collect();
+
+        while let Some(cell) = pending.pop() {
+            if reached[cell] { continue; }
+            reached[cell] = true;
+
+            if let Some(cage) = layout.cage_of(cell) {
+                pending.extend(layout.cages()[cage].cells.iter().copied());
+            }
+            for constraint in constraints.iter().filter(|constraint| constraint.reads_candidates()) {
+                pending.extend(constraint.related(&layout, cell));
+            }
+        }
 
-            candidate_map:  HashMap::default(),
-            value_map:      HashMap::default(),
-            choices:        OrdSet::default(),
+        let cells: Vec<usize> = (0 .. layout.cells()).filter(|&cell| reached[cell]).collect();
+
+        // Candidates first, since the value map is filled from them
+        for &cell in cells.iter() {
+            self.restore_candidates(cell);
+        }
+        for &cell in cells.iter() {
+            self.restore_values(cell);
+        }
+
+        let cages: OrdSet<usize> = cells.iter().filter_map(|&cell| layout.cage_of(cell)).collect();
+        for cage in cages {
+            self.prune_cage(cage);
+        }
+
+        for constraint in constraints {
+            self.constrain(constraint.as_ref(), cells.iter().copied());
+        }
+    }
+
+    // Removes the candidates the constraint rules out from the given cells, then any it can infer from the whole grid
+    fn constrain(&mut self, constraint: &dyn Constraint, cells: impl IntoIterator<Item = usize>) {
+        for cell in cells {
+            if self.puzzle[cell] > 0 { continue; }
+
+            for value in constraint.prune(self, cell) {
+                self.exclude_value(cell, value);
+                self.exclude_candidate(cell, value);
+            }
+        }
+
+        for (cell, value) in constraint.infer(self) {
+            if self.puzzle[cell] == 0 {
+                self.exclude_value(cell, value);
+                self.exclude_candidate(cell, value);
+            }
+        }
+    }
+
+    // Removes the candidates of a cage's empty cells that can't be part of any set of different digits adding up to
+    // what is left of its sum. Ex: the empty cells of a 3 cell cage of 10 holding a 1 can only be 2, 3, 4 or 5.
+    fn prune_cage(&mut self, cage: usize) {
+        let layout = self.layout.clone();
+        let cage = &layout.cages()[cage];
+
+        let empty: Vec<usize> = cage.cells.iter().copied().filter(|&cell| self.puzzle[cell] == 0).collect();
+        if empty.is_empty() { return; }
+
+        let placed: usize = cage.cells.iter().map(|&cell| usize::from(self.puzzle[cell])).sum();
+        let union = empty.iter()
+            .filter_map(|&cell| CandidatesRead::get(self, cell))
+            .fold(CandidateSet::empty(), |union, &set| union | set);
+
+        let allowed = match cage.sum.checked_sub(placed) {
+            Some(left) => combinations(union, empty.len(), left),
+            None => CandidateSet::empty()
         };
 
-        yaws.init_candidates();
-        yaws.init_values();
+        for cell in empty {
+            let excluded = match CandidatesRead::get(self, cell) {
+                Some(&set) => set - allowed,
+                None => continue
+            };
 
-        yaws
+            for candidate in excluded.iter() {
+                let value = u8::from(candidate);
+                self.exclude_value(cell, value);
+                self.exclude_candidate(cell, value);
+            }
+        }
+    }
+
+    // Lists the values that can still be placed at the given index. Cells that already hold a value have no candidates.
+    pub fn candidates(&self, index: usize) -> Vec<u8> {
+        match CandidatesRead::get(self, index) {
+            Some(set) => set.iter().map(u8::from).collect(),
+            None => Vec::default()
+        }
+    }
+
+    // Creates a clone of the current state with the given value removed from the cell's candidates. This is meant for
+    // recreating states where candidates were eliminated by means other than placing values (ex: pencil marks).
+    pub fn eliminate(&self, index: usize, value: u8) -> Self {
+        let mut clone = self.clone();
+        if clone.puzzle[index] == 0 {
+            clone.exclude_value(index, value);
+            clone.exclude_candidate(index, value);
+        }
+        clone
+    }
+}
+
+impl SudokuState for Sudoku {
+    fn new(puzzle: &[u8; 81]) -> Self {
+        Sudoku::build(Layout::standard(), &puzzle[..])
+    }
+
+    #[inline]
+    fn layout(&self) -> &Layout {
+        &self.layout
     }
 
     fn next_choice(&self) -> Vec<(usize, u8)> {
@@ -134,7 +305,7 @@ impl SudokuState for Sudoku {
     }
 
     #[inline]
-    fn iter(&self) -> PuzzleIter {
+    fn iter(&self) -> PuzzleIter<'_> {
         self.puzzle.iter()
     }
 
@@ -170,10 +341,10 @@ impl From<[u8; 81]> for Sudoku {
     }
 }
 
-impl Into<[u8; 81]> for Sudoku {
-    fn into(self) -> [u8; 81] {
+impl From<Sudoku> for [u8; 81] {
+    fn from(sudoku: Sudoku) -> [u8; 81] {
         let mut array = [0; 81];
-        for (i, cell_value) in self.into_iter().enumerate() {
+        for (i, cell_value) in sudoku.into_iter().enumerate() {
             array[i] = cell_value;
         }
         array
@@ -187,6 +358,7 @@ impl Clone for Sudoku {
     // parts of the data.
     fn clone(&self) -> Self {
         Sudoku {
+            layout:         self.layout.clone(),
             puzzle:         self.puzzle.clone(),
             remaining:      self.remaining,
 
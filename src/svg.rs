@@ -0,0 +1,661 @@
+use crate :: {
+    sudoku  :: { Sudoku, traits :: SudokuState },
+    formats :: escape,
+    format  :: symbol,
+    cages   :: Cage,
+    layout  :: Layout,
+    values  :: value_key :: SetType,
+    constraints :: { Thermometer, Arrow, Sandwich },
+};
+
+use std::fmt::Write;
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Theme {
+    pub background:     String,
+    pub lines:          String,
+    pub givens:         String,
+    pub solved:         String,
+    pub pencil_marks:   String,
+    pub highlight:      String,
+    pub diagonals:      String,
+    pub text:           String,
+    pub font_family:    String,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::light()
+    }
+}
+
+impl Theme {
+    pub fn light() -> Self {
+        Self {
+            background:     String::from("#ffffff"),
+            lines:          String::from("#222222"),
+            givens:         String::from("#000000"),
+            solved:         String::from("#1f5fbf"),
+            pencil_marks:   String::from("#666666"),
+            highlight:      String::from("#fff3b0"),
+            diagonals:      String::from("#eef2f8"),
+            text:           String::from("#222222"),
+            font_family:    String::from("sans-serif"),
+        }
+    }
+
+    pub fn dark() -> Self {
+        Self {
+            background:     String::from("#1e1e1e"),
+            lines:          String::from("#cccccc"),
+            givens:         String::from("#ffffff"),
+            solved:         String::from("#7fb2ff"),
+            pencil_marks:   String::from("#999999"),
+            highlight:      String::from("#4a4220"),
+            diagonals:      String::from("#2c3038"),
+            text:           String::from("#dddddd"),
+            font_family:    String::from("sans-serif"),
+        }
+    }
+
+    // Black and white only; solved digits are told apart from givens by weight alone
+    pub fn print() -> Self {
+        Self {
+            background:     String::from("#ffffff"),
+            lines:          String::from("#000000"),
+            givens:         String::from("#000000"),
+            solved:         String::from("#000000"),
+            pencil_marks:   String::from("#000000"),
+            highlight:      String::from("#e0e0e0"),
+            diagonals:      String::from("#f0f0f0"),
+            text:           String::from("#000000"),
+            font_family:    String::from("serif"),
+        }
+    }
+}
+
+// Draws the board of a layout, 9x9 unless told otherwise, with thick lines around each box or jigsaw region and along
+// the edges of every grid. Givens are drawn in bold, while any digits added on top of them (ex: a solution) use the
+// theme's solved color. Empty cells can optionally show pencil marks, and the diagonals of Sudoku X puzzles or the
+// windows of Hyper puzzles can be shaded. Killer Sudoku cages are outlined with dashed lines, with their sum written
+// in the corner of their top left cell. Thermometers are drawn as shaded tubes with a bulb, arrows as a line from a
+// circled cell, and sandwich sums just outside the grid, left of rows and above columns.
+pub struct SvgRenderer {
+    layout:         Layout,
+    theme:          Theme,
+    cell_size:      u32,
+    title:          Option<String>,
+    footer:         Option<String>,
+    highlights:     Vec<usize>,
+    diagonals:      bool,
+    windows:        bool,
+    cages:          Vec<Cage>,
+    thermometers:   Vec<Thermometer>,
+    arrows:         Vec<Arrow>,
+    sandwiches:     Vec<Sandwich>,
+    pencil_marks:   Option<Vec<Vec<u8>>>,
+}
+
+impl Default for SvgRenderer {
+    fn default() -> Self {
+        Self {
+            layout:         Layout::standard(),
+            theme:          Theme::default(),
+            cell_size:      40,
+            title:          None,
+            footer:         None,
+            highlights:     Vec::default(),
+            diagonals:      false,
+            windows:        false,
+            cages:          Vec::default(),
+            thermometers:   Vec::default(),
+            arrows:         Vec::default(),
+            sandwiches:     Vec::default(),
+            pencil_marks:   None,
+        }
+    }
+}
+
+impl SvgRenderer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Draws puzzles of the given layout, shading its diagonals and windows if it has any. Cells are numbered the same
+    // way as the layout's, and the image grows with its board.
+    pub fn layout(&mut self, layout: &Layout) -> &mut Self {
+        self.layout = layout.clone();
+        self.diagonals = layout.has_diagonals();
+        self.windows = layout.has_windows();
+
+        self
+    }
+
+    pub fn theme(&mut self, theme: Theme) -> &mut Self {
+        self.theme = theme;
+
+        self
+    }
+
+    // Size of a single cell in pixels; everything else (margins, lines, fonts) is scaled from it.
+    pub fn cell_size(&mut self, cell_size: u32) -> &mut Self {
+        self.cell_size = std::cmp::max(cell_size, 10);
+
+        self
+    }
+
+    pub fn title(&mut self, title: &str) -> &mut Self {
+        self.title = Some(title.to_string());
+
+        self
+    }
+
+    pub fn footer(&mut self, footer: &str) -> &mut Self {
+        self.footer = Some(footer.to_string());
+
+        self
+    }
+
+    // Cells past the last one of the layout are left out when drawing, as are the cages, thermometers, arrows and
+    // sandwiches below that reach outside of it
+    pub fn highlight(&mut self, cells: &[usize]) -> &mut Self {
+        self.highlights = cells.to_vec();
+
+        self
+    }
+
+    // Shades the cells of the layout's diagonals, for Sudoku X puzzles
+    pub fn diagonals(&mut self, shade: bool) -> &mut Self {
+        self.diagonals = shade;
+
+        self
+    }
+
+    // Shades the windows of Hyper layouts, in the same color as diagonals
+    pub fn windows(&mut self, shade: bool) -> &mut Self {
+        self.windows = shade;
+
+        self
+    }
+
+    // Outlines the given cages, ex: those of a Killer Sudoku layout
+    pub fn cages(&mut self, cages: &[Cage]) -> &mut Self {
+        self.cages = cages.to_vec();
+
+        self
+    }
+
+    pub fn thermometers(&mut self, thermometers: &[Thermometer]) -> &mut Self {
+        self.thermometers = thermometers.to_vec();
+
+        self
+    }
+
+    pub fn arrows(&mut self, arrows: &[Arrow]) -> &mut Self {
+        self.arrows = arrows.to_vec();
+
+        self
+    }
+
+    pub fn sandwiches(&mut self, sandwiches: &[Sandwich]) -> &mut Self {
+        self.sandwiches = sandwiches.to_vec();
+
+        self
+    }
+
+    // Expects one list of digits per cell; lists for cells that end up holding a value are ignored.
+    pub fn pencil_marks(&mut self, marks: &[Vec<u8>]) -> &mut Self {
+        self.pencil_marks = Some(marks.to_vec());
+
+        self
+    }
+
+    // Uses the candidates of the given state as pencil marks
+    pub fn candidates(&mut self, state: &Sudoku) -> &mut Self {
+        self.pencil_marks = Some((0 .. state.layout().cells()).map(|index| state.candidates(index)).collect());
+
+        self
+    }
+
+    // The board plus half a cell of margin on each side
+    pub fn width(&self) -> u32 {
+        let (_, cols) = self.layout.board_size();
+        self.cell_size * (cols as u32 + 1)
+    }
+
+    pub fn height(&self) -> u32 {
+        let (rows, _) = self.layout.board_size();
+        let mut height = self.cell_size * (rows as u32 + 1);
+        if self.title.is_some()  { height += self.cell_size; }
+        if self.footer.is_some() { height += self.cell_size * 2 / 3; }
+        height
+    }
+
+    // Takes the values of every cell of the layout, 0 for empty ones
+    pub fn render(&self, puzzle: &[u8], values: Option<&[u8]>) -> String {
+        let mut svg = String::new();
+
+        // Writing into a String can't fail
+        let _ = self.write(&mut svg, puzzle, values);
+
+        svg
+    }
+
+    // Writes the complete <svg> element, allowing several puzzles to be placed within a larger document
+    pub fn write<W: Write>(&self, writer: &mut W, puzzle: &[u8], values: Option<&[u8]>) -> std::fmt::Result {
+        let (width, height) = (self.width(), self.height());
+        let theme = &self.theme;
+
+        writeln!(
+            writer,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" viewBox="0 0 {0} {1}" font-family="{2}">"#,
+            width, height, escape(&theme.font_family)
+        )?;
+        writeln!(writer, r#"<rect width="{}" height="{}" fill="{}"/>"#, width, height, theme.background)?;
+
+        let margin = self.cell_size as f32 / 2.0;
+        let mut top = margin;
+
+        if let Some(title) = &self.title {
+            self.text(writer, width as f32 / 2.0, top + self.cell_size as f32 * 0.4, 0.6, &theme.text, title)?;
+            top += self.cell_size as f32;
+        }
+
+        self.grid(writer, margin, top, puzzle, values)?;
+
+        if let Some(footer) = &self.footer {
+            let (rows, _) = self.layout.board_size();
+            let y = top + self.cell_size as f32 * rows as f32 + margin + self.cell_size as f32 * 0.2;
+            self.text(writer, width as f32 / 2.0, y, 0.4, &theme.text, footer)?;
+        }
+
+        writeln!(writer, "</svg>")
+    }
+
+    fn grid<W: Write>(&self, writer: &mut W, left: f32, top: f32, puzzle: &[u8], values: Option<&[u8]>)
+    -> std::fmt::Result {
+        let size = self.cell_size as f32;
+        let theme = &self.theme;
+        let layout = &self.layout;
+
+        writeln!(writer, r#"<g transform="translate({} {})">"#, left, top)?;
+
+        let mut shaded: Vec<usize> = layout.houses()
+            .filter(|&(set_type, index)| match set_type {
+                SetType::Diag   => self.diagonals,
+                SetType::Hyper  => self.windows && is_window(layout, layout.house(set_type, index)),
+                _               => false,
+            })
+            .flat_map(|(set_type, index)| layout.house(set_type, index).iter().copied())
+            .collect();
+        shaded.sort_unstable();
+        shaded.dedup();
+
+        for index in shaded {
+            let (x, y) = self.position(index);
+            writeln!(
+                writer,
+                r#"<rect x="{}" y="{}" width="{2}" height="{2}" fill="{3}"/>"#,
+                x, y, size, theme.diagonals
+            )?;
+        }
+
+        for &index in self.highlights.iter().filter(|&&index| index < layout.cells()) {
+            let (x, y) = self.position(index);
+            writeln!(writer, r#"<rect x="{}" y="{}" width="{2}" height="{2}" fill="{3}"/>"#, x, y, size, theme.highlight)?;
+        }
+
+        for thermometer in self.thermometers.iter().filter(|thermometer| self.covers(thermometer.cells())) {
+            self.thermometer(writer, thermometer)?;
+        }
+
+        for arrow in self.arrows.iter().filter(|arrow| self.covers(&[arrow.circle()]) && self.covers(arrow.cells())) {
+            self.arrow(writer, arrow)?;
+        }
+
+        for cage in self.cages.iter().filter(|cage| self.covers(&cage.cells)) {
+            self.cage(writer, cage)?;
+        }
+
+        // Sums sit in the margin, next to the first cell of their row or column
+        for sandwich in self.sandwiches.iter() {
+            let first = match sandwich.cells(layout).first() {
+                Some(&first) => first,
+                None => continue,
+            };
+
+            let sum = sandwich.sum().to_string();
+            let (x, y) = self.position(first);
+            let (x, y) = match sandwich.row_index() {
+                Some(_) => (x - size / 4.0, y + size / 2.0),
+                None    => (x + size / 2.0, y - size / 4.0),
+            };
+
+            self.text(writer, x, y, 0.3, &theme.text, &sum)?;
+        }
+
+        // Pencil marks are laid out in a square of the digits, 3x3 on a 9x9 grid
+        let digits = layout.size();
+        let columns = (1 ..= digits).find(|columns| columns * columns >= digits).unwrap_or(1);
+        let rows = digits.div_ceil(columns);
+
+        for index in 0 .. layout.cells() {
+            let (x, y) = self.center(index);
+
+            let given = puzzle.get(index).copied().unwrap_or(0);
+            let value = values.map_or(given, |values| values.get(index).copied().unwrap_or(0));
+
+            if given > 0 {
+                self.digit(writer, (x, y), 0.6, &theme.givens, "bold", given)?;
+            }
+            else if value > 0 {
+                self.digit(writer, (x, y), 0.6, &theme.solved, "normal", value)?;
+            }
+            else if let Some(marks) = self.pencil_marks.as_ref().and_then(|marks| marks.get(index)) {
+                for &mark in marks.iter().filter(|&&mark| mark > 0 && usize::from(mark) <= digits) {
+                    let offset_x = (usize::from(mark - 1) % columns) as f32 - (columns - 1) as f32 / 2.0;
+                    let offset_y = (usize::from(mark - 1) / columns) as f32 - (rows - 1) as f32 / 2.0;
+                    let step = size * 0.9 / columns as f32;
+                    let (x, y) = (x + offset_x * step, y + offset_y * step);
+
+                    self.digit(writer, (x, y), 0.75 / columns as f32, &theme.pencil_marks, "normal", mark)?;
+                }
+            }
+        }
+
+        // Each side between two cells is drawn once, from the cell below or to the right of it. Sides along the edge
+        // of a grid, or between cells that don't share a box or jigsaw region, are drawn thick.
+        let thin = (size / 40.0).max(1.0);
+        let mut thin_path = String::new();
+        let mut thick_path = String::new();
+
+        for index in 0 .. layout.cells() {
+            let (x, y) = self.position(index);
+            // The offset to the cell across each side, where the side starts, and whether it runs across
+            let sides = [
+                ((-1, 0), (x, y), true),
+                ((0, -1), (x, y), false),
+                ((1, 0), (x, y + size), true),
+                ((0, 1), (x + size, y), false),
+            ];
+
+            for ((rows, cols), (x, y), across) in sides {
+                let thick = match layout.offset(index, rows, cols) {
+                    Some(_) if rows > 0 || cols > 0 => continue,
+                    Some(other) => !same_box(layout, index, other),
+                    None => true,
+                };
+
+                let path = if thick { &mut thick_path } else { &mut thin_path };
+                match across {
+                    true  => write!(path, "M{} {}H{}", x, y, x + size)?,
+                    false => write!(path, "M{} {}V{}", x, y, y + size)?,
+                }
+            }
+        }
+
+        writeln!(writer, r#"<path d="{}" stroke="{}" stroke-width="{}"/>"#, thin_path, theme.lines, thin)?;
+        writeln!(
+            writer,
+            r#"<path d="{}" stroke="{}" stroke-width="{}" stroke-linecap="square"/>"#,
+            thick_path, theme.lines, thin * 3.0
+        )?;
+
+        writeln!(writer, "</g>")
+    }
+
+    // The tube and bulb are drawn solid within a translucent group, so they don't darken where they overlap
+    fn thermometer<W: Write>(&self, writer: &mut W, thermometer: &Thermometer) -> std::fmt::Result {
+        let size = self.cell_size as f32;
+        let (bulb, _) = match thermometer.cells().split_first() {
+            Some(split) => split,
+            None => return Ok(()),
+        };
+
+        let (x, y) = self.center(*bulb);
+        let mut path = format!("M{} {}", x, y);
+        for &index in thermometer.cells().iter().skip(1) {
+            let (x, y) = self.center(index);
+            write!(path, "L{} {}", x, y)?;
+        }
+
+        writeln!(writer, r#"<g opacity="0.2">"#)?;
+        writeln!(writer, r#"<circle cx="{}" cy="{}" r="{}" fill="{}"/>"#, x, y, size * 0.35, self.theme.lines)?;
+        writeln!(
+            writer,
+            r#"<path d="{}" fill="none" stroke="{}" stroke-width="{}" stroke-linecap="round" stroke-linejoin="round"/>"#,
+            path, self.theme.lines, size * 0.3
+        )?;
+        writeln!(writer, "</g>")
+    }
+
+    // The line starts at the edge of the circle, and ends with a head pointing the way of its last step
+    fn arrow<W: Write>(&self, writer: &mut W, arrow: &Arrow) -> std::fmt::Result {
+        let size = self.cell_size as f32;
+        let radius = size * 0.4;
+        let stroke = (size / 40.0).max(1.0);
+
+        let (x, y) = self.center(arrow.circle());
+        writeln!(
+            writer,
+            r#"<circle cx="{}" cy="{}" r="{}" fill="none" stroke="{}" stroke-width="{}"/>"#,
+            x, y, radius, self.theme.lines, stroke
+        )?;
+
+        let points: Vec<(f32, f32)> = arrow.cells().iter().map(|&index| self.center(index)).collect();
+        let (first, last) = match (points.first(), points.last()) {
+            (Some(&first), Some(&last)) => (first, last),
+            _ => return Ok(()),
+        };
+
+        let (dx, dy) = (first.0 - x, first.1 - y);
+        let length = dx.hypot(dy).max(f32::EPSILON);
+        let mut path = format!("M{} {}", x + dx / length * radius, y + dy / length * radius);
+        for &(x, y) in points.iter() {
+            write!(path, "L{} {}", x, y)?;
+        }
+
+        let before = if points.len() > 1 { points[points.len() - 2] } else { (x, y) };
+        let angle = (last.1 - before.1).atan2(last.0 - before.0);
+        for side in [-0.6f32, 0.6] {
+            let (sin, cos) = (angle + std::f32::consts::PI + side).sin_cos();
+            write!(path, "M{} {}l{} {}", last.0, last.1, cos * size * 0.25, sin * size * 0.25)?;
+        }
+
+        writeln!(
+            writer,
+            r#"<path d="{}" fill="none" stroke="{}" stroke-width="{}" stroke-linecap="round" stroke-linejoin="round"/>"#,
+            path, self.theme.lines, stroke
+        )
+    }
+
+    // Cage outlines are inset from the cell borders, so each side is drawn up to where it meets the next one: short of
+    // the cell's corner on the outside of a bend, past it on the inside of one.
+    fn cage<W: Write>(&self, writer: &mut W, cage: &Cage) -> std::fmt::Result {
+        let size = self.cell_size as f32;
+        let inset = size * 0.08;
+        let theme = &self.theme;
+
+        let reach = |side: bool, corner: bool| match (side, corner) {
+            (false, _)      => inset,
+            (true, true)    => -inset,
+            (true, false)   => 0.0,
+        };
+
+        let mut path = String::new();
+
+        for &index in cage.cells.iter() {
+            let inside = |dx: isize, dy: isize| {
+                self.layout.offset(index, dy, dx).is_some_and(|other| cage.cells.contains(&other))
+            };
+
+            let (x, y) = self.position(index);
+            let (right, bottom) = (x + size, y + size);
+            let (up, down, left, across) = (inside(0, -1), inside(0, 1), inside(-1, 0), inside(1, 0));
+
+            if !up {
+                let (start, end) = (x + reach(left, inside(-1, -1)), right - reach(across, inside(1, -1)));
+                write!(path, "M{} {}H{}", start, y + inset, end)?;
+            }
+            if !down {
+                let (start, end) = (x + reach(left, inside(-1, 1)), right - reach(across, inside(1, 1)));
+                write!(path, "M{} {}H{}", start, bottom - inset, end)?;
+            }
+            if !left {
+                let (start, end) = (y + reach(up, inside(-1, -1)), bottom - reach(down, inside(-1, 1)));
+                write!(path, "M{} {}V{}", x + inset, start, end)?;
+            }
+            if !across {
+                let (start, end) = (y + reach(up, inside(1, -1)), bottom - reach(down, inside(1, 1)));
+                write!(path, "M{} {}V{}", right - inset, start, end)?;
+            }
+        }
+
+        let dash = size / 10.0;
+        writeln!(
+            writer,
+            r#"<path d="{}" fill="none" stroke="{}" stroke-width="{}" stroke-dasharray="{3} {3}"/>"#,
+            path, theme.lines, (size / 40.0).max(1.0), dash
+        )?;
+
+        // The sum sits on a patch of background, so the outline doesn't run through it
+        let (x, y) = self.position(cage.anchor());
+        let sum = cage.sum.to_string();
+        let width = size * (0.14 * sum.len() as f32 + 0.04);
+        writeln!(
+            writer,
+            r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+            x + size * 0.04, y + size * 0.04, width, size * 0.26, theme.background
+        )?;
+        writeln!(
+            writer,
+            r#"<text x="{}" y="{}" font-size="{}" fill="{}" dominant-baseline="hanging">{}</text>"#,
+            x + size * 0.06, y + size * 0.06, size * 0.22, theme.text, sum
+        )
+    }
+
+    fn digit<W: Write>(&self, writer: &mut W, (x, y): (f32, f32), scale: f32, color: &str, weight: &str, digit: u8)
+    -> std::fmt::Result {
+        writeln!(
+            writer,
+            r#"<text x="{}" y="{}" font-size="{}" font-weight="{}" fill="{}" text-anchor="middle" dominant-baseline="central">{}</text>"#,
+            x, y, self.cell_size as f32 * scale, weight, color, symbol(digit)
+        )
+    }
+
+    fn text<W: Write>(&self, writer: &mut W, x: f32, y: f32, scale: f32, color: &str, text: &str) -> std::fmt::Result {
+        writeln!(
+            writer,
+            r#"<text x="{}" y="{}" font-size="{}" fill="{}" text-anchor="middle" dominant-baseline="central">{}</text>"#,
+            x, y, self.cell_size as f32 * scale, color, escape(text)
+        )
+    }
+
+    // Top left corner of the cell, from the top left corner of the board
+    fn position(&self, index: usize) -> (f32, f32) {
+        let (row, col) = self.layout.position(index);
+        let size = self.cell_size as f32;
+
+        (col as f32 * size, row as f32 * size)
+    }
+
+    fn center(&self, index: usize) -> (f32, f32) {
+        let (x, y) = self.position(index);
+        let size = self.cell_size as f32;
+
+        (x + size / 2.0, y + size / 2.0)
+    }
+
+    // Whether all of the cells are in the layout
+    fn covers(&self, cells: &[usize]) -> bool {
+        cells.iter().all(|&index| index < self.layout.cells())
+    }
+}
+
+// Cells of the same box or jigsaw region. Overlapping layouts keep a single box where grids share one.
+fn same_box(layout: &Layout, cell: usize, other: usize) -> bool {
+    layout.memberships(cell).iter()
+        .any(|&(set_type, index)| set_type == SetType::Box && layout.house(set_type, index).contains(&other))
+}
+
+// Hyper houses also hold the regions the windows imply, which are scattered across the grid where the windows are
+// solid blocks of cells
+fn is_window(layout: &Layout, cells: &[usize]) -> bool {
+    let positions: Vec<(usize, usize)> = cells.iter().map(|&cell| layout.position(cell)).collect();
+    let rows = positions.iter().map(|&(row, _)| row);
+    let cols = positions.iter().map(|&(_, col)| col);
+
+    let height = rows.clone().max().unwrap_or(0) - rows.min().unwrap_or(0) + 1;
+    let width = cols.clone().max().unwrap_or(0) - cols.min().unwrap_or(0) + 1;
+
+    width * height == cells.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The path of the thick lines around boxes and grids
+    fn thick_lines(svg: &str) -> &str {
+        svg.lines().find(|line| line.contains(r#"stroke-linecap="square""#)).unwrap()
+    }
+
+    fn shaded(svg: &str, theme: &Theme) -> usize {
+        svg.matches(&format!(r#"fill="{}""#, theme.diagonals)).count()
+    }
+
+    #[test]
+    fn the_image_follows_the_board() {
+        let mut renderer = SvgRenderer::new();
+        renderer.cell_size(10).layout(&Layout::samurai());
+
+        assert_eq!((renderer.width(), renderer.height()), (220, 220));
+
+        let values: Vec<u8> = (0 .. 369).map(|index| (index % 9 + 1) as u8).collect();
+        let svg = renderer.render(&vec![0; 369], Some(&values));
+
+        assert_eq!(svg.matches("<text").count(), 369);
+        assert!(thick_lines(&svg).contains("M60 90V100"));
+    }
+
+    #[test]
+    fn digits_past_9_are_letters() {
+        let layout = Layout::new(4, 4).unwrap();
+        let mut cells = vec![0; 256];
+        cells[0] = 16;
+
+        let svg = SvgRenderer::new().layout(&layout).render(&cells, None);
+
+        assert!(svg.contains(">G</text>"));
+    }
+
+    #[test]
+    fn diagonals_and_windows_come_from_the_layout() {
+        let theme = Theme::default();
+
+        let diagonals = SvgRenderer::new().layout(&Layout::with_diagonals(3, 3).unwrap()).render(&[0; 81], None);
+        assert_eq!(shaded(&diagonals, &theme), 17);
+
+        let windows = SvgRenderer::new().layout(&Layout::standard().with_windows().unwrap()).render(&[0; 81], None);
+        assert_eq!(shaded(&windows, &theme), 36);
+
+        let layout = Layout::new(2, 2).unwrap().with_windows().unwrap();
+        let windows = SvgRenderer::new().layout(&layout).render(&[0; 16], None);
+        assert_eq!(shaded(&windows, &theme), 4);
+
+        let plain = SvgRenderer::new().diagonals(true).windows(true).render(&[0; 81], None);
+        assert_eq!(shaded(&plain, &theme), 0);
+    }
+
+    #[test]
+    fn jigsaw_regions_are_outlined() {
+        let layout = Layout::jigsaw(&[1, 2, 2, 2, 1, 1, 1, 2, 3, 3, 4, 4, 3, 3, 4, 4]).unwrap();
+        let svg = SvgRenderer::new().layout(&layout).render(&[0; 16], None);
+        let thick = thick_lines(&svg);
+
+        assert!(thick.contains("M40 0V40"));
+        assert!(thick.contains("M40 40H80"));
+        assert!(!thick.contains("M80 0V40"));
+    }
+}
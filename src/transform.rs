@@ -0,0 +1,250 @@
+use crate :: random :: traits :: Random;
+
+const PERMUTATIONS: [[usize; 3]; 6] = [
+    [0, 1, 2], [0, 2, 1], [1, 0, 2], [1, 2, 0], [2, 0, 1], [2, 1, 0]
+];
+
+// One of the symmetries of a sudoku grid. Relabeling digits, transposing, reordering bands and stacks, and reordering
+// rows or columns within a band or stack all turn a puzzle into an equivalent one: it has the same number of solutions,
+// and is solved using the same steps.
+//
+// The cell at (row, col) of the output is taken from (rows[row], columns[col]) of the input, after it is transposed.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub struct Transform {
+    transpose:  bool,
+    rows:       [usize; 9],
+    columns:    [usize; 9],
+    digits:     [u8; 10],
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl Transform {
+    pub fn identity() -> Self {
+        Self {
+            transpose:  false,
+            rows:       [0, 1, 2, 3, 4, 5, 6, 7, 8],
+            columns:    [0, 1, 2, 3, 4, 5, 6, 7, 8],
+            digits:     [0, 1, 2, 3, 4, 5, 6, 7, 8, 9],
+        }
+    }
+
+    pub fn transpose() -> Self {
+        Self { transpose: true, ..Self::identity() }
+    }
+
+    // Rotates the grid 90 degrees clockwise
+    pub fn rotate() -> Self {
+        Self { transpose: true, columns: [8, 7, 6, 5, 4, 3, 2, 1, 0], ..Self::identity() }
+    }
+
+    // Mirrors the grid left to right
+    pub fn flip_horizontal() -> Self {
+        Self { columns: [8, 7, 6, 5, 4, 3, 2, 1, 0], ..Self::identity() }
+    }
+
+    // Mirrors the grid top to bottom
+    pub fn flip_vertical() -> Self {
+        Self { rows: [8, 7, 6, 5, 4, 3, 2, 1, 0], ..Self::identity() }
+    }
+
+    // Digits are given in the order they replace 1 through 9, ex: "912345678" turns every 1 into a 9 and every 2 into a
+    // 1. Returns None if the digits are not a permutation of 1 through 9.
+    pub fn relabel(digits: &[u8; 9]) -> Option<Self> {
+        let mut mapping = [0; 10];
+
+        for (i, &digit) in digits.iter().enumerate() {
+            if digit == 0 || digit > 9 || mapping[1..].contains(&digit) {
+                return None;
+            }
+            mapping[i + 1] = digit;
+        }
+
+        Some(Self { digits: mapping, ..Self::identity() })
+    }
+
+    // Rows are swapped either as whole bands (0-2) or, when both fall within the same band, as individual rows (0-8).
+    // Returns None for rows in different bands, which would not result in an equivalent puzzle.
+    pub fn swap_rows(a: usize, b: usize) -> Option<Self> {
+        Some(Self { rows: swapped(a, b)?, ..Self::identity() })
+    }
+
+    pub fn swap_bands(a: usize, b: usize) -> Option<Self> {
+        Some(Self { rows: swapped_groups(a, b)?, ..Self::identity() })
+    }
+
+    pub fn swap_columns(a: usize, b: usize) -> Option<Self> {
+        Some(Self { columns: swapped(a, b)?, ..Self::identity() })
+    }
+
+    pub fn swap_stacks(a: usize, b: usize) -> Option<Self> {
+        Some(Self { columns: swapped_groups(a, b)?, ..Self::identity() })
+    }
+
+    // Any one of the 2 * 6^8 * 9! equivalent grids, picked at random
+    pub fn random() -> Self {
+        let pick = || *PERMUTATIONS.iter().random().next().unwrap();
+        let transpose = [false, true].iter().random().next().copied().unwrap_or(false);
+
+        let mut digits = [0; 10];
+        for (i, &digit) in (1..10u8).collect::<Vec<u8>>().iter().random().enumerate() {
+            digits[i + 1] = digit;
+        }
+
+        Self {
+            transpose,
+            rows:       lines(&pick(), &[pick(), pick(), pick()]),
+            columns:    lines(&pick(), &[pick(), pick(), pick()]),
+            digits
+        }
+    }
+
+    // The transform that turns the given puzzle into its canonical form, see canonicalize.
+    pub fn canonical(puzzle: &[u8; 81]) -> Self {
+        let mut best = [10; 81];
+        let mut best_transform = Self::identity();
+
+        for &transpose in [false, true].iter() {
+            let source = Self { transpose, ..Self::identity() }.apply(puzzle);
+
+            for rows in arrangements() {
+                for columns in arrangements() {
+                    if let Some(digits) = relabeled(&source, &rows, &columns, &mut best) {
+                        best_transform = Self { transpose, rows, columns, digits };
+                    }
+                }
+            }
+        }
+
+        best_transform
+    }
+
+    pub fn apply(&self, puzzle: &[u8; 81]) -> [u8; 81] {
+        let mut output = [0; 81];
+
+        for (index, cell) in output.iter_mut().enumerate() {
+            let (row, col) = (self.rows[index / 9], self.columns[index % 9]);
+            let source = if self.transpose { col * 9 + row } else { row * 9 + col };
+
+            *cell = self.digits.get(puzzle[source] as usize).copied().unwrap_or(0);
+        }
+
+        output
+    }
+
+    // Combines two transforms into one that applies this transform first, followed by the next one.
+    pub fn then(&self, next: &Transform) -> Transform {
+        let mut combined = Self::identity();
+
+        for i in 0..9 {
+            if next.transpose {
+                combined.rows[i]    = self.columns[next.rows[i]];
+                combined.columns[i] = self.rows[next.columns[i]];
+            }
+            else {
+                combined.rows[i]    = self.rows[next.rows[i]];
+                combined.columns[i] = self.columns[next.columns[i]];
+            }
+        }
+
+        for digit in 0..10 {
+            combined.digits[digit] = next.digits[self.digits[digit] as usize];
+        }
+
+        combined.transpose = self.transpose != next.transpose;
+        combined
+    }
+}
+
+// The smallest of all equivalent puzzles, comparing the 81 cells as digits with empty cells written as 0, and with the
+// digits relabeled in order of first appearance. Two puzzles are equivalent if and only if their canonical forms match.
+pub fn canonicalize(puzzle: &[u8; 81]) -> [u8; 81] {
+    Transform::canonical(puzzle).apply(puzzle)
+}
+
+// Every ordering of rows (or columns) that keeps each band together: 6 orders of the bands, times 6 orders of the rows
+// within each of the three bands.
+fn arrangements() -> impl Iterator<Item = [usize; 9]> {
+    PERMUTATIONS.iter().flat_map(|bands| {
+        PERMUTATIONS.iter().flat_map(move |a| {
+            PERMUTATIONS.iter().flat_map(move |b| {
+                PERMUTATIONS.iter().map(move |c| lines(bands, &[*a, *b, *c]))
+            })
+        })
+    })
+}
+
+#[inline]
+fn lines(groups: &[usize; 3], within: &[[usize; 3]; 3]) -> [usize; 9] {
+    let mut lines = [0; 9];
+    for (i, line) in lines.iter_mut().enumerate() {
+        *line = groups[i / 3] * 3 + within[i / 3][i % 3];
+    }
+    lines
+}
+
+// Relabels the rearranged puzzle in order of first appearance, and replaces best if the result is smaller. Stops as
+// soon as the result is known to be larger, which is the case for the vast majority of arrangements.
+fn relabeled(source: &[u8; 81], rows: &[usize; 9], columns: &[usize; 9], best: &mut [u8; 81]) -> Option<[u8; 10]> {
+    let mut digits = [0; 10];
+    let mut next = 0;
+    let mut output = [0; 81];
+    let mut smaller = false;
+
+    for (index, cell) in output.iter_mut().enumerate() {
+        let value = source[rows[index / 9] * 9 + columns[index % 9]] as usize;
+
+        if value > 0 && digits[value] == 0 {
+            next += 1;
+            digits[value] = next;
+        }
+        *cell = digits[value];
+
+        if !smaller {
+            match (*cell).cmp(&best[index]) {
+                std::cmp::Ordering::Less    => smaller = true,
+                std::cmp::Ordering::Greater => return None,
+                std::cmp::Ordering::Equal   => ()
+            }
+        }
+    }
+
+    if !smaller {
+        return None;
+    }
+
+    // Digits that don't appear in the puzzle still need a label for the transform to be a complete relabeling
+    for digit in digits.iter_mut().skip(1).filter(|digit| **digit == 0) {
+        next += 1;
+        *digit = next;
+    }
+
+    *best = output;
+    Some(digits)
+}
+
+fn swapped(a: usize, b: usize) -> Option<[usize; 9]> {
+    if a > 8 || b > 8 || a / 3 != b / 3 {
+        return None;
+    }
+
+    let mut lines = Transform::identity().rows;
+    lines.swap(a, b);
+    Some(lines)
+}
+
+fn swapped_groups(a: usize, b: usize) -> Option<[usize; 9]> {
+    if a > 2 || b > 2 {
+        return None;
+    }
+
+    let mut lines = Transform::identity().rows;
+    for i in 0..3 {
+        lines.swap(a * 3 + i, b * 3 + i);
+    }
+    Some(lines)
+}
@@ -1,7 +1,8 @@
 use crate::{
+    sudoku      :: traits :: SudokuState,
     candidates  :: { CandidateSet, traits :: CandidatesRead },
     bitsets     :: traits::BitSet,
-    indices,
+    layout      :: Layout,
 };
 use self::{ value_key::*, traits::* };
 
@@ -10,13 +11,15 @@ pub type LocationSet = std::collections::HashSet<usize>;
 pub mod traits {
     use super::*;
 
-    pub trait ValueBase: ValuesRead + ValuesModify + CandidatesRead { }
+    pub trait ValueBase: SudokuState + ValuesRead + ValuesModify + CandidatesRead { }
 
     pub trait ValuesRead {
+        #[allow(dead_code)]
         fn get(&self, key: &ValueKey) -> Option<&LocationSet>;
     }
 
     pub trait ValuesModify {
+        #[allow(dead_code)]
         fn insert(&mut self, key: ValueKey, values: LocationSet) -> Option<LocationSet>;
         fn insert_into(&mut self, key: ValueKey, value: usize) -> Option<&LocationSet>;
 
@@ -27,19 +30,23 @@ pub mod traits {
     pub trait Values {
         fn init_values(&mut self);
         fn update_values(&mut self, index: usize, new_value: u8, old_value: u8);
+        fn exclude_value(&mut self, index: usize, value: u8);
+        fn restore_values(&mut self, index: usize);
     }
 }
 
 impl<T> Values for T where T: ValueBase {
     fn init_values(&mut self) {
-        for i in 0..9 {
-            populate(self, SetType::Row, i);
-            populate(self, SetType::Col, i);
-            populate(self, SetType::Box, i);
+        let layout = self.layout().clone();
+
+        for (set_type, set_index) in layout.houses() {
+            populate(self, &layout, set_type, set_index);
         }
     }
 
     fn update_values(&mut self, index: usize, new_value: u8, old_value: u8) {
+        let layout = self.layout().clone();
+
         if new_value == 0 {
             // For each allowed candidate at this index insert the index to the corresponding value map
             insert_all(self, index);
@@ -47,14 +54,14 @@ impl<T> Values for T where T: ValueBase {
             // The remaining cells in this row, column, and box can now potentially contain the value that is being
             // removed. But this depends on the other values in the rows, columns, and boxes that the other cells are
             // associated with. Never the less, we must handle this:
-            for &key in rcb_value_key(index, old_value).iter() {
+            for key in rcb_value_key(&layout, index, old_value) {
                 insert_for(self, key);
             }
         }
         else {
-            // Since every index is associated with three value records (Row, Column and Box), get the appropriate keys 
-            // and perform updates for each of them
-            for &key in rcb_value_key(index, new_value).iter() {
+            // Since every index is associated with a value record for each of its houses (Row, Column, Box and any
+            // Diagonal or Hyper window), get the appropriate keys and perform updates for each of them
+            for key in rcb_value_key(&layout, index, new_value) {
                 remove_for(self, key);
             }
 
@@ -65,16 +72,28 @@ impl<T> Values for T where T: ValueBase {
             remove_all(self, index);
         }
     }
-}
 
-fn populate<T: ValueBase>(context: &mut T, set_type: SetType, set_index: usize) {
-    let set_indices = match set_type {
-        SetType::Row => indices::row_at(set_index),
-        SetType::Col => indices::col_at(set_index),
-        SetType::Box => indices::box_at(set_index)
-    };
+    // The counterpart of Candidates::exclude_candidate: the cell is no longer a possible location for the value within
+    // any of its Row, Column and Box records.
+    fn exclude_value(&mut self, index: usize, value: u8) {
+        let layout = self.layout().clone();
+
+        for key in rcb_value_key(&layout, index, value) {
+            if let Some(remaining) = self.remove_from(&key, index) {
+                if remaining.is_empty() { self.remove(&key); }
+            }
+        }
+    }
+
+    // The counterpart of Candidates::restore_candidates: the cell is a possible location for each of its candidates
+    // again, within every house it belongs to.
+    fn restore_values(&mut self, index: usize) {
+        insert_all(self, index);
+    }
+}
 
-    for &index in set_indices.iter() {
+fn populate<T: ValueBase>(context: &mut T, layout: &Layout, set_type: SetType, set_index: usize) {
+    for &index in layout.house(set_type, set_index) {
         let candidate_set = match CandidatesRead::get(context, index) {
             Some(set) => *set,
             None => continue
@@ -87,13 +106,9 @@ fn populate<T: ValueBase>(context: &mut T, set_type: SetType, set_index: usize)
 }
 
 fn insert_for<T: ValueBase>(context: &mut T, key: ValueKey) {
-    let affected = match key.set_type {
-        SetType::Row => (indices::row_at(key.set_index), [SetType::Col, SetType::Box]),
-        SetType::Col => (indices::col_at(key.set_index), [SetType::Row, SetType::Box]),
-        SetType::Box => (indices::box_at(key.set_index), [SetType::Row, SetType::Col]),
-    };
+    let layout = context.layout().clone();
 
-    for &index in affected.0.iter() {
+    for &index in layout.house(key.set_type, key.set_index) {
         let candidate_set = match CandidatesRead::get(context, index) {
             Some(set) => *set,
             None => continue
@@ -105,17 +120,11 @@ fn insert_for<T: ValueBase>(context: &mut T, key: ValueKey) {
 
         context.insert_into(key, index);
 
-        for &set_type in affected.1.iter() {
-            let associated_key = ValueKey {
-                value: key.value,
-                set_type,
-                set_index:  match set_type {
-                    SetType::Row => indices::row_index(index),
-                    SetType::Col => indices::col_index(index),
-                    SetType::Box => indices::box_index(index)
-                }
-            };
-            context.insert_into(associated_key, index);
+        // Along with every other house the cell belongs to
+        for &(set_type, set_index) in layout.memberships(index) {
+            if set_type != key.set_type || set_index != key.set_index {
+                context.insert_into(ValueKey { value: key.value, set_type, set_index }, index);
+            }
         }
     }
 }
@@ -129,24 +138,16 @@ fn remove_for<T: ValueBase>(context: &mut T, key: ValueKey) {
     };
 
     // Next, for each of the locations where the value could have been present within this collection, we need to
-    // update the other two types of collections and remove those specific indices.
-    let associated_sets = match key.set_type {
-        SetType::Row => [SetType::Col, SetType::Box],
-        SetType::Col => [SetType::Row, SetType::Box],
-        SetType::Box => [SetType::Row, SetType::Col]
-    };
+    // update the other houses containing them and remove those specific indices.
+    let layout = context.layout().clone();
 
     for &cell_index in location_set.iter() {
-        for &set_type in associated_sets.iter() {
-            let key = ValueKey {
-                value: key.value,
-                set_type, 
-                set_index:  match set_type {
-                    SetType::Row => indices::row_index(cell_index),
-                    SetType::Col => indices::col_index(cell_index),
-                    SetType::Box => indices::box_index(cell_index)
-                }
-            };
+        for &(set_type, set_index) in layout.memberships(cell_index) {
+            if set_type == key.set_type && set_index == key.set_index {
+                continue;
+            }
+
+            let key = ValueKey { value: key.value, set_type, set_index };
 
             match context.remove_from(&key, cell_index) {
                 Some(remaining) => if remaining.is_empty() { context.remove(&key); },
@@ -163,9 +164,11 @@ fn insert_all<T: ValueBase>(context: &mut T, index: usize) {
         None => return
     };
 
+    let layout = context.layout().clone();
+
     for candidate in candidate_set.iter() {
-        for key in rcb_value_key(index, candidate.into()).iter() {
-            context.insert_into(*key, index);
+        for key in rcb_value_key(&layout, index, candidate.into()) {
+            context.insert_into(key, index);
         }
     }
 }
@@ -177,27 +180,33 @@ fn remove_all<T: ValueBase>(context: &mut T, index: usize) {
         None => return
     };
 
+    let layout = context.layout().clone();
+
     for candidate in candidate_set.iter() {
-        for key in rcb_value_key(index, candidate.into()).iter() {
-            match context.remove_from(key, index) {
-                Some(remaining) => if remaining.is_empty() { context.remove(key); },
+        for key in rcb_value_key(&layout, index, candidate.into()) {
+            match context.remove_from(&key, index) {
+                Some(remaining) => if remaining.is_empty() { context.remove(&key); },
                 None => continue
             }
         }
     }
 }
 
+// The keys of every house (Row, Column, Box, Diagonal and Hyper window) containing the cell
 #[inline]
-fn rcb_value_key(cell_index: usize, value: u8) -> [ValueKey; 3] {[ 
-    ValueKey { value, set_type: SetType::Row, set_index: indices::row_index(cell_index) }, 
-    ValueKey { value, set_type: SetType::Col, set_index: indices::col_index(cell_index) }, 
-    ValueKey { value, set_type: SetType::Box, set_index: indices::box_index(cell_index) }
-]}
+fn rcb_value_key(layout: &Layout, cell_index: usize, value: u8) -> impl Iterator<Item = ValueKey> + '_ {
+    layout.memberships(cell_index).iter()
+        .map(move |&(set_type, set_index)| ValueKey { value, set_type, set_index })
+}
 
 pub mod value_key {
     #[repr(u8)]
     #[derive(PartialEq, Eq, Ord, PartialOrd, Hash, Copy, Clone, Debug)]
-    pub enum SetType { Row, Col, Box }
+    pub enum SetType { Row, Col, Box, Diag, Hyper }
+
+    impl SetType {
+        pub const ALL: [SetType; 5] = [SetType::Row, SetType::Col, SetType::Box, SetType::Diag, SetType::Hyper];
+    }
 
     impl ::core::fmt::Display for SetType {
         fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
@@ -205,7 +214,9 @@ pub mod value_key {
                 match &self { 
                     SetType::Row => "R",
                     SetType::Col => "C",
-                    SetType::Box => "B"
+                    SetType::Box => "B",
+                    SetType::Diag => "D",
+                    SetType::Hyper => "H"
                 }
             )
         }
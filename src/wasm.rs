@@ -1,4 +1,4 @@
-use crate :: { Generator, Solver };
+use crate :: { Generator, Solver, parse_puzzle, codec };
 
 pub fn set_panic_hook() {
     #[cfg(feature = "console_error_panic_hook")]
@@ -40,6 +40,47 @@ pub fn solve(puzzle: &[u8], config: Option<SolverConfig>) -> Result<SolverOutput
 }
 
 #[wasm_bindgen]
+pub fn parse(input: &str) -> Result<Box<[u8]>, JsValue> {
+    match parse_puzzle(input) {
+        Ok(puzzle) => Ok(Box::from(puzzle)),
+        Err(err) => Err(JsValue::from_str(&err.to_string()))
+    }
+}
+
+// Packs a puzzle, and optionally its solution, into a short URL safe string for share links
+#[wasm_bindgen]
+pub fn encode_puzzle(puzzle: &[u8], solution: Option<Box<[u8]>>) -> Result<String, JsValue> {
+    let puzzle = to_grid(puzzle)?;
+    let solution = match solution {
+        Some(solution) => Some(to_grid(&solution)?),
+        None => None
+    };
+
+    codec::CompactPuzzle::new(puzzle, solution)
+        .to_base64()
+        .map_err(|err| JsValue::from_str(&err.to_string()))
+}
+
+#[wasm_bindgen]
+pub fn decode_puzzle(text: &str) -> Result<CompactPuzzle, JsValue> {
+    match codec::CompactPuzzle::from_base64(text) {
+        Ok(compact) => Ok(CompactPuzzle(compact)),
+        Err(err) => Err(JsValue::from_str(&err.to_string()))
+    }
+}
+
+fn to_grid(cells: &[u8]) -> Result<[u8; 81], JsValue> {
+    if cells.len() != 81 {
+        return Err(JsValue::from_str("Invalid input"));
+    }
+
+    let mut grid = [0; 81];
+    grid.copy_from_slice(cells);
+    Ok(grid)
+}
+
+#[wasm_bindgen]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SolverConfig { 
     pub limit: usize 
 }
@@ -59,6 +100,7 @@ impl SolverConfig {
 }
 
 #[wasm_bindgen]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GeneratorConfig {
     pub samples:    u8,
     pub iterations: u8,
@@ -105,6 +147,22 @@ impl GeneratorOutput {
 }
 
 
+#[wasm_bindgen]
+pub struct CompactPuzzle(codec::CompactPuzzle);
+
+#[wasm_bindgen]
+impl CompactPuzzle {
+    #[wasm_bindgen(getter)]
+    pub fn puzzle(&self) -> Box<[u8]> {
+        Box::from(self.0.puzzle)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn solution(&self) -> Option<Box<[u8]>> {
+        self.0.solution.map(Box::from)
+    }
+}
+
 #[wasm_bindgen]
 pub struct SolutionRecord(crate::solver::output::SolutionRecord);
 
@@ -152,3 +210,165 @@ impl SolverOutput {
         output
     }
 }
+
+// A puzzle being played, so the web client can leave entries, pencil marks, undo and redo and mistake checks to the
+// library. Methods changing the board throw a string describing the problem for a given cell or an invalid digit.
+#[wasm_bindgen]
+pub struct Game(crate::game::Game);
+
+#[wasm_bindgen]
+impl Game {
+    // Throws if the puzzle doesn't have a unique solution
+    #[wasm_bindgen(constructor)]
+    pub fn new(puzzle: &[u8]) -> Result<Game, JsValue> {
+        match crate::game::Game::new(&to_grid(puzzle)?) {
+            Ok(game) => Ok(Game(game)),
+            Err(err) => Err(JsValue::from_str(&err.to_string()))
+        }
+    }
+
+    pub fn from_generated(output: &GeneratorOutput) -> Game {
+        Game(crate::game::Game::with_solution(&output.0.puzzle, &output.0.solution))
+    }
+
+    // Restores a session written by save, from either Rust or WASM
+    pub fn load(text: &str) -> Result<Game, JsValue> {
+        match crate::game::Game::from_base64(text) {
+            Ok(game) => Ok(Game(game)),
+            Err(err) => Err(JsValue::from_str(&err.to_string()))
+        }
+    }
+
+    pub fn save(&self) -> String {
+        self.0.to_base64()
+    }
+
+    // Time played in milliseconds, kept by the client and saved along with the session
+    #[wasm_bindgen(getter)]
+    pub fn elapsed(&self) -> f64 {
+        self.0.elapsed().as_millis() as f64
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_elapsed(&mut self, milliseconds: f64) {
+        self.0.set_elapsed(std::time::Duration::from_millis(milliseconds.max(0.0) as u64));
+    }
+
+    // Whether placing and erasing digits keeps the pencil marks of the cells they see up to date
+    #[wasm_bindgen(getter)]
+    pub fn auto_marks(&self) -> bool {
+        self.0.has_auto_marks()
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_auto_marks(&mut self, enabled: bool) {
+        self.0.auto_marks(enabled);
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn givens(&self) -> Box<[u8]> {
+        Box::from(self.0.givens())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn solution(&self) -> Box<[u8]> {
+        Box::from(self.0.solution())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn board(&self) -> Box<[u8]> {
+        Box::from(self.0.board())
+    }
+
+    pub fn is_given(&self, index: usize) -> bool {
+        self.0.is_given(index)
+    }
+
+    pub fn value(&self, index: usize) -> u8 {
+        self.0.value(index)
+    }
+
+    pub fn marks(&self, index: usize) -> Box<[u8]> {
+        self.0.marks(index).into_boxed_slice()
+    }
+
+    pub fn place(&mut self, index: usize, value: u8) -> Result<(), JsValue> {
+        self.0.place(index, value).map_err(|err| JsValue::from_str(&err.to_string()))
+    }
+
+    pub fn erase(&mut self, index: usize) -> Result<(), JsValue> {
+        self.0.erase(index).map_err(|err| JsValue::from_str(&err.to_string()))
+    }
+
+    pub fn toggle_mark(&mut self, index: usize, value: u8) -> Result<(), JsValue> {
+        self.0.toggle_mark(index, value).map_err(|err| JsValue::from_str(&err.to_string()))
+    }
+
+    pub fn set_marks(&mut self, index: usize, values: &[u8]) -> Result<(), JsValue> {
+        self.0.set_marks(index, values).map_err(|err| JsValue::from_str(&err.to_string()))
+    }
+
+    pub fn undo(&mut self) -> bool {
+        self.0.undo()
+    }
+
+    pub fn redo(&mut self) -> bool {
+        self.0.redo()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn can_undo(&self) -> bool {
+        self.0.can_undo()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn can_redo(&self) -> bool {
+        self.0.can_redo()
+    }
+
+    // Indices of the cells holding mistakes
+    pub fn mistakes(&self) -> Box<[u8]> {
+        self.0.mistakes().into_iter().map(|index| index as u8).collect()
+    }
+
+    // Indices of the cells whose digit repeats within their row, column or box
+    pub fn conflicts(&self) -> Box<[u8]> {
+        self.0.conflicts().into_iter().map(|index| index as u8).collect()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn is_complete(&self) -> bool {
+        self.0.is_complete()
+    }
+
+    pub fn hint(&mut self) -> Option<Hint> {
+        self.0.hint().map(Hint)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn hints(&self) -> usize {
+        self.0.hints()
+    }
+}
+
+#[wasm_bindgen]
+pub struct Hint(crate::grader::Hint);
+
+#[wasm_bindgen]
+impl Hint {
+    #[wasm_bindgen(getter)]
+    pub fn index(&self) -> usize {
+        self.0.index
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn value(&self) -> u8 {
+        self.0.value
+    }
+
+    // The technique's name, ex: "Hidden Single"
+    #[wasm_bindgen(getter)]
+    pub fn technique(&self) -> String {
+        self.0.technique.to_string()
+    }
+}
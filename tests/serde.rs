@@ -0,0 +1,188 @@
+#![cfg(feature = "serde")]
+
+use waffle_iron :: {
+    Sudoku, Solver, Generator, Format, PuzzleRecord, ParseError, CompactPuzzle, CodecError, Theme, PageSize, Game,
+    Layout, Puzzle, Cage, AntiKnight, parse_puzzle, traits :: SudokuState
+};
+use std :: time :: Duration;
+use serde :: { Serialize, de::DeserializeOwned };
+
+const PUZZLE: &str = "309000400200709000087000000750060230600904008028050041000000590000106007006000104";
+
+// Serializes the value, reads it back in and checks that serializing the copy gives the exact same output
+fn round_trip<T: Serialize + DeserializeOwned>(value: &T) -> String {
+    let json = serde_json::to_string(value).unwrap();
+    let copy: T = serde_json::from_str(&json).unwrap();
+    assert_eq!(json, serde_json::to_string(&copy).unwrap());
+    json
+}
+
+#[test]
+fn sudoku_is_a_grid_string() {
+    let state = Sudoku::new(&parse_puzzle(PUZZLE).unwrap()).set(1, 6);
+    let json = round_trip(&state);
+
+    assert_eq!(json, format!("\"{}6{}\"", &PUZZLE[..1], &PUZZLE[2..]));
+
+    let copy: Sudoku = serde_json::from_str(&json).unwrap();
+    assert_eq!(copy.remaining(), state.remaining());
+}
+
+#[test]
+fn grids_accept_any_notation() {
+    let json = format!("\"{}\"", PUZZLE.replace('0', "."));
+    let state: Sudoku = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(round_trip(&state), format!("\"{}\"", PUZZLE));
+    assert!(serde_json::from_str::<Sudoku>("\"123\"").is_err());
+}
+
+#[test]
+fn solver_output() {
+    let output = Solver::with_limit(2).solve(&parse_puzzle(PUZZLE).unwrap());
+    let json = round_trip(&output);
+
+    assert!(json.contains("\"solution\":\"369218475215749863487635912754861239631924758928357641173482596542196387896573124\""));
+    round_trip(&output.result[0]);
+}
+
+#[test]
+fn generator_output() {
+    let output = Generator::new().samples(1).iterations(4).generate();
+    let json = round_trip(&output);
+
+    assert!(json.contains(&format!("\"difficulty\":{}", output.difficulty)));
+}
+
+#[test]
+fn configuration() {
+    assert_eq!(round_trip(&Solver::with_limit(3)), "{\"limit\":3}");
+
+    let json = round_trip(Generator::new().samples(5).iterations(16).removals(2));
+    assert_eq!(json, "{\"samples\":5,\"iterations\":16,\"removals\":2}");
+
+    // Settings are applied through the generator's setters, so the usual limits still hold
+    let generator: Generator = serde_json::from_str("{\"samples\":5,\"iterations\":200,\"removals\":0}").unwrap();
+    assert_eq!(round_trip(&generator), "{\"samples\":5,\"iterations\":64,\"removals\":1}");
+}
+
+#[test]
+fn file_formats() {
+    let mut record = PuzzleRecord::new(parse_puzzle(PUZZLE).unwrap());
+    record.title = Some(String::from("Title"));
+    record.difficulty = Some(String::from("Hard"));
+
+    let json = round_trip(&record);
+    let copy: PuzzleRecord = serde_json::from_str(&json).unwrap();
+    assert_eq!(copy, record);
+
+    assert_eq!(round_trip(&Format::OpenSudoku), "\"OpenSudoku\"");
+}
+
+#[test]
+fn parse_errors() {
+    let error = parse_puzzle("12x").unwrap_err();
+    let copy: ParseError = serde_json::from_str(&round_trip(&error)).unwrap();
+    assert_eq!(copy, error);
+}
+
+#[test]
+fn compact_puzzles_use_base64() {
+    let output = Solver::new().solve(&parse_puzzle(PUZZLE).unwrap());
+    let compact = CompactPuzzle::new(parse_puzzle(PUZZLE).unwrap(), Some(output.result[0].solution));
+
+    let json = round_trip(&compact);
+    assert_eq!(json, format!("\"{}\"", compact.to_base64().unwrap()));
+
+    let copy: CompactPuzzle = serde_json::from_str(&json).unwrap();
+    assert_eq!(copy, compact);
+
+    let error = CompactPuzzle::from_base64("AQ!").unwrap_err();
+    let copy: CodecError = serde_json::from_str(&round_trip(&error)).unwrap();
+    assert_eq!(copy, error);
+}
+
+#[test]
+fn games_keep_their_history() {
+    let mut game = Game::new(&parse_puzzle(PUZZLE).unwrap()).unwrap();
+    game.auto_marks(true);
+    game.set_marks(7, &[1, 5, 6]).unwrap();
+    game.place(1, 6).unwrap();
+    game.place(3, 2).unwrap();
+    game.toggle_mark(3, 8).unwrap();
+    game.undo();
+    game.hint();
+    game.set_elapsed(Duration::from_millis(754_321));
+
+    let json = round_trip(&game);
+    assert_eq!(json, format!("\"{}\"", game.to_base64()));
+
+    let mut copy: Game = serde_json::from_str(&json).unwrap();
+    assert_eq!(copy, game);
+
+    while copy.undo() { }
+    assert_eq!(copy.board(), game.givens());
+    while copy.redo() { }
+    assert_eq!(copy.marks(3), vec![8]);
+    assert_eq!(copy.marks(7), vec![1, 5]);
+
+    // Erasing the 6 puts back the mark it stripped, also after being read back in
+    copy.erase(1).unwrap();
+    assert_eq!(copy.marks(7), vec![1, 5, 6]);
+
+    assert_eq!(Game::from_base64("Aw").unwrap_err(), CodecError::UnsupportedVersion(3));
+}
+
+#[test]
+fn themes() {
+    let theme = Theme::dark();
+    let copy: Theme = serde_json::from_str(&round_trip(&theme)).unwrap();
+    assert_eq!(copy, theme);
+
+    assert_eq!(round_trip(&PageSize::Letter), "\"Letter\"");
+}
+
+#[test]
+fn puzzles_keep_their_layout() {
+    let layout = Layout::new(3, 2).unwrap();
+    let puzzle = Puzzle::parse(&layout, "..4 5.. 6.5 ... ..2 .3. 4.. 1.5 .6. 3.. ... ...").unwrap();
+    let json = round_trip(&puzzle);
+
+    assert_eq!(json, "{\"layout\":{\"box_width\":3,\"box_height\":2},\"cells\":\"004500605000002030400105060300000000\"}");
+    assert_eq!(serde_json::from_str::<Puzzle>(&json).unwrap(), puzzle);
+
+    assert!(serde_json::from_str::<Layout>("{\"box_width\":1,\"box_height\":1}").is_err());
+
+    let diagonal = Layout::with_diagonals(3, 3).unwrap();
+    assert_eq!(round_trip(&diagonal), "{\"box_width\":3,\"box_height\":3,\"diagonals\":true}");
+    assert_ne!(serde_json::from_str::<Layout>(&round_trip(&diagonal)).unwrap(), Layout::standard());
+
+    // Jigsaw layouts are stored as their region map
+    let jigsaw = Layout::jigsaw(&[1, 1, 1, 2, 1, 3, 2, 2, 3, 3, 4, 2, 3, 4, 4, 4]).unwrap();
+    let json = round_trip(&jigsaw);
+    assert_eq!(json, "{\"regions\":[0,0,0,1,0,2,1,1,2,2,3,1,2,3,3,3]}");
+    assert_eq!(serde_json::from_str::<Layout>(&json).unwrap(), jigsaw);
+    assert!(serde_json::from_str::<Layout>("{\"regions\":[0,1,1,0]}").is_err());
+    assert!(serde_json::from_str::<Puzzle>(&json.replace("45", "47")).is_err());
+
+    let hyper = Layout::standard().with_windows().unwrap();
+    assert_eq!(round_trip(&hyper), "{\"box_width\":3,\"box_height\":3,\"windows\":true}");
+    assert_eq!(serde_json::from_str::<Layout>(&round_trip(&hyper)).unwrap(), hyper);
+
+    // Overlapping layouts are stored as the position of each of their grids
+    let samurai = Layout::samurai();
+    let json = round_trip(&samurai);
+    assert_eq!(json, "{\"box_width\":3,\"box_height\":3,\"grids\":[[0,0],[0,12],[6,6],[12,0],[12,12]]}");
+    assert_eq!(serde_json::from_str::<Layout>(&json).unwrap(), samurai);
+    assert_eq!(serde_json::from_str::<Puzzle>(&round_trip(&Puzzle::empty(&samurai))).unwrap().cells().len(), 369);
+
+    // Killer Sudoku cages are listed after the rest of the layout
+    let killer = Layout::new(2, 2).unwrap().with_cages(&[Cage::new(3, &[0, 1]), Cage::new(7, &[2, 6])]).unwrap();
+    let json = round_trip(&killer);
+    assert_eq!(json, "{\"box_width\":2,\"box_height\":2,\"cages\":[{\"sum\":3,\"cells\":[0,1]},{\"sum\":7,\"cells\":[2,6]}]}");
+    assert_eq!(serde_json::from_str::<Layout>(&json).unwrap(), killer);
+    assert!(serde_json::from_str::<Layout>(&json.replace("\"sum\":3", "\"sum\":2")).is_err());
+
+    // Constraints could come from outside of the library, so there's no telling how to write them out
+    assert!(serde_json::to_string(&Layout::standard().with_constraint(AntiKnight)).is_err());
+}